@@ -0,0 +1,167 @@
+//! Board operations behind AI search and bulk simulation, measured against
+//! the per-cell grid the board used before it kept row bit masks. The
+//! grid's `clear_lines` only counts rows; the board also hands back the
+//! cleared rows for the line-clear animation.
+//!
+//! Run with `cargo bench --no-default-features --bench board`.
+
+use criterion::{BatchSize, Criterion, black_box, criterion_group, criterion_main};
+use gpui_tetris::game::board::{BOARD_HEIGHT, BOARD_WIDTH, Board, Cell};
+use gpui_tetris::game::pieces::{Rotation, Tetromino, TetrominoType};
+
+const ROTATIONS: [Rotation; 4] = [
+    Rotation::North,
+    Rotation::East,
+    Rotation::South,
+    Rotation::West,
+];
+
+/// A ragged stack, ten rows deep with one or two holes per row.
+fn messy_board() -> Board {
+    let mut board = Board::new();
+    let mut seed = 0x2545_f491_u32;
+    for y in BOARD_HEIGHT - 10..BOARD_HEIGHT {
+        seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+        let holes = [
+            (seed >> 8) as usize % BOARD_WIDTH,
+            (seed >> 20) as usize % BOARD_WIDTH,
+        ];
+        for x in (0..BOARD_WIDTH).filter(|x| !holes.contains(x)) {
+            board.set_cell(x, y, Cell::piece(TetrominoType::ALL[x % 7]));
+        }
+    }
+    board
+}
+
+/// The cell grid the board stored before: every lookup is a bounds check
+/// and a nested `Vec` index.
+struct CellGrid {
+    cells: Vec<Vec<Cell>>,
+}
+
+impl CellGrid {
+    fn from_board(board: &Board) -> Self {
+        Self {
+            cells: board.cells().to_vec(),
+        }
+    }
+
+    fn is_occupied(&self, x: i32, y: i32) -> bool {
+        if x < 0 || y < 0 || y as usize >= self.cells.len() || x as usize >= BOARD_WIDTH {
+            return true;
+        }
+        self.cells[y as usize][x as usize].filled
+    }
+
+    fn can_place(&self, piece: &Tetromino, x: i32, y: i32, rotation: Rotation) -> bool {
+        piece
+            .blocks(rotation)
+            .iter()
+            .all(|&(dx, dy)| !self.is_occupied(x + dx, y + dy))
+    }
+
+    fn clear_lines(&mut self) -> usize {
+        let height = self.cells.len();
+        self.cells.retain(|row| !row.iter().all(|cell| cell.filled));
+        let cleared = height - self.cells.len();
+        self.cells.splice(
+            0..0,
+            (0..cleared).map(|_| vec![Cell::default(); BOARD_WIDTH]),
+        );
+        cleared
+    }
+
+    fn column_heights(&self) -> Vec<usize> {
+        (0..BOARD_WIDTH)
+            .map(|x| {
+                (0..self.cells.len())
+                    .find(|&y| self.cells[y][x].filled)
+                    .map_or(0, |y| self.cells.len() - y)
+            })
+            .collect()
+    }
+}
+
+/// Drops every piece in every rotation from every column, the way a bot
+/// enumerates placements, and returns how many it found.
+fn search(can_place: impl Fn(&Tetromino, i32, i32, Rotation) -> bool) -> usize {
+    let mut placements = 0;
+    for kind in TetrominoType::ALL {
+        let piece = Tetromino::new(kind, 0, 0);
+        for rotation in ROTATIONS {
+            for x in -2..BOARD_WIDTH as i32 {
+                let mut y = 0;
+                if !can_place(&piece, x, y, rotation) {
+                    continue;
+                }
+                while can_place(&piece, x, y + 1, rotation) {
+                    y += 1;
+                }
+                placements += 1;
+            }
+        }
+    }
+    placements
+}
+
+fn placement_search(c: &mut Criterion) {
+    let board = messy_board();
+    let grid = CellGrid::from_board(&board);
+    let mut group = c.benchmark_group("placement_search");
+    group.bench_function("bitboard", |b| {
+        b.iter(|| {
+            search(|piece, x, y, rotation| black_box(&board).can_place(piece, x, y, rotation))
+        })
+    });
+    group.bench_function("cell_grid", |b| {
+        b.iter(|| search(|piece, x, y, rotation| black_box(&grid).can_place(piece, x, y, rotation)))
+    });
+    group.finish();
+}
+
+/// Four full rows under a ragged stack, as after a tetris locks.
+fn tetris_board() -> Board {
+    let mut board = messy_board();
+    for y in BOARD_HEIGHT - 4..BOARD_HEIGHT {
+        for x in 0..BOARD_WIDTH {
+            board.set_cell(x, y, Cell::piece(TetrominoType::I));
+        }
+    }
+    board
+}
+
+fn clear_lines(c: &mut Criterion) {
+    let mut group = c.benchmark_group("clear_lines");
+    for (name, board) in [("tetris", tetris_board()), ("none", messy_board())] {
+        let grid = CellGrid::from_board(&board);
+        group.bench_function(format!("{name}/bitboard"), |b| {
+            b.iter_batched_ref(|| board.clone(), Board::clear_lines, BatchSize::SmallInput)
+        });
+        group.bench_function(format!("{name}/cell_grid"), |b| {
+            b.iter_batched_ref(
+                || CellGrid {
+                    cells: grid.cells.clone(),
+                },
+                CellGrid::clear_lines,
+                BatchSize::SmallInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+fn column_heights(c: &mut Criterion) {
+    let board = messy_board();
+    let grid = CellGrid::from_board(&board);
+    let mut group = c.benchmark_group("column_heights");
+    group.bench_function("bitboard", |b| {
+        b.iter(|| black_box(&board).column_heights())
+    });
+    group.bench_function("cell_grid", |b| {
+        b.iter(|| black_box(&grid).column_heights())
+    });
+    group.finish();
+}
+
+criterion_group!(benches, placement_search, clear_lines, column_heights);
+criterion_main!(benches);
@@ -0,0 +1,102 @@
+//! Plays a batch of seeded games without a window using a greedy bot.
+//!
+//! ```bash
+//! cargo run --release --no-default-features --example headless -- 200
+//! ```
+
+use gpui_tetris::game::env::{Env, EnvAction, Observation};
+use gpui_tetris::game::state::GameConfig;
+use std::time::Instant;
+
+const MAX_PIECES: u32 = 1_000;
+
+struct GameSummary {
+    score: u32,
+    lines: u32,
+    pieces: u32,
+}
+
+fn main() {
+    let games = std::env::args()
+        .nth(1)
+        .and_then(|arg| arg.parse::<u64>().ok())
+        .unwrap_or(100);
+
+    let started = Instant::now();
+    let mut env = Env::new(GameConfig::default());
+    let results: Vec<GameSummary> = (0..games).map(|seed| play(&mut env, seed)).collect();
+    let elapsed = started.elapsed();
+
+    let pieces: u32 = results.iter().map(|game| game.pieces).sum();
+    let scores: Vec<u32> = results.iter().map(|game| game.score).collect();
+    let lines: Vec<u32> = results.iter().map(|game| game.lines).collect();
+    println!("games:  {games}");
+    println!(
+        "pieces: {pieces} ({:.0}/s)",
+        pieces as f64 / elapsed.as_secs_f64()
+    );
+    print_stats("score", &scores);
+    print_stats("lines", &lines);
+}
+
+fn play(env: &mut Env, seed: u64) -> GameSummary {
+    env.reset(seed);
+    let mut pieces = 0;
+    while !env.is_done() && pieces < MAX_PIECES {
+        let best = env
+            .placements()
+            .into_iter()
+            .filter_map(|placement| {
+                let mut probe = env.clone();
+                let result = probe.step(EnvAction::Place(placement)).ok()?;
+                let value = evaluate(&result.observation) + 0.76 * result.lines_cleared as f64;
+                Some((placement, if result.done { f64::MIN } else { value }))
+            })
+            .max_by(|a, b| a.1.total_cmp(&b.1));
+        let Some((placement, _)) = best else {
+            break;
+        };
+        env.step(EnvAction::Place(placement))
+            .expect("placement came from Env::placements");
+        pieces += 1;
+    }
+
+    let state = env.state();
+    GameSummary {
+        score: state.score,
+        lines: state.lines,
+        pieces,
+    }
+}
+
+/// Classic hand-tuned heuristic: low, flat stacks without holes.
+fn evaluate(observation: &Observation) -> f64 {
+    let board = &observation.board;
+    let rows = board.len();
+    let mut heights = vec![0usize; board.first().map_or(0, Vec::len)];
+    let mut holes = 0;
+    for (x, height) in heights.iter_mut().enumerate() {
+        let top = (0..rows).find(|&y| board[y][x]);
+        if let Some(top) = top {
+            *height = rows - top;
+            holes += (top..rows).filter(|&y| !board[y][x]).count();
+        }
+    }
+    let aggregate: usize = heights.iter().sum();
+    let bumpiness: usize = heights
+        .windows(2)
+        .map(|pair| pair[0].abs_diff(pair[1]))
+        .sum();
+
+    -0.51 * aggregate as f64 - 0.36 * holes as f64 - 0.18 * bumpiness as f64
+}
+
+fn print_stats(label: &str, values: &[u32]) {
+    if values.is_empty() {
+        return;
+    }
+    let min = values.iter().min().copied().unwrap_or(0);
+    let max = values.iter().max().copied().unwrap_or(0);
+    let mean = values.iter().map(|&value| value as f64).sum::<f64>() / values.len() as f64;
+    println!("{label:<7} mean {mean:.1}  min {min}  max {max}");
+}
@@ -411,6 +411,15 @@ fn sound_spec(event: &SoundEvent) -> SoundSpec {
             key: "line_clear_4",
             gain: 0.9,
         },
+        // No dedicated sample yet; a louder tetris clear stands in.
+        SoundEvent::PerfectClear => SoundSpec {
+            key: "line_clear_4",
+            gain: 1.0,
+        },
+        SoundEvent::CountdownTick => SoundSpec {
+            key: "move",
+            gain: 0.6,
+        },
         SoundEvent::GameOver => SoundSpec {
             key: "game_over",
             gain: 0.8,
@@ -1,17 +1,164 @@
-use crate::game::pieces::{Rotation, Tetromino};
+use std::ops::{Deref, DerefMut};
+
+use crate::game::pieces::{Rotation, Tetromino, TetrominoType};
+
+mod ascii;
+
+pub use ascii::{AsciiError, ascii_diff};
 
 pub const BOARD_WIDTH: usize = 10;
-pub const BOARD_HEIGHT: usize = 20;
+/// Rows shown to the player, at the bottom of the board.
+pub const VISIBLE_HEIGHT: usize = 20;
+/// Hidden rows above the visible field (the vanish zone). Pieces spawn here.
+pub const BUFFER_HEIGHT: usize = 20;
+pub const BOARD_HEIGHT: usize = BUFFER_HEIGHT + VISIBLE_HEIGHT;
+
+pub const MIN_BOARD_WIDTH: usize = 4;
+pub const MAX_BOARD_WIDTH: usize = 32;
+pub const MIN_BOARD_HEIGHT: usize = 4;
+pub const MAX_BOARD_HEIGHT: usize = 64;
+
+/// Playfield dimensions. `height` includes the hidden rows above the
+/// `visible_height` rows the player sees.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BoardSize {
+    pub width: usize,
+    pub height: usize,
+    pub visible_height: usize,
+}
+
+impl Default for BoardSize {
+    fn default() -> Self {
+        Self {
+            width: BOARD_WIDTH,
+            height: BOARD_HEIGHT,
+            visible_height: VISIBLE_HEIGHT,
+        }
+    }
+}
+
+impl BoardSize {
+    /// A field of `width` x `visible_height` with the standard vanish zone on top.
+    pub fn with_buffer(width: usize, visible_height: usize) -> Self {
+        Self {
+            width,
+            height: visible_height + BUFFER_HEIGHT,
+            visible_height,
+        }
+    }
+
+    pub fn buffer_height(&self) -> usize {
+        self.height - self.visible_height
+    }
+
+    pub fn is_valid(&self) -> bool {
+        (MIN_BOARD_WIDTH..=MAX_BOARD_WIDTH).contains(&self.width)
+            && (MIN_BOARD_HEIGHT..=MAX_BOARD_HEIGHT).contains(&self.height)
+            && (1..=self.height).contains(&self.visible_height)
+    }
+
+    /// Pulls each dimension into the supported range.
+    pub fn clamped(self) -> Self {
+        let width = self.width.clamp(MIN_BOARD_WIDTH, MAX_BOARD_WIDTH);
+        let height = self.height.clamp(MIN_BOARD_HEIGHT, MAX_BOARD_HEIGHT);
+        Self {
+            width,
+            height,
+            visible_height: self.visible_height.clamp(1, height),
+        }
+    }
+
+    /// Spawn box origin: centred (rounding left) with the piece in the two
+    /// rows just above the visible field, or at the top without a vanish zone.
+    pub fn spawn_position(&self) -> (i32, i32) {
+        let x = (self.width as i32 - 4) / 2;
+        let y = self.buffer_height().saturating_sub(2) as i32;
+        (x, y)
+    }
+}
 
 #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
 pub struct Cell {
     pub filled: bool,
-    pub kind: Option<crate::game::pieces::TetrominoType>,
+    pub kind: Option<TetrominoType>,
+    /// Pushed up from below rather than placed by a piece.
+    pub garbage: bool,
 }
 
+impl Cell {
+    pub const GARBAGE: Cell = Cell {
+        filled: true,
+        kind: None,
+        garbage: true,
+    };
+
+    /// A cell locked by a piece of `kind`.
+    pub fn piece(kind: TetrominoType) -> Self {
+        Self {
+            filled: true,
+            kind: Some(kind),
+            garbage: false,
+        }
+    }
+}
+
+/// Rows are indexed from the top of the vanish zone, `y` growing downwards.
+/// Occupancy is kept as one bit mask per row, bit `x` for column `x`, so
+/// collision, locking and line clears are a few word operations; `cells`
+/// holds the piece colours and garbage flags alongside for rendering.
+/// Writing `cells` directly is allowed; the masks are rebuilt from it before
+/// the next change made through `Board`.
 #[derive(Clone, Debug)]
 pub struct Board {
-    pub cells: [[Cell; BOARD_WIDTH]; BOARD_HEIGHT],
+    rows: Vec<u32>,
+    pub cells: CellGrid,
+    size: BoardSize,
+}
+
+impl PartialEq for Board {
+    fn eq(&self, other: &Self) -> bool {
+        self.size == other.size && self.cells == other.cells
+    }
+}
+
+impl Eq for Board {}
+
+/// The cell layer of a `Board`, indexed `cells[y][x]`. Mutable access marks
+/// the board's row masks stale.
+#[derive(Clone, Debug, Default)]
+pub struct CellGrid {
+    rows: Vec<Vec<Cell>>,
+    stale: bool,
+}
+
+impl Deref for CellGrid {
+    type Target = [Vec<Cell>];
+
+    fn deref(&self) -> &Self::Target {
+        &self.rows
+    }
+}
+
+impl DerefMut for CellGrid {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.stale = true;
+        &mut self.rows
+    }
+}
+
+impl PartialEq for CellGrid {
+    fn eq(&self, other: &Self) -> bool {
+        self.rows == other.rows
+    }
+}
+
+impl Eq for CellGrid {}
+
+fn mask_of(row: &[Cell]) -> u32 {
+    row.iter()
+        .enumerate()
+        .filter(|(_, cell)| cell.filled)
+        .fold(0, |mask, (x, _)| mask | 1 << x)
 }
 
 impl Default for Board {
@@ -22,31 +169,126 @@ impl Default for Board {
 
 impl Board {
     pub fn new() -> Self {
+        Self::with_size(BoardSize::default())
+    }
+
+    /// Panics if `size` is wider than `MAX_BOARD_WIDTH`, the bits in a row mask.
+    pub fn with_size(size: BoardSize) -> Self {
+        assert!(
+            size.width <= MAX_BOARD_WIDTH,
+            "board is wider than a row mask"
+        );
         Self {
-            cells: [[Cell::default(); BOARD_WIDTH]; BOARD_HEIGHT],
+            rows: vec![0; size.height],
+            cells: CellGrid {
+                rows: vec![vec![Cell::default(); size.width]; size.height],
+                stale: false,
+            },
+            size,
+        }
+    }
+
+    pub fn size(&self) -> BoardSize {
+        self.size
+    }
+
+    pub fn width(&self) -> usize {
+        self.size.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.size.height
+    }
+
+    pub fn spawn_position(&self) -> (i32, i32) {
+        self.size.spawn_position()
+    }
+
+    /// Rows of cells from the top of the vanish zone.
+    pub fn cells(&self) -> &[Vec<Cell>] {
+        &self.cells
+    }
+
+    pub fn cell(&self, x: usize, y: usize) -> Cell {
+        self.cells[y][x]
+    }
+
+    pub fn set_cell(&mut self, x: usize, y: usize, cell: Cell) {
+        self.sync_rows();
+        self.cells.rows[y][x] = cell;
+        if cell.filled {
+            self.rows[y] |= 1 << x;
+        } else {
+            self.rows[y] &= !(1 << x);
+        }
+    }
+
+    /// Filled columns of row `y`, bit `x` for column `x`.
+    pub fn row_mask(&self, y: usize) -> u32 {
+        if self.cells.stale {
+            mask_of(&self.cells[y])
+        } else {
+            self.rows[y]
+        }
+    }
+
+    /// Rebuilds the row masks after `cells` was written directly.
+    fn sync_rows(&mut self) {
+        if self.cells.stale {
+            self.rows = self.cells.rows.iter().map(|row| mask_of(row)).collect();
+            self.cells.stale = false;
         }
     }
 
+    /// The mask of a row with every column filled.
+    pub fn full_row_mask(&self) -> u32 {
+        u32::MAX >> (u32::BITS as usize - self.size.width)
+    }
+
+    /// Rows from the highest filled cell in column `x` down to the floor.
+    pub fn column_height(&self, x: usize) -> usize {
+        let bit = 1 << x;
+        (0..self.size.height)
+            .position(|y| self.row_mask(y) & bit != 0)
+            .map_or(0, |y| self.size.height - y)
+    }
+
+    /// `column_height` for every column, found in one pass down the rows.
+    pub fn column_heights(&self) -> Vec<usize> {
+        let mut heights = vec![0; self.size.width];
+        let mut seen = 0u32;
+        for y in 0..self.size.height {
+            let row = self.row_mask(y);
+            let mut new = row & !seen;
+            seen |= row;
+            while new != 0 {
+                heights[new.trailing_zeros() as usize] = self.size.height - y;
+                new &= new - 1;
+            }
+        }
+        heights
+    }
+
     pub fn is_inside(&self, x: i32, y: i32) -> bool {
-        x >= 0 && x < BOARD_WIDTH as i32 && y >= 0 && y < BOARD_HEIGHT as i32
+        x >= 0 && x < self.size.width as i32 && y >= 0 && y < self.size.height as i32
     }
 
     pub fn is_occupied(&self, x: i32, y: i32) -> bool {
         if !self.is_inside(x, y) {
             return true;
         }
-        self.cells[y as usize][x as usize].filled
+        self.row_mask(y as usize) & (1 << x) != 0
     }
 
     pub fn can_place(&self, piece: &Tetromino, x: i32, y: i32, rotation: Rotation) -> bool {
-        for (dx, dy) in piece.blocks(rotation) {
-            let nx = x + dx;
-            let ny = y + dy;
-            if self.is_occupied(nx, ny) {
-                return false;
-            }
-        }
-        true
+        piece.blocks(rotation).iter().all(|&(dx, dy)| {
+            let (nx, ny) = (x + dx, y + dy);
+            // Negative coordinates wrap past the size, so one compare each
+            // covers both edges.
+            (nx as u32) < self.size.width as u32
+                && (ny as u32) < self.size.height as u32
+                && self.row_mask(ny as usize) & (1 << nx) == 0
+        })
     }
 
     pub fn lock_piece(&mut self, piece: &Tetromino) {
@@ -54,35 +296,102 @@ impl Board {
             let nx = piece.x + dx;
             let ny = piece.y + dy;
             if self.is_inside(nx, ny) {
-                let cell = &mut self.cells[ny as usize][nx as usize];
-                cell.filled = true;
-                cell.kind = Some(piece.kind);
+                self.set_cell(nx as usize, ny as usize, Cell::piece(piece.kind));
             }
         }
     }
 
-    pub fn clear_lines(&mut self) -> usize {
-        let mut cleared = 0;
-        let mut write_row = BOARD_HEIGHT as i32 - 1;
+    /// Whether row `y` lies in the vanish zone above the visible field.
+    pub fn is_hidden_row(&self, y: i32) -> bool {
+        y < self.size.buffer_height() as i32
+    }
 
-        for read_row in (0..BOARD_HEIGHT as i32).rev() {
-            let full =
-                (0..BOARD_WIDTH as i32).all(|x| self.cells[read_row as usize][x as usize].filled);
+    pub fn is_empty(&self) -> bool {
+        (0..self.size.height).all(|y| self.row_mask(y) == 0)
+    }
 
-            if full {
-                cleared += 1;
+    /// Rows that still hold at least one garbage cell.
+    pub fn garbage_rows(&self) -> usize {
+        self.cells
+            .iter()
+            .filter(|row| row.iter().any(|cell| cell.garbage))
+            .count()
+    }
+
+    /// Pushes the stack up by `rows` and fills the bottom with garbage rows
+    /// open at `hole_column`, which must lie inside the board. Returns false
+    /// if filled cells were pushed off the top of the board.
+    pub fn insert_garbage(&mut self, rows: usize, hole_column: usize) -> bool {
+        debug_assert!(
+            hole_column < self.size.width,
+            "garbage hole column {hole_column} is off the board"
+        );
+        self.sync_rows();
+        let rows = rows.min(self.size.height);
+        let overflow = self.rows[..rows].iter().any(|&row| row != 0);
+        self.rows.drain(..rows);
+        self.cells.rows.drain(..rows);
+
+        let mut row = vec![Cell::GARBAGE; self.size.width];
+        let mut mask = self.full_row_mask();
+        row[hole_column] = Cell::default();
+        mask &= !(1 << hole_column);
+        self.rows.extend((0..rows).map(|_| mask));
+        self.cells.rows.extend((0..rows).map(|_| row.clone()));
+        !overflow
+    }
+
+    /// Removes full rows, drops everything above them and returns the
+    /// removed rows top to bottom.
+    pub fn clear_lines(&mut self) -> Vec<ClearedRow> {
+        self.sync_rows();
+        let full = self.full_row_mask();
+        if !self.rows.contains(&full) {
+            return Vec::new();
+        }
+        // Compact the kept rows downwards, moving the cleared ones out.
+        let mut cleared = Vec::new();
+        let mut write = self.size.height;
+        for read in (0..self.size.height).rev() {
+            if self.rows[read] == full {
+                cleared.push(ClearedRow {
+                    y: read,
+                    cells: std::mem::take(&mut self.cells.rows[read]),
+                });
             } else {
-                if write_row != read_row {
-                    self.cells[write_row as usize] = self.cells[read_row as usize];
-                }
-                write_row -= 1;
+                write -= 1;
+                self.rows[write] = self.rows[read];
+                self.cells.rows.swap(write, read);
             }
         }
-
-        for y in 0..=write_row {
-            self.cells[y as usize] = [Cell::default(); BOARD_WIDTH];
+        for y in 0..write {
+            self.rows[y] = 0;
+            self.cells.rows[y].clear();
+            self.cells.rows[y].resize(self.size.width, Cell::default());
         }
-
+        cleared.reverse();
         cleared
     }
+
+    /// Undoes `clear_lines`: puts `rows` back where they were and lifts the
+    /// stack above them, dropping the empty rows the clear added on top.
+    pub fn restore_lines(&mut self, rows: &[ClearedRow]) {
+        self.sync_rows();
+        let removed = rows.len().min(self.rows.len());
+        self.rows.drain(..removed);
+        self.cells.rows.drain(..removed);
+        for row in rows {
+            let y = row.y.min(self.rows.len());
+            self.rows.insert(y, mask_of(&row.cells));
+            self.cells.rows.insert(y, row.cells.clone());
+        }
+    }
+}
+
+/// A full row taken by `Board::clear_lines`, as it was before the clear.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ClearedRow {
+    /// Index before the clear, counted from the top like `Board::cells`.
+    pub y: usize,
+    pub cells: Vec<Cell>,
 }
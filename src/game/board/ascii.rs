@@ -0,0 +1,176 @@
+use std::fmt;
+
+use super::{Board, BoardSize, Cell, MAX_BOARD_WIDTH, MIN_BOARD_WIDTH, VISIBLE_HEIGHT};
+use crate::game::pieces::TetrominoType;
+
+const EMPTY: char = '.';
+const GARBAGE: char = 'G';
+/// A filled cell with no piece colour, as old saves stored them.
+const PLAIN: char = '#';
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AsciiError {
+    /// Rows are counted from the first non-blank line.
+    RaggedRow {
+        row: usize,
+        width: usize,
+    },
+    InvalidCell {
+        row: usize,
+        column: usize,
+        found: char,
+    },
+    InvalidWidth(usize),
+    TooManyRows {
+        rows: usize,
+        height: usize,
+    },
+}
+
+impl fmt::Display for AsciiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AsciiError::RaggedRow { row, width } => {
+                write!(f, "row {row} is not {width} cells wide")
+            }
+            AsciiError::InvalidCell { row, column, found } => {
+                write!(f, "unknown cell {found:?} at row {row}, column {column}")
+            }
+            AsciiError::InvalidWidth(width) => write!(f, "boards cannot be {width} cells wide"),
+            AsciiError::TooManyRows { rows, height } => {
+                write!(f, "{rows} rows do not fit a board {height} rows tall")
+            }
+        }
+    }
+}
+
+impl std::error::Error for AsciiError {}
+
+impl Board {
+    /// Parses rows written top to bottom, one line each: `.` is empty, a
+    /// piece letter is a cell of that colour and `G` is garbage. The rows
+    /// sit at the bottom of a standard-height board as wide as the lines;
+    /// surrounding whitespace and blank lines are ignored.
+    pub fn from_ascii(text: &str) -> Result<Board, AsciiError> {
+        let width = ascii_rows(text)
+            .next()
+            .map_or(BoardSize::default().width, str::len);
+        if !(MIN_BOARD_WIDTH..=MAX_BOARD_WIDTH).contains(&width) {
+            return Err(AsciiError::InvalidWidth(width));
+        }
+        Self::from_ascii_sized(BoardSize::with_buffer(width, VISIBLE_HEIGHT), text)
+    }
+
+    /// `from_ascii` onto a board of `size`, whose width the rows must match.
+    pub fn from_ascii_sized(size: BoardSize, text: &str) -> Result<Board, AsciiError> {
+        let rows: Vec<&str> = ascii_rows(text).collect();
+        if rows.len() > size.height {
+            return Err(AsciiError::TooManyRows {
+                rows: rows.len(),
+                height: size.height,
+            });
+        }
+        let mut board = Board::with_size(size);
+        let top = size.height - rows.len();
+        for (row, line) in rows.iter().enumerate() {
+            if line.chars().count() != size.width {
+                return Err(AsciiError::RaggedRow {
+                    row,
+                    width: size.width,
+                });
+            }
+            for (column, found) in line.chars().enumerate() {
+                let cell =
+                    parse_cell(found).ok_or(AsciiError::InvalidCell { row, column, found })?;
+                board.set_cell(column, top + row, cell);
+            }
+        }
+        Ok(board)
+    }
+
+    /// The rows from the highest filled cell down to the floor, in the
+    /// `from_ascii` format. An empty board gives an empty string.
+    pub fn to_ascii(&self) -> String {
+        let top = self
+            .rows
+            .iter()
+            .position(|&row| row != 0)
+            .unwrap_or(self.size.height);
+        self.cells[top..]
+            .iter()
+            .map(|row| row.iter().map(cell_char).collect::<String>())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+fn ascii_rows(text: &str) -> impl Iterator<Item = &str> {
+    text.lines().map(str::trim).filter(|line| !line.is_empty())
+}
+
+fn parse_cell(found: char) -> Option<Cell> {
+    match found {
+        EMPTY => Some(Cell::default()),
+        GARBAGE => Some(Cell::GARBAGE),
+        PLAIN => Some(Cell {
+            filled: true,
+            ..Cell::default()
+        }),
+        letter => TetrominoType::from_letter(letter).map(Cell::piece),
+    }
+}
+
+fn cell_char(cell: &Cell) -> char {
+    if !cell.filled {
+        EMPTY
+    } else if cell.garbage {
+        GARBAGE
+    } else {
+        cell.kind.map_or(PLAIN, TetrominoType::letter)
+    }
+}
+
+/// Side-by-side listing of `actual` against `expected`, bottom rows lined
+/// up, or `None` when they match. Empty rows on top of `expected` are
+/// ignored. Used by `assert_board_eq!`.
+pub fn ascii_diff(actual: &Board, expected: &str) -> Option<String> {
+    let actual = actual.to_ascii();
+    let actual: Vec<&str> = ascii_rows(&actual).collect();
+    let expected: Vec<&str> = ascii_rows(expected)
+        .skip_while(|line| line.chars().all(|found| found == EMPTY))
+        .collect();
+    if actual == expected {
+        return None;
+    }
+
+    let rows = actual.len().max(expected.len());
+    let pad = |lines: &[&str], index: usize| -> String {
+        index
+            .checked_sub(rows - lines.len())
+            .map_or(String::new(), |index| lines[index].to_string())
+    };
+    let width = expected
+        .iter()
+        .map(|line| line.len())
+        .max()
+        .unwrap_or(0)
+        .max(8);
+    let mut out = format!("  {:<width$}   actual\n", "expected");
+    for index in 0..rows {
+        let (left, right) = (pad(&expected, index), pad(&actual, index));
+        let marker = if left == right { ' ' } else { '>' };
+        out.push_str(&format!("{marker} {left:<width$}   {right}\n"));
+    }
+    Some(out)
+}
+
+/// Asserts that a board matches rows in the `Board::from_ascii` format,
+/// printing both side by side when they differ.
+#[macro_export]
+macro_rules! assert_board_eq {
+    ($board:expr, $expected:expr $(,)?) => {
+        if let Some(diff) = $crate::game::board::ascii_diff(&$board, $expected) {
+            panic!("boards differ:\n{diff}");
+        }
+    };
+}
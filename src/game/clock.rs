@@ -0,0 +1,41 @@
+use std::time::Duration;
+
+/// Turns variable frame times into a whole number of fixed simulation steps,
+/// carrying the remainder over to the next frame.
+#[derive(Clone, Copy, Debug)]
+pub struct FixedTimestep {
+    step: Duration,
+    accumulator: Duration,
+}
+
+impl FixedTimestep {
+    /// Longest stretch simulated in one frame; anything beyond is dropped so a
+    /// stalled window does not fast-forward the game when it wakes up.
+    pub const MAX_CATCH_UP: Duration = Duration::from_millis(250);
+
+    pub fn new(step_ms: u64) -> Self {
+        Self {
+            step: Duration::from_millis(step_ms.max(1)),
+            accumulator: Duration::ZERO,
+        }
+    }
+
+    pub fn step_ms(&self) -> u64 {
+        self.step.as_millis() as u64
+    }
+
+    /// Adds `elapsed` to the accumulator and returns how many steps to run.
+    pub fn advance(&mut self, elapsed: Duration) -> u32 {
+        self.accumulator = (self.accumulator + elapsed).min(Self::MAX_CATCH_UP);
+        let mut steps = 0;
+        while self.accumulator >= self.step {
+            self.accumulator -= self.step;
+            steps += 1;
+        }
+        steps
+    }
+
+    pub fn reset(&mut self) {
+        self.accumulator = Duration::ZERO;
+    }
+}
@@ -0,0 +1,242 @@
+use std::fmt;
+
+use crate::game::input::GameAction;
+use crate::game::pieces::{Rotation, Tetromino, TetrominoType};
+use crate::game::state::{GameConfig, GameState};
+
+/// Final resting spot for the active piece, reached by an optional hold,
+/// clockwise rotations, sideways moves and a hard drop.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Placement {
+    pub hold: bool,
+    pub rotation: Rotation,
+    pub x: i32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EnvAction {
+    /// Let one simulation step pass without input.
+    Idle,
+    /// Apply one input, then run one simulation step.
+    Input(GameAction),
+    /// Move the active piece to a placement and hard drop it.
+    Place(Placement),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EnvError {
+    UnreachablePlacement,
+    /// `Place` was sent while the game is paused.
+    Paused,
+    /// `Place` was sent while no piece is in play, or the next piece never
+    /// spawned.
+    SpawnPending,
+}
+
+impl fmt::Display for EnvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EnvError::UnreachablePlacement => write!(f, "placement cannot be reached"),
+            EnvError::Paused => write!(f, "game is paused"),
+            EnvError::SpawnPending => write!(f, "no piece is in play"),
+        }
+    }
+}
+
+impl std::error::Error for EnvError {}
+
+/// Upper bound on the steps `Place` runs to get the next piece into play.
+const MAX_SPAWN_STEPS: u32 = 100_000;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Observation {
+    /// Filled cells as `board[y][x]`, vanish zone included.
+    pub board: Vec<Vec<bool>>,
+    pub active: Tetromino,
+    pub queue: Vec<TetrominoType>,
+    pub hold: Option<TetrominoType>,
+    pub can_hold: bool,
+    pub score: u32,
+    pub level: u32,
+    pub lines: u32,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct StepResult {
+    pub observation: Observation,
+    /// Score gained by this step.
+    pub reward: f64,
+    pub lines_cleared: u32,
+    pub done: bool,
+}
+
+/// Windowless wrapper around `GameState` for bots and batch runs.
+#[derive(Clone, Debug)]
+pub struct Env {
+    config: GameConfig,
+    state: GameState,
+}
+
+impl Env {
+    pub fn new(config: GameConfig) -> Self {
+        Self {
+            config,
+            state: GameState::new(0, config),
+        }
+    }
+
+    pub fn reset(&mut self, seed: u64) -> Observation {
+        self.state = GameState::new(seed, self.config);
+        self.observe()
+    }
+
+    pub fn state(&self) -> &GameState {
+        &self.state
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.state.game_over
+    }
+
+    pub fn step(&mut self, action: EnvAction) -> Result<StepResult, EnvError> {
+        let score = self.state.score;
+        let lines = self.state.lines;
+
+        match action {
+            EnvAction::Idle => self.state.step(false),
+            EnvAction::Input(action) => {
+                self.state.apply_action(action);
+                self.state.step(false);
+            }
+            EnvAction::Place(placement) => {
+                if self.state.paused {
+                    return Err(EnvError::Paused);
+                }
+                if self.state.is_spawn_delay_active() {
+                    return Err(EnvError::SpawnPending);
+                }
+                let mut next = self.state.clone();
+                if !move_to(&mut next, placement) {
+                    return Err(EnvError::UnreachablePlacement);
+                }
+                next.apply_action(GameAction::HardDrop);
+                // Run out the line clear and entry delay so the next piece is in play.
+                let mut steps = 0;
+                while next.is_spawn_delay_active() && !next.game_over {
+                    if steps == MAX_SPAWN_STEPS {
+                        return Err(EnvError::SpawnPending);
+                    }
+                    next.step(false);
+                    steps += 1;
+                }
+                self.state = next;
+            }
+        }
+        // Nobody reads the events here; drop them so long runs stay flat.
+        self.state.take_events();
+
+        Ok(StepResult {
+            observation: self.observe(),
+            reward: self.state.score.saturating_sub(score) as f64,
+            lines_cleared: self.state.lines.saturating_sub(lines),
+            done: self.state.game_over,
+        })
+    }
+
+    pub fn observe(&self) -> Observation {
+        let board = self
+            .state
+            .board
+            .cells()
+            .iter()
+            .map(|row| row.iter().map(|cell| cell.filled).collect())
+            .collect();
+        Observation {
+            board,
+            active: self.state.active,
+            queue: self.state.next_queue.clone(),
+            hold: self.state.hold,
+            can_hold: self.state.can_hold,
+            score: self.state.score,
+            level: self.state.level,
+            lines: self.state.lines,
+        }
+    }
+
+    /// Every distinct spot the active (or held) piece can be hard dropped
+    /// into. Placements that land on the same cells are reported once.
+    pub fn placements(&self) -> Vec<Placement> {
+        let mut seen: Vec<(bool, [(i32, i32); 4])> = Vec::new();
+        let mut placements = Vec::new();
+        if self.state.game_over {
+            return placements;
+        }
+
+        for hold in [false, true] {
+            if hold && !self.state.can_hold {
+                continue;
+            }
+            for turns in 0..4 {
+                let mut state = self.state.clone();
+                if hold {
+                    state.apply_action(GameAction::Hold);
+                }
+                for _ in 0..turns {
+                    state.apply_action(GameAction::RotateCw);
+                }
+                if state.game_over || state.active.rotation.index() != turns {
+                    continue;
+                }
+                for step in [GameAction::MoveLeft, GameAction::MoveRight] {
+                    let mut probe = state.clone();
+                    loop {
+                        let mut landing = probe.ghost_blocks();
+                        landing.sort_unstable();
+                        if !seen.contains(&(hold, landing)) {
+                            seen.push((hold, landing));
+                            placements.push(Placement {
+                                hold,
+                                rotation: probe.active.rotation,
+                                x: probe.active.x,
+                            });
+                        }
+                        let x = probe.active.x;
+                        probe.apply_action(step);
+                        if probe.active.x == x {
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+        placements
+    }
+}
+
+fn move_to(state: &mut GameState, placement: Placement) -> bool {
+    if state.game_over || (placement.hold && !state.can_hold) {
+        return false;
+    }
+    if placement.hold {
+        state.apply_action(GameAction::Hold);
+    }
+    for _ in 0..placement.rotation.index() {
+        state.apply_action(GameAction::RotateCw);
+    }
+    if state.game_over || state.active.rotation != placement.rotation {
+        return false;
+    }
+    let step = if placement.x < state.active.x {
+        GameAction::MoveLeft
+    } else {
+        GameAction::MoveRight
+    };
+    while state.active.x != placement.x {
+        let x = state.active.x;
+        state.apply_action(step);
+        if state.active.x == x {
+            return false;
+        }
+    }
+    true
+}
@@ -0,0 +1,662 @@
+//! Fumen v115, the text format the community shares fields in
+//! (`v115@vhAAgH`). A fumen holds pages, each a 10-wide field 23 rows tall
+//! plus the piece being placed on it. Pages map onto a standard `Board`
+//! with the fumen's bottom row on the board's floor.
+
+use std::fmt;
+
+use crate::game::board::{BOARD_HEIGHT, Board, Cell};
+use crate::game::codec::DecodeError;
+use crate::game::pieces::{Rotation, Tetromino, TetrominoType};
+use crate::game::state::GameState;
+
+const DIGITS: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+const VERSION: u32 = 115;
+const FIELD_WIDTH: usize = 10;
+/// Rows a fumen field holds above the floor.
+const FIELD_TOP: usize = 23;
+/// The field rows plus the garbage row under the floor that rises into
+/// play when a page asks for it.
+const FIELD_BLOCKS: usize = (FIELD_TOP + 1) * FIELD_WIDTH;
+/// A field run that repeats the previous page unchanged: diff 0 over every block.
+const UNCHANGED_FIELD: u32 = 8 * FIELD_BLOCKS as u32 + FIELD_BLOCKS as u32 - 1;
+const GRAY: u8 = 8;
+/// Comments are stored four characters to a five-digit group, each
+/// character an offset into printable ASCII.
+const COMMENT_BASE: u32 = 96;
+const MAX_COMMENT_LEN: usize = 4095;
+/// `?` goes after the first 42 data characters and every 47 after that, as
+/// the fumen editor wraps its URLs.
+const FIRST_CHUNK: usize = 42;
+const CHUNK: usize = 47;
+
+/// One fumen page: the field as it is before the piece is placed, the piece
+/// itself, if any, and the page's comment.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FumenPage {
+    pub board: Board,
+    /// In board coordinates, using the SRS shapes.
+    pub piece: Option<Tetromino>,
+    pub comment: String,
+}
+
+impl FumenPage {
+    /// The board and the falling piece, once it is in play.
+    pub fn from_state(state: &GameState) -> Self {
+        let settled =
+            state.game_over || state.is_line_clear_active() || state.is_spawn_delay_active();
+        Self {
+            board: state.board.clone(),
+            piece: (!settled).then_some(state.active),
+            comment: String::new(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum EncodeError {
+    /// Fumen fields are always ten columns wide.
+    Width(usize),
+    /// A filled cell or the piece lies above the 23 rows a fumen holds.
+    OutOfField,
+    CommentTooLong(usize),
+}
+
+impl fmt::Display for EncodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EncodeError::Width(width) => {
+                write!(f, "fumen fields are {FIELD_WIDTH} wide, not {width}")
+            }
+            EncodeError::OutOfField => write!(f, "blocks above the top of a fumen field"),
+            EncodeError::CommentTooLong(len) => {
+                write!(
+                    f,
+                    "comment escapes to {len} characters, over {MAX_COMMENT_LEN}"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for EncodeError {}
+
+/// Reads every page of a fumen. `text` may be a whole URL; everything up to
+/// the `v115@` marker is skipped and `?` line breaks are ignored.
+pub fn decode(text: &str) -> Result<Vec<FumenPage>, DecodeError> {
+    let mut reader = Reader::new(fumen_data(text)?)?;
+    let mut pages = Vec::new();
+    let mut prev = [0u8; FIELD_BLOCKS];
+    let mut repeat = 0;
+    let mut comment = String::new();
+
+    while !reader.is_empty() {
+        let mut field = prev;
+        if repeat > 0 {
+            repeat -= 1;
+        } else {
+            let mut index = 0;
+            while index < FIELD_BLOCKS {
+                let run = reader.poll(2)?;
+                let diff = (run / FIELD_BLOCKS as u32) as i32 - 8;
+                let count = (run % FIELD_BLOCKS as u32) as usize + 1;
+                if run == UNCHANGED_FIELD {
+                    repeat = reader.poll(1)?;
+                }
+                if index + count > FIELD_BLOCKS {
+                    return Err(DecodeError::InvalidValue("fumen field"));
+                }
+                for block in &mut field[index..index + count] {
+                    *block = u8::try_from(*block as i32 + diff)
+                        .ok()
+                        .filter(|&value| value <= GRAY)
+                        .ok_or(DecodeError::InvalidValue("fumen field"))?;
+                }
+                index += count;
+            }
+        }
+
+        let action = Action::decode(reader.poll(3)?)?;
+        if action.comment {
+            comment = read_comment(&mut reader)?;
+        }
+
+        pages.push(FumenPage {
+            board: field_to_board(&field),
+            piece: action.piece.map(|piece| piece.to_tetromino()),
+            comment: comment.clone(),
+        });
+
+        if action.lock {
+            if let Some(piece) = action.piece {
+                for (x, y) in piece.cells() {
+                    field[block_index(x as usize, y)] = piece_number(piece.kind);
+                }
+            }
+            clear_full_rows(&mut field);
+            if action.rise {
+                rise_garbage(&mut field);
+            }
+            if action.mirror {
+                for row in field[..FIELD_TOP * FIELD_WIDTH].chunks_mut(FIELD_WIDTH) {
+                    row.reverse();
+                }
+            }
+        }
+        prev = field;
+    }
+    Ok(pages)
+}
+
+/// Writes `pages` as one fumen, each piece locking into the next page's
+/// field. A page whose board matches what the previous page left behind
+/// costs a few characters.
+pub fn encode(pages: &[FumenPage]) -> Result<String, EncodeError> {
+    let mut data = Vec::new();
+    let mut prev = [0u8; FIELD_BLOCKS];
+    let mut repeat_at: Option<usize> = None;
+    let mut prev_comment = "";
+
+    for (index, page) in pages.iter().enumerate() {
+        let mut field = board_to_field(&page.board)?;
+        let piece = page
+            .piece
+            .as_ref()
+            .map(|piece| FieldPiece::from_tetromino(piece, page.board.height()))
+            .transpose()?;
+
+        let runs = field_runs(&prev, &field);
+        match repeat_at {
+            Some(at) if runs == [UNCHANGED_FIELD] && data[at] < 63 => data[at] += 1,
+            _ => {
+                for run in runs.iter() {
+                    push(&mut data, *run, 2);
+                }
+                repeat_at = (runs == [UNCHANGED_FIELD]).then(|| {
+                    data.push(0);
+                    data.len() - 1
+                });
+            }
+        }
+
+        let comment = page.comment != prev_comment;
+        let action = Action {
+            piece,
+            rise: false,
+            mirror: false,
+            color: index == 0,
+            comment,
+            lock: true,
+        };
+        push(&mut data, action.encode(), 3);
+        if comment {
+            write_comment(&mut data, &page.comment)?;
+            prev_comment = &page.comment;
+        }
+
+        if let Some(piece) = piece {
+            for (x, y) in piece.cells() {
+                field[block_index(x as usize, y)] = piece_number(piece.kind);
+            }
+        }
+        clear_full_rows(&mut field);
+        prev = field;
+    }
+
+    let data: String = data
+        .iter()
+        .map(|&digit| DIGITS[digit as usize] as char)
+        .collect();
+    let mut out = format!("v{VERSION}@");
+    let (head, mut rest) = data.split_at(data.len().min(FIRST_CHUNK));
+    out.push_str(head);
+    while !rest.is_empty() {
+        let (chunk, tail) = rest.split_at(rest.len().min(CHUNK));
+        out.push('?');
+        out.push_str(chunk);
+        rest = tail;
+    }
+    Ok(out)
+}
+
+/// The data after the `v115@` marker with line breaks removed.
+fn fumen_data(text: &str) -> Result<Vec<u8>, DecodeError> {
+    let text = text.trim();
+    let bytes = text.as_bytes();
+    let start = (4..bytes.len())
+        .find(|&at| {
+            bytes[at] == b'@'
+                && matches!(bytes[at - 4], b'v' | b'm' | b'd')
+                && bytes[at - 3..at].iter().all(u8::is_ascii_digit)
+        })
+        .ok_or(DecodeError::BadMagic)?;
+    let version = text[start - 3..start]
+        .parse()
+        .map_err(|_| DecodeError::BadMagic)?;
+    if version != VERSION {
+        return Err(DecodeError::UnsupportedVersion(version));
+    }
+    bytes[start + 1..]
+        .iter()
+        .filter(|&&byte| byte != b'?')
+        .map(|&byte| {
+            DIGITS
+                .iter()
+                .position(|&digit| digit == byte)
+                .map(|value| value as u8)
+                .ok_or(DecodeError::InvalidValue("fumen data"))
+        })
+        .collect()
+}
+
+struct Reader {
+    digits: Vec<u8>,
+    at: usize,
+}
+
+impl Reader {
+    fn new(digits: Vec<u8>) -> Result<Self, DecodeError> {
+        if digits.is_empty() {
+            return Err(DecodeError::UnexpectedEof);
+        }
+        Ok(Self { digits, at: 0 })
+    }
+
+    fn is_empty(&self) -> bool {
+        self.at >= self.digits.len()
+    }
+
+    /// `count` base-64 digits, least significant first.
+    fn poll(&mut self, count: usize) -> Result<u32, DecodeError> {
+        let digits = self
+            .digits
+            .get(self.at..self.at + count)
+            .ok_or(DecodeError::UnexpectedEof)?;
+        self.at += count;
+        Ok(digits
+            .iter()
+            .rev()
+            .fold(0, |value, &digit| value * 64 + digit as u32))
+    }
+}
+
+fn push(data: &mut Vec<u8>, mut value: u32, count: usize) {
+    for _ in 0..count {
+        data.push((value % 64) as u8);
+        value /= 64;
+    }
+}
+
+/// Fumen rows count up from the floor; `y == -1` is the garbage row.
+fn block_index(x: usize, y: i32) -> usize {
+    (FIELD_TOP as i32 - 1 - y) as usize * FIELD_WIDTH + x
+}
+
+/// The field as `diff * 240 + (count - 1)` runs against `prev`.
+fn field_runs(prev: &[u8; FIELD_BLOCKS], field: &[u8; FIELD_BLOCKS]) -> Vec<u32> {
+    let mut runs: Vec<(u32, u32)> = Vec::new();
+    for (&before, &after) in prev.iter().zip(field.iter()) {
+        let diff = after as u32 + 8 - before as u32;
+        match runs.last_mut() {
+            Some((last, count)) if *last == diff => *count += 1,
+            _ => runs.push((diff, 1)),
+        }
+    }
+    runs.into_iter()
+        .map(|(diff, count)| diff * FIELD_BLOCKS as u32 + count - 1)
+        .collect()
+}
+
+fn clear_full_rows(field: &mut [u8; FIELD_BLOCKS]) {
+    let rows: Vec<[u8; FIELD_WIDTH]> = field[..FIELD_TOP * FIELD_WIDTH]
+        .chunks(FIELD_WIDTH)
+        .filter(|row| row.contains(&0))
+        .map(|row| row.try_into().expect("rows are ten wide"))
+        .collect();
+    let top = (FIELD_TOP - rows.len()) * FIELD_WIDTH;
+    field[..top].fill(0);
+    for (row, blocks) in rows.iter().enumerate() {
+        let at = top + row * FIELD_WIDTH;
+        field[at..at + FIELD_WIDTH].copy_from_slice(blocks);
+    }
+}
+
+/// Pushes the play field up a row, dropping its top, and moves the garbage
+/// row onto the floor.
+fn rise_garbage(field: &mut [u8; FIELD_BLOCKS]) {
+    field.copy_within(FIELD_WIDTH.., 0);
+    field[FIELD_BLOCKS - FIELD_WIDTH..].fill(0);
+}
+
+fn field_to_board(field: &[u8; FIELD_BLOCKS]) -> Board {
+    let mut board = Board::new();
+    let bottom = board.height() - 1;
+    for y in 0..FIELD_TOP {
+        for x in 0..FIELD_WIDTH {
+            let cell = match field[block_index(x, y as i32)] {
+                0 => continue,
+                GRAY => Cell::GARBAGE,
+                number => Cell::piece(piece_kind(number).expect("field values are checked")),
+            };
+            board.set_cell(x, bottom - y, cell);
+        }
+    }
+    board
+}
+
+fn board_to_field(board: &Board) -> Result<[u8; FIELD_BLOCKS], EncodeError> {
+    if board.width() != FIELD_WIDTH {
+        return Err(EncodeError::Width(board.width()));
+    }
+    let mut field = [0; FIELD_BLOCKS];
+    let bottom = board.height() - 1;
+    for (row, cells) in board.cells().iter().enumerate() {
+        for (x, cell) in cells.iter().enumerate().filter(|(_, cell)| cell.filled) {
+            let y = bottom - row;
+            if y >= FIELD_TOP {
+                return Err(EncodeError::OutOfField);
+            }
+            field[block_index(x, y as i32)] = match cell.kind {
+                Some(kind) if !cell.garbage => piece_number(kind),
+                _ => GRAY,
+            };
+        }
+    }
+    Ok(field)
+}
+
+fn piece_number(kind: TetrominoType) -> u8 {
+    match kind {
+        TetrominoType::I => 1,
+        TetrominoType::L => 2,
+        TetrominoType::O => 3,
+        TetrominoType::Z => 4,
+        TetrominoType::T => 5,
+        TetrominoType::J => 6,
+        TetrominoType::S => 7,
+    }
+}
+
+fn piece_kind(number: u8) -> Option<TetrominoType> {
+    TetrominoType::ALL
+        .into_iter()
+        .find(|&kind| piece_number(kind) == number)
+}
+
+fn rotation_number(rotation: Rotation) -> u32 {
+    match rotation {
+        Rotation::South => 0,
+        Rotation::East => 1,
+        Rotation::North => 2,
+        Rotation::West => 3,
+    }
+}
+
+/// A piece on the fumen field, placed by its SRS rotation centre with `y`
+/// counting up from the floor.
+#[derive(Clone, Copy, Debug)]
+struct FieldPiece {
+    kind: TetrominoType,
+    rotation: Rotation,
+    x: i32,
+    y: i32,
+}
+
+impl FieldPiece {
+    fn cells(&self) -> [(i32, i32); 4] {
+        let spawn = match self.kind {
+            TetrominoType::I => [(0, 0), (-1, 0), (1, 0), (2, 0)],
+            TetrominoType::T => [(0, 0), (-1, 0), (1, 0), (0, 1)],
+            TetrominoType::O => [(0, 0), (1, 0), (0, 1), (1, 1)],
+            TetrominoType::L => [(0, 0), (-1, 0), (1, 0), (1, 1)],
+            TetrominoType::J => [(0, 0), (-1, 0), (1, 0), (-1, 1)],
+            TetrominoType::S => [(0, 0), (-1, 0), (0, 1), (1, 1)],
+            TetrominoType::Z => [(0, 0), (1, 0), (0, 1), (-1, 1)],
+        };
+        spawn.map(|(dx, dy)| {
+            let (dx, dy) = match self.rotation {
+                Rotation::North => (dx, dy),
+                Rotation::East => (dy, -dx),
+                Rotation::South => (-dx, -dy),
+                Rotation::West => (-dy, dx),
+            };
+            (self.x + dx, self.y + dy)
+        })
+    }
+
+    /// The fumen format predates SRS and places some rotations by a
+    /// different block; this is the shift from the SRS centre to that block.
+    fn legacy_offset(&self) -> (i32, i32) {
+        match (self.kind, self.rotation) {
+            (TetrominoType::O, Rotation::West) => (-1, 1),
+            (TetrominoType::O, Rotation::South) => (-1, 0),
+            (TetrominoType::O, Rotation::North) => (0, 1),
+            (TetrominoType::I, Rotation::South) => (-1, 0),
+            (TetrominoType::I, Rotation::West) => (0, 1),
+            (TetrominoType::S, Rotation::North) => (0, 1),
+            (TetrominoType::S, Rotation::East) => (1, 0),
+            (TetrominoType::Z, Rotation::North) => (0, 1),
+            (TetrominoType::Z, Rotation::West) => (-1, 0),
+            _ => (0, 0),
+        }
+    }
+
+    /// Board coordinates on a `Board::new()` board, using the SRS shapes.
+    fn to_tetromino(self) -> Tetromino {
+        let cells = self.cells().map(|(x, y)| (x, BOARD_HEIGHT as i32 - 1 - y));
+        let mut piece = Tetromino::new(self.kind, 0, 0);
+        piece.rotation = self.rotation;
+        let shape = piece.blocks(self.rotation);
+        piece.x = min_x(&cells) - min_x(&shape);
+        piece.y = min_y(&cells) - min_y(&shape);
+        piece
+    }
+
+    /// Matches the blocks `piece` covers on a board `height` rows tall, so
+    /// pieces from other rotation systems come out in the orientation they
+    /// show.
+    fn from_tetromino(piece: &Tetromino, height: usize) -> Result<Self, EncodeError> {
+        let mut cells = piece
+            .blocks(piece.rotation)
+            .map(|(dx, dy)| (piece.x + dx, height as i32 - 1 - (piece.y + dy)));
+        if !cells.into_iter().all(in_field) {
+            return Err(EncodeError::OutOfField);
+        }
+        cells.sort_unstable();
+        let rotations = [piece.rotation]
+            .into_iter()
+            .chain((0..4).filter_map(Rotation::from_index));
+        for rotation in rotations {
+            let origin = FieldPiece {
+                kind: piece.kind,
+                rotation,
+                x: 0,
+                y: 0,
+            };
+            let shape = origin.cells();
+            let candidate = FieldPiece {
+                x: min_x(&cells) - min_x(&shape),
+                y: min_y(&cells) - min_y(&shape),
+                ..origin
+            };
+            let mut placed = candidate.cells();
+            placed.sort_unstable();
+            if placed == cells {
+                return Ok(candidate);
+            }
+        }
+        unreachable!("every tetromino orientation has a fumen rotation")
+    }
+}
+
+fn in_field((x, y): (i32, i32)) -> bool {
+    (0..FIELD_WIDTH as i32).contains(&x) && (0..FIELD_TOP as i32).contains(&y)
+}
+
+fn min_x(cells: &[(i32, i32)]) -> i32 {
+    cells.iter().map(|&(x, _)| x).min().unwrap_or(0)
+}
+
+fn min_y(cells: &[(i32, i32)]) -> i32 {
+    cells.iter().map(|&(_, y)| y).min().unwrap_or(0)
+}
+
+/// The three-digit page action. Flags after the piece: raise the garbage
+/// row, mirror the field, use guideline colours (first page only), a
+/// comment follows, and whether the piece stays out of the next page.
+struct Action {
+    piece: Option<FieldPiece>,
+    rise: bool,
+    mirror: bool,
+    color: bool,
+    comment: bool,
+    lock: bool,
+}
+
+impl Action {
+    fn decode(mut value: u32) -> Result<Self, DecodeError> {
+        let mut take = |base: u32| {
+            let digit = value % base;
+            value /= base;
+            digit
+        };
+        let kind = take(8) as u8;
+        let rotation = match take(4) {
+            0 => Rotation::South,
+            1 => Rotation::East,
+            2 => Rotation::North,
+            _ => Rotation::West,
+        };
+        let coordinate = take(FIELD_BLOCKS as u32) as i32;
+        let rise = take(2) == 1;
+        let mirror = take(2) == 1;
+        let color = take(2) == 1;
+        let comment = take(2) == 1;
+        let lock = take(2) == 0;
+
+        let piece = match piece_kind(kind) {
+            Some(kind) => {
+                let mut piece = FieldPiece {
+                    kind,
+                    rotation,
+                    x: coordinate % FIELD_WIDTH as i32,
+                    y: FIELD_TOP as i32 - 1 - coordinate / FIELD_WIDTH as i32,
+                };
+                let (dx, dy) = piece.legacy_offset();
+                piece.x -= dx;
+                piece.y -= dy;
+                if !piece.cells().into_iter().all(in_field) {
+                    return Err(DecodeError::InvalidValue("fumen piece"));
+                }
+                Some(piece)
+            }
+            None => None,
+        };
+        Ok(Self {
+            piece,
+            rise,
+            mirror,
+            color,
+            comment,
+            lock,
+        })
+    }
+
+    fn encode(&self) -> u32 {
+        let (kind, rotation, coordinate) = match self.piece {
+            Some(piece) => {
+                let (dx, dy) = piece.legacy_offset();
+                let (x, y) = (piece.x + dx, piece.y + dy);
+                (
+                    piece_number(piece.kind) as u32,
+                    rotation_number(piece.rotation),
+                    (FIELD_TOP as i32 - 1 - y) as u32 * FIELD_WIDTH as u32 + x as u32,
+                )
+            }
+            None => (0, rotation_number(Rotation::South), 0),
+        };
+        let flags = [!self.lock, self.comment, self.color, self.mirror, self.rise]
+            .into_iter()
+            .fold(0, |value, flag| value * 2 + u32::from(flag));
+        ((flags * FIELD_BLOCKS as u32 + coordinate) * 4 + rotation) * 8 + kind
+    }
+}
+
+fn read_comment(reader: &mut Reader) -> Result<String, DecodeError> {
+    let len = reader.poll(2)? as usize;
+    let mut escaped = String::with_capacity(len + 3);
+    for _ in 0..len.div_ceil(4) {
+        let mut group = reader.poll(5)?;
+        for _ in 0..4 {
+            let offset = group % COMMENT_BASE;
+            group /= COMMENT_BASE;
+            let found = char::from_u32(32 + offset)
+                .filter(|found| found.is_ascii_graphic() || *found == ' ')
+                .ok_or(DecodeError::InvalidValue("fumen comment"))?;
+            escaped.push(found);
+        }
+    }
+    escaped.truncate(len);
+    Ok(unescape(&escaped))
+}
+
+fn write_comment(data: &mut Vec<u8>, comment: &str) -> Result<(), EncodeError> {
+    let escaped = escape(comment);
+    if escaped.len() > MAX_COMMENT_LEN {
+        return Err(EncodeError::CommentTooLong(escaped.len()));
+    }
+    push(data, escaped.len() as u32, 2);
+    for group in escaped.as_bytes().chunks(4) {
+        let value = group
+            .iter()
+            .rev()
+            .fold(0, |value, &byte| value * COMMENT_BASE + (byte - 32) as u32);
+        push(data, value, 5);
+    }
+    Ok(())
+}
+
+/// JavaScript's `escape`, which fumen comments have always used.
+fn escape(text: &str) -> String {
+    let mut out = String::new();
+    for unit in text.encode_utf16() {
+        match char::from_u32(unit as u32) {
+            Some(found) if found.is_ascii_alphanumeric() || "@*_+-./".contains(found) => {
+                out.push(found)
+            }
+            _ if unit < 0x100 => out.push_str(&format!("%{unit:02X}")),
+            _ => out.push_str(&format!("%u{unit:04X}")),
+        }
+    }
+    out
+}
+
+/// JavaScript's `unescape`: malformed escapes are kept as they are.
+fn unescape(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let hex = |from: usize, len: usize| {
+        text.get(from..from + len)
+            .filter(|digits| digits.bytes().all(|byte| byte.is_ascii_hexdigit()))
+            .and_then(|digits| u16::from_str_radix(digits, 16).ok())
+    };
+    let mut units = Vec::with_capacity(bytes.len());
+    let mut at = 0;
+    while at < bytes.len() {
+        let (unit, len) = match bytes[at] {
+            b'%' if bytes.get(at + 1) == Some(&b'u') => match hex(at + 2, 4) {
+                Some(unit) => (unit, 6),
+                None => (b'%' as u16, 1),
+            },
+            b'%' => match hex(at + 1, 2) {
+                Some(unit) => (unit, 3),
+                None => (b'%' as u16, 1),
+            },
+            byte => (byte as u16, 1),
+        };
+        units.push(unit);
+        at += len;
+    }
+    char::decode_utf16(units)
+        .map(|found| found.unwrap_or(char::REPLACEMENT_CHARACTER))
+        .collect()
+}
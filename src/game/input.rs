@@ -1,3 +1,5 @@
+use crate::game::state::Ruleset;
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum GameAction {
     MoveLeft,
@@ -6,6 +8,7 @@ pub enum GameAction {
     HardDrop,
     RotateCw,
     RotateCcw,
+    Rotate180,
     Hold,
     Pause,
     Restart,
@@ -17,6 +20,23 @@ pub struct RepeatConfig {
     pub arr_ms: u64,
 }
 
+impl RepeatConfig {
+    /// NES autorepeat: 16 frames before the first repeat, then every 6.
+    pub fn nes() -> Self {
+        Self {
+            das_ms: 16 * 1000 / 60,
+            arr_ms: 6 * 1000 / 60,
+        }
+    }
+
+    pub fn for_ruleset(ruleset: Ruleset) -> Self {
+        match ruleset {
+            Ruleset::Nes => Self::nes(),
+            Ruleset::Classic | Ruleset::Modern => Self::default(),
+        }
+    }
+}
+
 impl Default for RepeatConfig {
     fn default() -> Self {
         Self {
@@ -86,3 +106,47 @@ pub fn key_to_action(key: &str) -> Option<GameAction> {
         _ => None,
     }
 }
+
+/// Rotate and hold buttons currently held down. With initial actions
+/// (IRS/IHS) on, the ones held as a piece spawns act on it straight away.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct HeldButtons {
+    pub rotate_cw: bool,
+    pub rotate_ccw: bool,
+    pub rotate_180: bool,
+    pub hold: bool,
+}
+
+impl HeldButtons {
+    /// Marks the button behind `action` as held or released. Other actions
+    /// are ignored.
+    pub fn set(&mut self, action: GameAction, held: bool) {
+        match action {
+            GameAction::RotateCw => self.rotate_cw = held,
+            GameAction::RotateCcw => self.rotate_ccw = held,
+            GameAction::Rotate180 => self.rotate_180 = held,
+            GameAction::Hold => self.hold = held,
+            _ => {}
+        }
+    }
+
+    pub fn union(self, other: HeldButtons) -> HeldButtons {
+        HeldButtons::from_bits(self.bits() | other.bits())
+    }
+
+    pub(crate) fn bits(self) -> u8 {
+        self.rotate_cw as u8
+            | (self.rotate_ccw as u8) << 1
+            | (self.rotate_180 as u8) << 2
+            | (self.hold as u8) << 3
+    }
+
+    pub(crate) fn from_bits(bits: u8) -> HeldButtons {
+        HeldButtons {
+            rotate_cw: bits & 1 != 0,
+            rotate_ccw: bits & 2 != 0,
+            rotate_180: bits & 4 != 0,
+            hold: bits & 8 != 0,
+        }
+    }
+}
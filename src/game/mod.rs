@@ -1,9 +1,17 @@
 pub mod board;
+pub mod clock;
+pub mod codec;
+pub mod env;
+pub mod fumen;
 pub mod input;
 pub mod pieces;
+pub mod records;
+pub mod replay;
+pub mod rotation;
 pub mod state;
 
 pub use board::{Board, Cell};
 pub use input::GameAction;
 pub use pieces::{Rotation, Tetromino, TetrominoType};
+pub use rotation::RotationSystem;
 pub use state::{GameConfig, GameState};
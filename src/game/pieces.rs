@@ -1,3 +1,5 @@
+use crate::game::rotation::RotationSystem;
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum TetrominoType {
     I,
@@ -9,6 +11,50 @@ pub enum TetrominoType {
     L,
 }
 
+impl TetrominoType {
+    pub const ALL: [TetrominoType; 7] = [
+        TetrominoType::I,
+        TetrominoType::O,
+        TetrominoType::T,
+        TetrominoType::S,
+        TetrominoType::Z,
+        TetrominoType::J,
+        TetrominoType::L,
+    ];
+
+    pub fn index(self) -> usize {
+        match self {
+            TetrominoType::I => 0,
+            TetrominoType::O => 1,
+            TetrominoType::T => 2,
+            TetrominoType::S => 3,
+            TetrominoType::Z => 4,
+            TetrominoType::J => 5,
+            TetrominoType::L => 6,
+        }
+    }
+
+    pub fn from_index(index: usize) -> Option<Self> {
+        Self::ALL.get(index).copied()
+    }
+
+    pub fn letter(self) -> char {
+        match self {
+            TetrominoType::I => 'I',
+            TetrominoType::O => 'O',
+            TetrominoType::T => 'T',
+            TetrominoType::S => 'S',
+            TetrominoType::Z => 'Z',
+            TetrominoType::J => 'J',
+            TetrominoType::L => 'L',
+        }
+    }
+
+    pub fn from_letter(letter: char) -> Option<Self> {
+        Self::ALL.into_iter().find(|kind| kind.letter() == letter)
+    }
+}
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum Rotation {
     North,
@@ -27,6 +73,10 @@ impl Rotation {
         }
     }
 
+    pub fn flip(self) -> Self {
+        self.cw().cw()
+    }
+
     pub fn ccw(self) -> Self {
         match self {
             Rotation::North => Rotation::West,
@@ -44,6 +94,16 @@ impl Rotation {
             Rotation::West => 3,
         }
     }
+
+    pub fn from_index(index: usize) -> Option<Self> {
+        match index {
+            0 => Some(Rotation::North),
+            1 => Some(Rotation::East),
+            2 => Some(Rotation::South),
+            3 => Some(Rotation::West),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -52,6 +112,7 @@ pub struct Tetromino {
     pub rotation: Rotation,
     pub x: i32,
     pub y: i32,
+    pub system: RotationSystem,
 }
 
 impl Tetromino {
@@ -61,81 +122,22 @@ impl Tetromino {
             rotation: Rotation::North,
             x,
             y,
+            system: RotationSystem::Srs,
         }
     }
 
+    pub fn with_system(mut self, system: RotationSystem) -> Self {
+        self.system = system;
+        self
+    }
+
     pub fn blocks(&self, rotation: Rotation) -> [(i32, i32); 4] {
-        shape_for(self.kind, rotation)
+        self.system.shape(self.kind, rotation)
     }
 }
 
+/// Spawn box origin on the default 10-wide board: rows 21-22, just above
+/// the visible field. Other sizes use `BoardSize::spawn_position`.
 pub fn spawn_position() -> (i32, i32) {
-    // Classic spawn near the top center of a 10x20 board.
-    (3, 0)
-}
-
-fn shape_for(kind: TetrominoType, rotation: Rotation) -> [(i32, i32); 4] {
-    const SHAPES: [[[(i32, i32); 4]; 4]; 7] = [
-        // I
-        [
-            [(0, 1), (1, 1), (2, 1), (3, 1)],
-            [(2, 0), (2, 1), (2, 2), (2, 3)],
-            [(0, 2), (1, 2), (2, 2), (3, 2)],
-            [(1, 0), (1, 1), (1, 2), (1, 3)],
-        ],
-        // O
-        [
-            [(1, 0), (2, 0), (1, 1), (2, 1)],
-            [(1, 0), (2, 0), (1, 1), (2, 1)],
-            [(1, 0), (2, 0), (1, 1), (2, 1)],
-            [(1, 0), (2, 0), (1, 1), (2, 1)],
-        ],
-        // T
-        [
-            [(1, 0), (0, 1), (1, 1), (2, 1)],
-            [(1, 0), (1, 1), (2, 1), (1, 2)],
-            [(0, 1), (1, 1), (2, 1), (1, 2)],
-            [(1, 0), (0, 1), (1, 1), (1, 2)],
-        ],
-        // S
-        [
-            [(1, 0), (2, 0), (0, 1), (1, 1)],
-            [(1, 0), (1, 1), (2, 1), (2, 2)],
-            [(1, 1), (2, 1), (0, 2), (1, 2)],
-            [(0, 0), (0, 1), (1, 1), (1, 2)],
-        ],
-        // Z
-        [
-            [(0, 0), (1, 0), (1, 1), (2, 1)],
-            [(2, 0), (1, 1), (2, 1), (1, 2)],
-            [(0, 1), (1, 1), (1, 2), (2, 2)],
-            [(1, 0), (0, 1), (1, 1), (0, 2)],
-        ],
-        // J
-        [
-            [(0, 0), (0, 1), (1, 1), (2, 1)],
-            [(1, 0), (2, 0), (1, 1), (1, 2)],
-            [(0, 1), (1, 1), (2, 1), (2, 2)],
-            [(1, 0), (1, 1), (0, 2), (1, 2)],
-        ],
-        // L
-        [
-            [(2, 0), (0, 1), (1, 1), (2, 1)],
-            [(1, 0), (1, 1), (1, 2), (2, 2)],
-            [(0, 1), (1, 1), (2, 1), (0, 2)],
-            [(0, 0), (1, 0), (1, 1), (1, 2)],
-        ],
-    ];
-
-    let kind_index = match kind {
-        TetrominoType::I => 0,
-        TetrominoType::O => 1,
-        TetrominoType::T => 2,
-        TetrominoType::S => 3,
-        TetrominoType::Z => 4,
-        TetrominoType::J => 5,
-        TetrominoType::L => 6,
-    };
-
-    SHAPES[kind_index][rotation.index()]
+    crate::game::board::BoardSize::default().spawn_position()
 }
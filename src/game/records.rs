@@ -0,0 +1,85 @@
+use std::collections::BTreeMap;
+
+/// Best results per mode and goal, kept as plain `key=value` lines so the file
+/// stays readable and survives new modes being added.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PersonalBests {
+    sprint: BTreeMap<u32, u64>,
+    ultra: BTreeMap<u64, u32>,
+}
+
+impl PersonalBests {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Fastest time in milliseconds for a sprint of `lines` lines.
+    pub fn sprint_best(&self, lines: u32) -> Option<u64> {
+        self.sprint.get(&lines).copied()
+    }
+
+    /// Stores `time_ms` if it beats the current best and reports whether it did.
+    pub fn record_sprint(&mut self, lines: u32, time_ms: u64) -> bool {
+        match self.sprint.get(&lines) {
+            Some(&best) if best <= time_ms => false,
+            _ => {
+                self.sprint.insert(lines, time_ms);
+                true
+            }
+        }
+    }
+
+    /// Highest score for an ultra run lasting `duration_ms`.
+    pub fn ultra_best(&self, duration_ms: u64) -> Option<u32> {
+        self.ultra.get(&duration_ms).copied()
+    }
+
+    /// Stores `score` if it beats the current best and reports whether it did.
+    pub fn record_ultra(&mut self, duration_ms: u64, score: u32) -> bool {
+        match self.ultra.get(&duration_ms) {
+            Some(&best) if best >= score => false,
+            _ => {
+                self.ultra.insert(duration_ms, score);
+                true
+            }
+        }
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut text = String::new();
+        for (lines, time_ms) in &self.sprint {
+            text.push_str(&format!("sprint.{lines}={time_ms}\n"));
+        }
+        for (duration_ms, score) in &self.ultra {
+            text.push_str(&format!("ultra.{duration_ms}={score}\n"));
+        }
+        text
+    }
+
+    /// Parses `to_text` output. Lines that are not understood are skipped.
+    pub fn from_text(text: &str) -> Self {
+        let mut bests = Self::new();
+        for line in text.lines() {
+            let Some((key, value)) = line.trim().split_once('=') else {
+                continue;
+            };
+            let Some((mode, goal)) = key.split_once('.') else {
+                continue;
+            };
+            match mode {
+                "sprint" => {
+                    if let (Ok(lines), Ok(time_ms)) = (goal.parse(), value.parse()) {
+                        bests.sprint.insert(lines, time_ms);
+                    }
+                }
+                "ultra" => {
+                    if let (Ok(duration_ms), Ok(score)) = (goal.parse(), value.parse()) {
+                        bests.ultra.insert(duration_ms, score);
+                    }
+                }
+                _ => {}
+            }
+        }
+        bests
+    }
+}
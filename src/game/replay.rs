@@ -0,0 +1,380 @@
+use crate::game::board::Board;
+use crate::game::codec::{ByteReader, ByteWriter, DecodeError};
+use crate::game::input::{GameAction, HeldButtons};
+use crate::game::state::{
+    GameConfig, GameEvent, GameState, decode_cell, encode_cell, read_config, write_config,
+};
+
+pub const REPLAY_VERSION: u32 = 1;
+const REPLAY_MAGIC: &[u8; 4] = b"GTRP";
+const SOFT_DROP_TAG: u8 = 0x80;
+const SOFT_DROP_HELD: u8 = 0x01;
+/// Low four bits carry the held rotate and hold buttons.
+const HELD_TAG: u8 = 0x40;
+
+/// Input landing on a simulation step. Events tagged with frame `n` are
+/// applied before step `n` runs.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReplayEvent {
+    Action { frame: u64, action: GameAction },
+    SoftDrop { frame: u64, held: bool },
+    Held { frame: u64, held: HeldButtons },
+}
+
+impl ReplayEvent {
+    pub fn frame(&self) -> u64 {
+        match self {
+            ReplayEvent::Action { frame, .. }
+            | ReplayEvent::SoftDrop { frame, .. }
+            | ReplayEvent::Held { frame, .. } => *frame,
+        }
+    }
+}
+
+/// Input log for one game: replaying it against `GameState::new(seed, config)`,
+/// on the starting field if there is one, with fixed steps reproduces the
+/// run exactly.
+#[derive(Clone, Debug)]
+pub struct Replay {
+    pub seed: u64,
+    pub config: GameConfig,
+    /// The board the game started on, when it was set up rather than empty.
+    pub field: Option<Board>,
+    pub frames: u64,
+    pub events: Vec<ReplayEvent>,
+    soft_drop: bool,
+    held: HeldButtons,
+}
+
+impl Replay {
+    pub fn new(seed: u64, config: GameConfig) -> Self {
+        Self {
+            seed,
+            config,
+            field: None,
+            frames: 0,
+            events: Vec::new(),
+            soft_drop: false,
+            held: HeldButtons::default(),
+        }
+    }
+
+    pub fn record_action(&mut self, frame: u64, action: GameAction) {
+        self.events.push(ReplayEvent::Action { frame, action });
+    }
+
+    pub fn record_held(&mut self, frame: u64, held: HeldButtons) {
+        if held != self.held {
+            self.held = held;
+            self.events.push(ReplayEvent::Held { frame, held });
+        }
+    }
+
+    /// Logs one simulation step; the soft-drop flag is only stored when it changes.
+    pub fn record_step(&mut self, frame: u64, soft_drop: bool) {
+        if soft_drop != self.soft_drop {
+            self.soft_drop = soft_drop;
+            self.events.push(ReplayEvent::SoftDrop {
+                frame,
+                held: soft_drop,
+            });
+        }
+        self.frames = frame + 1;
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames == 0 && self.events.is_empty()
+    }
+
+    pub fn step_ms(&self) -> u64 {
+        self.config.tick_ms.max(1)
+    }
+
+    pub fn duration_ms(&self) -> u64 {
+        self.frames.saturating_mul(self.step_ms())
+    }
+
+    /// The game as it stood before the first step.
+    fn start(&self) -> GameState {
+        let mut state = GameState::new(self.seed, self.config);
+        if let Some(field) = &self.field {
+            state.start_from_field(field.clone());
+        }
+        state
+    }
+
+    pub fn play(&self) -> GameState {
+        let mut state = self.start();
+        Playhead::default().advance_to(&mut state, self, self.frames);
+        state
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut w = ByteWriter::new();
+        w.bytes(REPLAY_MAGIC);
+        w.u32(REPLAY_VERSION);
+        w.u64(self.seed);
+        write_config(&mut w, &self.config);
+        write_field(&mut w, self.field.as_ref());
+        w.varint(self.frames);
+        w.varint(self.events.len() as u64);
+
+        // Frames are stored as deltas, so a typical event is two bytes.
+        let mut last_frame = 0;
+        for event in self.events.iter() {
+            match *event {
+                ReplayEvent::Action { action, .. } => w.u8(action_code(action)),
+                ReplayEvent::SoftDrop { held, .. } => {
+                    w.u8(SOFT_DROP_TAG | if held { SOFT_DROP_HELD } else { 0 })
+                }
+                ReplayEvent::Held { held, .. } => w.u8(HELD_TAG | held.bits()),
+            }
+            w.varint(event.frame().saturating_sub(last_frame));
+            last_frame = event.frame();
+        }
+        w.finish()
+    }
+
+    pub fn from_bytes(data: &[u8]) -> Result<Self, DecodeError> {
+        let mut r = ByteReader::new(data);
+        if r.bytes(REPLAY_MAGIC.len())? != REPLAY_MAGIC {
+            return Err(DecodeError::BadMagic);
+        }
+        let version = r.u32()?;
+        if version != REPLAY_VERSION {
+            return Err(DecodeError::UnsupportedVersion(version));
+        }
+        let seed = r.u64()?;
+        let config = read_config(&mut r)?;
+        let field = read_field(&mut r, &config)?;
+        let frames = r.varint()?;
+        let count = r.varint()? as usize;
+
+        let mut replay = Replay::new(seed, config);
+        replay.field = field;
+        replay.frames = frames;
+        replay.events.reserve(count.min(data.len()));
+        let mut last_frame = 0u64;
+        for _ in 0..count {
+            let tag = r.u8()?;
+            let frame = last_frame.saturating_add(r.varint()?);
+            if frame > frames {
+                return Err(DecodeError::InvalidValue("event frame"));
+            }
+            let event = if tag & SOFT_DROP_TAG != 0 {
+                let held = tag & SOFT_DROP_HELD != 0;
+                replay.soft_drop = held;
+                ReplayEvent::SoftDrop { frame, held }
+            } else if tag & HELD_TAG != 0 {
+                let held = HeldButtons::from_bits(tag & !HELD_TAG);
+                replay.held = held;
+                ReplayEvent::Held { frame, held }
+            } else {
+                ReplayEvent::Action {
+                    frame,
+                    action: action_from_code(tag).ok_or(DecodeError::InvalidValue("action"))?,
+                }
+            };
+            replay.events.push(event);
+            last_frame = frame;
+        }
+
+        if !r.is_empty() {
+            return Err(DecodeError::InvalidValue("trailing data"));
+        }
+        Ok(replay)
+    }
+}
+
+/// Feeds a replay back into a fresh `GameState` with pause, seek and speed
+/// controls for the viewer.
+#[derive(Clone, Debug)]
+pub struct ReplayPlayer {
+    replay: Replay,
+    state: GameState,
+    playhead: Playhead,
+    position_ms: u64,
+    carry_ms: f32,
+    speed: f32,
+    paused: bool,
+}
+
+impl ReplayPlayer {
+    pub const MIN_SPEED: f32 = 0.25;
+    pub const MAX_SPEED: f32 = 4.0;
+
+    pub fn new(replay: Replay) -> Self {
+        let mut state = replay.start();
+        let mut playhead = Playhead::default();
+        playhead.advance_to(&mut state, &replay, 0);
+        Self {
+            replay,
+            state,
+            playhead,
+            position_ms: 0,
+            carry_ms: 0.0,
+            speed: 1.0,
+            paused: false,
+        }
+    }
+
+    pub fn state(&self) -> &GameState {
+        &self.state
+    }
+
+    pub fn replay(&self) -> &Replay {
+        &self.replay
+    }
+
+    pub fn take_events(&mut self) -> Vec<GameEvent> {
+        self.state.take_events()
+    }
+
+    pub fn position_ms(&self) -> u64 {
+        self.position_ms
+    }
+
+    pub fn duration_ms(&self) -> u64 {
+        self.replay.duration_ms()
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.playhead.frame >= self.replay.frames
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    pub fn toggle_pause(&mut self) {
+        self.paused = !self.paused;
+    }
+
+    pub fn speed(&self) -> f32 {
+        self.speed
+    }
+
+    pub fn set_speed(&mut self, speed: f32) {
+        self.speed = speed.clamp(Self::MIN_SPEED, Self::MAX_SPEED);
+    }
+
+    /// Advances playback by wall-clock time, scaled by the current speed.
+    pub fn advance(&mut self, elapsed_ms: u64) {
+        if self.paused {
+            return;
+        }
+        let scaled = elapsed_ms as f32 * self.speed + self.carry_ms;
+        let whole = scaled.floor();
+        self.carry_ms = scaled - whole;
+        self.seek(self.position_ms.saturating_add(whole as u64));
+    }
+
+    pub fn seek(&mut self, target_ms: u64) {
+        let target_ms = target_ms.min(self.duration_ms());
+        let target_frame = target_ms / self.replay.step_ms();
+        if target_frame < self.playhead.frame {
+            self.state = self.replay.start();
+            self.playhead = Playhead::default();
+        }
+        self.playhead
+            .advance_to(&mut self.state, &self.replay, target_frame);
+        self.position_ms = target_ms;
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+struct Playhead {
+    cursor: usize,
+    frame: u64,
+    soft_drop: bool,
+}
+
+impl Playhead {
+    /// Steps `state` up to `target` and applies the inputs landing on it.
+    fn advance_to(&mut self, state: &mut GameState, replay: &Replay, target: u64) {
+        loop {
+            while let Some(event) = replay.events.get(self.cursor) {
+                if event.frame() > self.frame {
+                    break;
+                }
+                match *event {
+                    ReplayEvent::Action { action, .. } => state.apply_action(action),
+                    ReplayEvent::SoftDrop { held, .. } => self.soft_drop = held,
+                    ReplayEvent::Held { held, .. } => state.set_held_buttons(held),
+                }
+                self.cursor += 1;
+            }
+            if self.frame >= target {
+                break;
+            }
+            state.step(self.soft_drop);
+            self.frame += 1;
+        }
+    }
+}
+
+fn action_code(action: GameAction) -> u8 {
+    match action {
+        GameAction::MoveLeft => 0,
+        GameAction::MoveRight => 1,
+        GameAction::SoftDrop => 2,
+        GameAction::HardDrop => 3,
+        GameAction::RotateCw => 4,
+        GameAction::RotateCcw => 5,
+        GameAction::Hold => 6,
+        GameAction::Pause => 7,
+        GameAction::Restart => 8,
+        GameAction::Rotate180 => 9,
+    }
+}
+
+fn action_from_code(code: u8) -> Option<GameAction> {
+    match code {
+        0 => Some(GameAction::MoveLeft),
+        1 => Some(GameAction::MoveRight),
+        2 => Some(GameAction::SoftDrop),
+        3 => Some(GameAction::HardDrop),
+        4 => Some(GameAction::RotateCw),
+        5 => Some(GameAction::RotateCcw),
+        6 => Some(GameAction::Hold),
+        7 => Some(GameAction::Pause),
+        8 => Some(GameAction::Restart),
+        9 => Some(GameAction::Rotate180),
+        _ => None,
+    }
+}
+
+/// The starting field as its bottom rows up to the highest filled cell; a
+/// game without one stores no rows.
+fn write_field(w: &mut ByteWriter, field: Option<&Board>) {
+    let Some(field) = field else {
+        w.varint(0);
+        return;
+    };
+    let top = field
+        .cells()
+        .iter()
+        .position(|row| row.iter().any(|cell| cell.filled))
+        .unwrap_or(field.height());
+    w.varint((field.height() - top) as u64);
+    for cell in field.cells()[top..].iter().flatten() {
+        w.u8(encode_cell(cell));
+    }
+}
+
+fn read_field(r: &mut ByteReader<'_>, config: &GameConfig) -> Result<Option<Board>, DecodeError> {
+    let mut field = Board::with_size(config.board.clamped());
+    let rows = r.varint()? as usize;
+    if rows == 0 {
+        return Ok(None);
+    }
+    if rows > field.height() {
+        return Err(DecodeError::InvalidValue("field rows"));
+    }
+    for y in field.height() - rows..field.height() {
+        for x in 0..field.width() {
+            field.set_cell(x, y, decode_cell(r.u8()?)?);
+        }
+    }
+    Ok(Some(field))
+}
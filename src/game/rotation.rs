@@ -0,0 +1,338 @@
+use crate::game::pieces::{Rotation, TetrominoType};
+
+type Shape = [(i32, i32); 4];
+type ShapeTable = [[Shape; 4]; 7];
+
+/// Piece shapes plus the kick offsets tried when a rotation is blocked.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RotationSystem {
+    /// Guideline Super Rotation System.
+    #[default]
+    Srs,
+    /// Arika Rotation System (TGM): bottom-aligned shapes, one-cell wall
+    /// kicks and the centre-column rule for J, L and T.
+    Ars,
+    /// NES: right-handed shapes that rotate in place with no kicks.
+    Nes,
+    /// SRS with symmetric I kicks and 180° rotation, as in TETR.IO.
+    SrsPlus,
+}
+
+impl RotationSystem {
+    pub const ALL: [RotationSystem; 4] = [
+        RotationSystem::Srs,
+        RotationSystem::Ars,
+        RotationSystem::Nes,
+        RotationSystem::SrsPlus,
+    ];
+
+    pub fn shape(self, kind: TetrominoType, rotation: Rotation) -> Shape {
+        let table = match self {
+            RotationSystem::Srs | RotationSystem::SrsPlus => &SRS_SHAPES,
+            RotationSystem::Ars => &ARS_SHAPES,
+            RotationSystem::Nes => &NES_SHAPES,
+        };
+        table[kind.index()][rotation.index()]
+    }
+
+    pub fn supports_180(self) -> bool {
+        self == RotationSystem::SrsPlus
+    }
+
+    /// Offsets to try in order; the first one that fits wins.
+    pub fn kicks(self, kind: TetrominoType, from: Rotation, to: Rotation) -> &'static [(i32, i32)] {
+        match self {
+            RotationSystem::Srs => srs_kicks(kind, from, to),
+            RotationSystem::SrsPlus => srs_plus_kicks(kind, from, to),
+            RotationSystem::Ars => ars_kicks(kind),
+            RotationSystem::Nes => &NO_KICKS,
+        }
+    }
+
+    /// Kick that upgrades a T-spin mini to a full T-spin: the last SRS offset
+    /// of a 90° rotation, used for TST and fin setups.
+    pub fn tst_kick(self) -> Option<usize> {
+        match self {
+            RotationSystem::Srs | RotationSystem::SrsPlus => Some(4),
+            RotationSystem::Ars | RotationSystem::Nes => None,
+        }
+    }
+
+    /// ARS refuses to kick J, L and T when the first blocked cell of the
+    /// target shape, read left to right and top to bottom, sits in the middle
+    /// column of the 3x3 box. That keeps those pieces from climbing walls.
+    pub fn blocks_kick(
+        self,
+        kind: TetrominoType,
+        to: Rotation,
+        occupied: impl Fn(i32, i32) -> bool,
+    ) -> bool {
+        if self != RotationSystem::Ars
+            || !matches!(kind, TetrominoType::J | TetrominoType::L | TetrominoType::T)
+        {
+            return false;
+        }
+        let mut shape = self.shape(kind, to);
+        shape.sort_unstable_by_key(|&(x, y)| (y, x));
+        shape
+            .iter()
+            .find(|&&(x, y)| occupied(x, y))
+            .is_some_and(|&(x, _)| x == 1)
+    }
+}
+
+const NO_KICKS: [(i32, i32); 1] = [(0, 0)];
+
+const SRS_SHAPES: ShapeTable = [
+    // I
+    [
+        [(0, 1), (1, 1), (2, 1), (3, 1)],
+        [(2, 0), (2, 1), (2, 2), (2, 3)],
+        [(0, 2), (1, 2), (2, 2), (3, 2)],
+        [(1, 0), (1, 1), (1, 2), (1, 3)],
+    ],
+    // O
+    [
+        [(1, 0), (2, 0), (1, 1), (2, 1)],
+        [(1, 0), (2, 0), (1, 1), (2, 1)],
+        [(1, 0), (2, 0), (1, 1), (2, 1)],
+        [(1, 0), (2, 0), (1, 1), (2, 1)],
+    ],
+    // T
+    [
+        [(1, 0), (0, 1), (1, 1), (2, 1)],
+        [(1, 0), (1, 1), (2, 1), (1, 2)],
+        [(0, 1), (1, 1), (2, 1), (1, 2)],
+        [(1, 0), (0, 1), (1, 1), (1, 2)],
+    ],
+    // S
+    [
+        [(1, 0), (2, 0), (0, 1), (1, 1)],
+        [(1, 0), (1, 1), (2, 1), (2, 2)],
+        [(1, 1), (2, 1), (0, 2), (1, 2)],
+        [(0, 0), (0, 1), (1, 1), (1, 2)],
+    ],
+    // Z
+    [
+        [(0, 0), (1, 0), (1, 1), (2, 1)],
+        [(2, 0), (1, 1), (2, 1), (1, 2)],
+        [(0, 1), (1, 1), (1, 2), (2, 2)],
+        [(1, 0), (0, 1), (1, 1), (0, 2)],
+    ],
+    // J
+    [
+        [(0, 0), (0, 1), (1, 1), (2, 1)],
+        [(1, 0), (2, 0), (1, 1), (1, 2)],
+        [(0, 1), (1, 1), (2, 1), (2, 2)],
+        [(1, 0), (1, 1), (0, 2), (1, 2)],
+    ],
+    // L
+    [
+        [(2, 0), (0, 1), (1, 1), (2, 1)],
+        [(1, 0), (1, 1), (1, 2), (2, 2)],
+        [(0, 1), (1, 1), (2, 1), (0, 2)],
+        [(0, 0), (1, 0), (1, 1), (1, 2)],
+    ],
+];
+
+// Pieces spawn flat side up and rest on the bottom of their 3x3 box.
+const ARS_SHAPES: ShapeTable = [
+    // I
+    [
+        [(0, 1), (1, 1), (2, 1), (3, 1)],
+        [(2, 0), (2, 1), (2, 2), (2, 3)],
+        [(0, 1), (1, 1), (2, 1), (3, 1)],
+        [(2, 0), (2, 1), (2, 2), (2, 3)],
+    ],
+    // O
+    [
+        [(1, 1), (2, 1), (1, 2), (2, 2)],
+        [(1, 1), (2, 1), (1, 2), (2, 2)],
+        [(1, 1), (2, 1), (1, 2), (2, 2)],
+        [(1, 1), (2, 1), (1, 2), (2, 2)],
+    ],
+    // T
+    [
+        [(0, 1), (1, 1), (2, 1), (1, 2)],
+        [(1, 0), (0, 1), (1, 1), (1, 2)],
+        [(1, 1), (0, 2), (1, 2), (2, 2)],
+        [(1, 0), (1, 1), (2, 1), (1, 2)],
+    ],
+    // S
+    [
+        [(1, 1), (2, 1), (0, 2), (1, 2)],
+        [(0, 0), (0, 1), (1, 1), (1, 2)],
+        [(1, 1), (2, 1), (0, 2), (1, 2)],
+        [(0, 0), (0, 1), (1, 1), (1, 2)],
+    ],
+    // Z
+    [
+        [(0, 1), (1, 1), (1, 2), (2, 2)],
+        [(2, 0), (1, 1), (2, 1), (1, 2)],
+        [(0, 1), (1, 1), (1, 2), (2, 2)],
+        [(2, 0), (1, 1), (2, 1), (1, 2)],
+    ],
+    // J
+    [
+        [(0, 1), (1, 1), (2, 1), (2, 2)],
+        [(1, 0), (1, 1), (0, 2), (1, 2)],
+        [(0, 1), (0, 2), (1, 2), (2, 2)],
+        [(1, 0), (2, 0), (1, 1), (1, 2)],
+    ],
+    // L
+    [
+        [(0, 1), (1, 1), (2, 1), (0, 2)],
+        [(0, 0), (1, 0), (1, 1), (1, 2)],
+        [(2, 1), (0, 2), (1, 2), (2, 2)],
+        [(1, 0), (1, 1), (1, 2), (2, 2)],
+    ],
+];
+
+// Rotation about a fixed centre; the two-state pieces use the right-hand
+// vertical orientation.
+const NES_SHAPES: ShapeTable = [
+    // I
+    [
+        [(0, 2), (1, 2), (2, 2), (3, 2)],
+        [(2, 0), (2, 1), (2, 2), (2, 3)],
+        [(0, 2), (1, 2), (2, 2), (3, 2)],
+        [(2, 0), (2, 1), (2, 2), (2, 3)],
+    ],
+    // O
+    [
+        [(1, 1), (2, 1), (1, 2), (2, 2)],
+        [(1, 1), (2, 1), (1, 2), (2, 2)],
+        [(1, 1), (2, 1), (1, 2), (2, 2)],
+        [(1, 1), (2, 1), (1, 2), (2, 2)],
+    ],
+    // T
+    [
+        [(0, 1), (1, 1), (2, 1), (1, 2)],
+        [(1, 0), (0, 1), (1, 1), (1, 2)],
+        [(1, 0), (0, 1), (1, 1), (2, 1)],
+        [(1, 0), (1, 1), (2, 1), (1, 2)],
+    ],
+    // S
+    [
+        [(1, 1), (2, 1), (0, 2), (1, 2)],
+        [(1, 0), (1, 1), (2, 1), (2, 2)],
+        [(1, 1), (2, 1), (0, 2), (1, 2)],
+        [(1, 0), (1, 1), (2, 1), (2, 2)],
+    ],
+    // Z
+    [
+        [(0, 1), (1, 1), (1, 2), (2, 2)],
+        [(2, 0), (1, 1), (2, 1), (1, 2)],
+        [(0, 1), (1, 1), (1, 2), (2, 2)],
+        [(2, 0), (1, 1), (2, 1), (1, 2)],
+    ],
+    // J
+    [
+        [(0, 1), (1, 1), (2, 1), (2, 2)],
+        [(1, 0), (1, 1), (0, 2), (1, 2)],
+        [(0, 0), (0, 1), (1, 1), (2, 1)],
+        [(1, 0), (2, 0), (1, 1), (1, 2)],
+    ],
+    // L
+    [
+        [(0, 1), (1, 1), (2, 1), (0, 2)],
+        [(0, 0), (1, 0), (1, 1), (1, 2)],
+        [(2, 0), (0, 1), (1, 1), (2, 1)],
+        [(1, 0), (1, 1), (1, 2), (2, 2)],
+    ],
+];
+
+fn srs_kicks(kind: TetrominoType, from: Rotation, to: Rotation) -> &'static [(i32, i32)] {
+    use Rotation::*;
+
+    const JLSTZ_0_R: [(i32, i32); 5] = [(0, 0), (-1, 0), (-1, 1), (0, -2), (-1, -2)];
+    const JLSTZ_R_0: [(i32, i32); 5] = [(0, 0), (1, 0), (1, -1), (0, 2), (1, 2)];
+    const JLSTZ_R_2: [(i32, i32); 5] = [(0, 0), (1, 0), (1, -1), (0, 2), (1, 2)];
+    const JLSTZ_2_R: [(i32, i32); 5] = [(0, 0), (-1, 0), (-1, 1), (0, -2), (-1, -2)];
+    const JLSTZ_2_L: [(i32, i32); 5] = [(0, 0), (1, 0), (1, 1), (0, -2), (1, -2)];
+    const JLSTZ_L_2: [(i32, i32); 5] = [(0, 0), (-1, 0), (-1, -1), (0, 2), (-1, 2)];
+    const JLSTZ_L_0: [(i32, i32); 5] = [(0, 0), (-1, 0), (-1, -1), (0, 2), (-1, 2)];
+    const JLSTZ_0_L: [(i32, i32); 5] = [(0, 0), (1, 0), (1, 1), (0, -2), (1, -2)];
+
+    const I_0_R: [(i32, i32); 5] = [(0, 0), (-2, 0), (1, 0), (-2, -1), (1, 2)];
+    const I_R_0: [(i32, i32); 5] = [(0, 0), (2, 0), (-1, 0), (2, 1), (-1, -2)];
+    const I_R_2: [(i32, i32); 5] = [(0, 0), (-1, 0), (2, 0), (-1, 2), (2, -1)];
+    const I_2_R: [(i32, i32); 5] = [(0, 0), (1, 0), (-2, 0), (1, -2), (-2, 1)];
+    const I_2_L: [(i32, i32); 5] = [(0, 0), (2, 0), (-1, 0), (2, 1), (-1, -2)];
+    const I_L_2: [(i32, i32); 5] = [(0, 0), (-2, 0), (1, 0), (-2, -1), (1, 2)];
+    const I_L_0: [(i32, i32); 5] = [(0, 0), (1, 0), (-2, 0), (1, -2), (-2, 1)];
+    const I_0_L: [(i32, i32); 5] = [(0, 0), (-1, 0), (2, 0), (-1, 2), (2, -1)];
+
+    if kind == TetrominoType::O {
+        return &NO_KICKS;
+    }
+
+    match (kind, from, to) {
+        (TetrominoType::I, North, East) => &I_0_R,
+        (TetrominoType::I, East, North) => &I_R_0,
+        (TetrominoType::I, East, South) => &I_R_2,
+        (TetrominoType::I, South, East) => &I_2_R,
+        (TetrominoType::I, South, West) => &I_2_L,
+        (TetrominoType::I, West, South) => &I_L_2,
+        (TetrominoType::I, West, North) => &I_L_0,
+        (TetrominoType::I, North, West) => &I_0_L,
+        (_, North, East) => &JLSTZ_0_R,
+        (_, East, North) => &JLSTZ_R_0,
+        (_, East, South) => &JLSTZ_R_2,
+        (_, South, East) => &JLSTZ_2_R,
+        (_, South, West) => &JLSTZ_2_L,
+        (_, West, South) => &JLSTZ_L_2,
+        (_, West, North) => &JLSTZ_L_0,
+        (_, North, West) => &JLSTZ_0_L,
+        _ => &JLSTZ_0_R,
+    }
+}
+
+fn srs_plus_kicks(kind: TetrominoType, from: Rotation, to: Rotation) -> &'static [(i32, i32)] {
+    use Rotation::*;
+
+    // Mirror-symmetric I kicks, so clockwise and counter-clockwise behave alike.
+    const I_0_R: [(i32, i32); 5] = [(0, 0), (1, 0), (-2, 0), (-2, -1), (1, 2)];
+    const I_R_0: [(i32, i32); 5] = [(0, 0), (-1, 0), (2, 0), (-1, -2), (2, 1)];
+    const I_R_2: [(i32, i32); 5] = [(0, 0), (-1, 0), (2, 0), (-1, 2), (2, -1)];
+    const I_2_R: [(i32, i32); 5] = [(0, 0), (-2, 0), (1, 0), (-2, 1), (1, -2)];
+    const I_2_L: [(i32, i32); 5] = [(0, 0), (2, 0), (-1, 0), (2, 1), (-1, -2)];
+    const I_L_2: [(i32, i32); 5] = [(0, 0), (1, 0), (-2, 0), (1, -2), (-2, 1)];
+    const I_L_0: [(i32, i32); 5] = [(0, 0), (1, 0), (-2, 0), (1, -2), (-2, 1)];
+    const I_0_L: [(i32, i32); 5] = [(0, 0), (-1, 0), (2, 0), (-1, 2), (2, -1)];
+
+    const FLIP_0_2: [(i32, i32); 6] = [(0, 0), (0, 1), (1, 1), (-1, 1), (1, 0), (-1, 0)];
+    const FLIP_2_0: [(i32, i32); 6] = [(0, 0), (0, -1), (-1, -1), (1, -1), (-1, 0), (1, 0)];
+    const FLIP_R_L: [(i32, i32); 6] = [(0, 0), (1, 0), (1, 2), (1, 1), (0, 2), (0, 1)];
+    const FLIP_L_R: [(i32, i32); 6] = [(0, 0), (-1, 0), (-1, 2), (-1, 1), (0, 2), (0, 1)];
+
+    if kind == TetrominoType::O {
+        return &NO_KICKS;
+    }
+
+    match (kind, from, to) {
+        (_, North, South) => &FLIP_0_2,
+        (_, South, North) => &FLIP_2_0,
+        (_, East, West) => &FLIP_R_L,
+        (_, West, East) => &FLIP_L_R,
+        (TetrominoType::I, North, East) => &I_0_R,
+        (TetrominoType::I, East, North) => &I_R_0,
+        (TetrominoType::I, East, South) => &I_R_2,
+        (TetrominoType::I, South, East) => &I_2_R,
+        (TetrominoType::I, South, West) => &I_2_L,
+        (TetrominoType::I, West, South) => &I_L_2,
+        (TetrominoType::I, West, North) => &I_L_0,
+        (TetrominoType::I, North, West) => &I_0_L,
+        _ => srs_kicks(kind, from, to),
+    }
+}
+
+fn ars_kicks(kind: TetrominoType) -> &'static [(i32, i32)] {
+    const WALL_KICKS: [(i32, i32); 3] = [(0, 0), (1, 0), (-1, 0)];
+
+    // The I piece never kicks in TGM1/TGM2.
+    match kind {
+        TetrominoType::I | TetrominoType::O => &NO_KICKS,
+        _ => &WALL_KICKS,
+    }
+}
@@ -1,22 +1,40 @@
-use crate::game::board::{BOARD_HEIGHT, Board};
-use crate::game::input::GameAction;
-use crate::game::pieces::{Rotation, Tetromino, TetrominoType, spawn_position};
+use crate::game::board::{Board, ClearedRow};
+use crate::game::codec::DecodeError;
+use crate::game::input::{GameAction, HeldButtons};
+use crate::game::pieces::{Rotation, Tetromino, TetrominoType};
+use crate::game::replay::Replay;
+use crate::game::rotation::RotationSystem;
 
 mod actions;
-mod kicks;
+mod builder;
+mod finesse;
+mod garbage;
+mod master;
+mod nes;
 mod rng;
 mod scoring;
+mod snapshot;
 mod timing;
 mod types;
 
 use actions::{
     activate_soft_drop, apply_action, can_move_down, ghost_blocks, lock_active_piece, try_move,
+    update_ghost_cache,
 };
-use rng::{SimpleRng, ensure_queue, refill_bag};
+pub use builder::GameStateBuilder;
+pub use rng::{BagRandomizer, NesRandomizer, PureRandomizer, Randomizer, TgmRandomizer};
+use rng::{PieceGenerator, SimpleRng, ensure_queue};
 use scoring::apply_line_clear;
+pub use snapshot::{GameSnapshot, SNAPSHOT_VERSION};
+pub(crate) use snapshot::{decode_cell, encode_cell, read_config, write_config};
 use timing::{drop_interval_ms, tick};
-pub use types::{GameConfig, RulesConfig, Ruleset, SoundEvent, TSpinKind};
+pub use types::{
+    GameConfig, GameEvent, GameMode, Gravity, LevelTimings, LockDownMode, MAX_PREVIEW_COUNT,
+    MAX_TIMING_STEPS, PendingGarbage, RandomizerKind, RulesConfig, Ruleset, RulesetPreset,
+    SoundEvent, TSpinKind, TimingCurve, TopOutReason,
+};
 
+/// Pieces kept ahead of the active one even when fewer are shown.
 const NEXT_QUEUE_SIZE: usize = 5;
 
 #[derive(Clone, Debug)]
@@ -32,14 +50,28 @@ pub struct GameState {
     pub combo: i32,
     pub back_to_back: bool,
     pub ruleset: Ruleset,
+    pub start_level: u32,
+    pub mode: GameMode,
+    pub randomizer: RandomizerKind,
+    pub rotation_system: RotationSystem,
+    pub all_spin: bool,
+    pub preview_count: usize,
+    pub partial_lock_out: bool,
+    pub gravity: Option<Gravity>,
+    pub timings: TimingCurve,
+    pub initial_actions: bool,
     pub rules: types::RulesConfig,
     pub game_over: bool,
+    /// The mode's goal ended the game rather than a top out.
+    pub mode_complete: bool,
+    pub top_out: Option<TopOutReason>,
     pub paused: bool,
     pub tick_ms: u64,
     pub soft_drop_multiplier: u64,
     pub lock_delay_ms: u64,
     pub lock_reset_limit: u32,
     pub lock_reset_count: u32,
+    pub lock_down_mode: LockDownMode,
     pub base_drop_ms: u64,
     pub soft_drop_grace_ms: u64,
     pub soft_drop_active: bool,
@@ -47,40 +79,90 @@ pub struct GameState {
     pub drop_timer_ms: u64,
     pub lock_timer_ms: u64,
     pub line_clear_timer_ms: u64,
+    /// Length of the running line-clear pause and the rows it took.
+    line_clear_total_ms: u64,
+    cleared_rows: Vec<ClearedRow>,
+    /// Entry delay (ARE) left before the next piece appears.
+    pub spawn_delay_ms: u64,
+    /// A piece has locked and the next is waiting out the line clear and
+    /// entry delay. `active` still holds the locked piece until then.
+    spawn_pending: bool,
+    pub perfect_clear_timer_ms: u64,
     pub landing_flash_timer_ms: u64,
     pub last_lock_cells: [(i32, i32); 4],
     pub ghost_cache: [(i32, i32); 4],
     pub board_revision: u64,
-    sound_events: Vec<SoundEvent>,
-    last_action_rotate: bool,
+    pub sim_time_ms: u64,
+    pub frame: u64,
+    pub pieces_placed: u32,
+    pub finesse_faults: u32,
+    events: Vec<GameEvent>,
+    /// Lowest row the active piece has reached; extended placement only
+    /// clears the reset count below it.
+    lowest_y: i32,
+    /// Buttons the player is holding, as last reported by the input layer.
+    held: HeldButtons,
+    /// Oldest first; applied when a piece locks without clearing lines.
+    pending_garbage: Vec<PendingGarbage>,
+    /// Kick index of the rotation that last moved the active piece; cleared
+    /// by any other move.
+    last_kick: Option<usize>,
+    /// Whether that rotation was a 180° turn; only 90° turns get the TST/fin
+    /// upgrade.
+    last_kick_180: bool,
+    finesse: finesse::FinesseTracker,
     rng: SimpleRng,
+    pieces: PieceGenerator,
+    seed: u64,
+    replay: Option<Replay>,
+    /// Set when the game was started on a prepared board; recordings start
+    /// from it too.
+    start_field: Option<Board>,
 }
 
 impl GameState {
     pub fn new(seed: u64, config: GameConfig) -> Self {
-        let mut rng = SimpleRng::new(seed);
-        let mut next_queue = init_next_queue(&mut rng);
-        let active = spawn_first_piece(&mut next_queue);
+        let mut pieces = PieceGenerator::new(config.randomizer, seed);
+        let preview_count = config.preview_count.min(MAX_PREVIEW_COUNT);
+        let queue_len = queue_len(preview_count);
+        let board = Board::with_size(config.board.clamped());
+        let mut next_queue = Vec::new();
+        ensure_queue(&mut pieces, &mut next_queue, queue_len);
+        let active = spawn_first_piece(&board, &mut next_queue, config.rotation_system);
+        ensure_queue(&mut pieces, &mut next_queue, queue_len);
         let mut state = Self {
-            board: Board::new(),
+            board,
             active,
             hold: None,
-            can_hold: true,
+            can_hold: config.ruleset != Ruleset::Nes,
             next_queue,
             score: 0,
-            level: 0,
+            level: config.start_level,
             lines: 0,
             combo: -1,
             back_to_back: false,
             ruleset: config.ruleset,
+            start_level: config.start_level,
+            mode: config.mode,
+            randomizer: config.randomizer,
+            rotation_system: config.rotation_system,
+            all_spin: config.all_spin,
+            preview_count,
+            partial_lock_out: config.partial_lock_out,
+            gravity: config.gravity,
+            timings: config.timings,
+            initial_actions: config.initial_actions,
             rules: config.rules,
             game_over: false,
+            mode_complete: false,
+            top_out: None,
             paused: false,
             tick_ms: config.tick_ms,
             soft_drop_multiplier: config.soft_drop_multiplier,
             lock_delay_ms: config.lock_delay_ms,
             lock_reset_limit: config.lock_reset_limit,
             lock_reset_count: 0,
+            lock_down_mode: config.lock_down_mode,
             base_drop_ms: config.base_drop_ms,
             soft_drop_grace_ms: config.soft_drop_grace_ms,
             soft_drop_active: false,
@@ -88,29 +170,75 @@ impl GameState {
             drop_timer_ms: 0,
             lock_timer_ms: 0,
             line_clear_timer_ms: 0,
+            line_clear_total_ms: 0,
+            cleared_rows: Vec::new(),
+            spawn_delay_ms: 0,
+            spawn_pending: false,
+            perfect_clear_timer_ms: 0,
             landing_flash_timer_ms: 0,
             last_lock_cells: [(0, 0); 4],
             ghost_cache: [(0, 0); 4],
             board_revision: 1,
-            sound_events: Vec::new(),
-            last_action_rotate: false,
-            rng,
+            sim_time_ms: 0,
+            frame: 0,
+            pieces_placed: 0,
+            finesse_faults: 0,
+            events: Vec::new(),
+            lowest_y: active.y,
+            held: HeldButtons::default(),
+            pending_garbage: Vec::new(),
+            last_kick: None,
+            last_kick_180: false,
+            finesse: finesse::FinesseTracker::default(),
+            rng: SimpleRng::new(seed),
+            pieces,
+            seed,
+            replay: None,
+            start_field: None,
         };
+        if let GameMode::Dig { rows, messiness } = state.mode {
+            garbage::fill_dig_rows(&mut state, rows, messiness);
+        }
+        if state.ruleset == Ruleset::Nes {
+            state.lock_delay_ms = nes::lock_delay_ms(state.level);
+        }
+        if state.mode == GameMode::Master {
+            state.level = 0;
+            state.lock_delay_ms = master::lock_delay_ms(0);
+        }
         actions::update_ghost_cache(&mut state);
+        state.events.push(GameEvent::PieceSpawned {
+            kind: state.active.kind,
+        });
         state
     }
 
+    /// Sets up a game from a given position; see `GameStateBuilder`.
+    pub fn builder() -> GameStateBuilder {
+        GameStateBuilder::default()
+    }
+
     pub fn spawn_next(&mut self) {
-        ensure_queue(&mut self.rng, &mut self.next_queue);
+        self.spawn_from_queue(true);
+    }
 
-        let kind = self.next_queue.remove(0);
-        let (spawn_x, spawn_y) = spawn_position();
-        self.active = Tetromino::new(kind, spawn_x, spawn_y);
+    /// Brings in the next queued piece. IRS and IHS only apply to spawns
+    /// after a lock, not to the one a manual hold pulls in.
+    fn spawn_from_queue(&mut self, initial_actions: bool) {
+        let kind = self.take_next_kind();
+        let (spawn_x, spawn_y) = self.board.spawn_position();
+        self.active = Tetromino::new(kind, spawn_x, spawn_y).with_system(self.rotation_system);
         self.active.rotation = Rotation::North;
-        self.can_hold = true;
+        self.can_hold = self.hold_enabled();
         self.lock_reset_count = 0;
-        self.last_action_rotate = false;
-        actions::update_ghost_cache(self);
+        self.lowest_y = self.active.y;
+        self.last_kick = None;
+        self.finesse = finesse::FinesseTracker::default();
+        self.spawn_pending = false;
+        self.spawn_delay_ms = 0;
+        if initial_actions && self.initial_actions {
+            actions::apply_initial_actions(self);
+        }
 
         if !self.board.can_place(
             &self.active,
@@ -118,39 +246,226 @@ impl GameState {
             self.active.y,
             self.active.rotation,
         ) {
-            self.game_over = true;
-            self.sound_events.push(SoundEvent::GameOver);
+            actions::top_out(self, TopOutReason::BlockOut);
+        } else {
+            actions::sink_if_instant(self);
+            self.events.push(GameEvent::PieceSpawned {
+                kind: self.active.kind,
+            });
         }
+        actions::update_ghost_cache(self);
     }
 
     pub fn apply_line_clear(&mut self, cleared: usize, t_spin: TSpinKind) {
-        apply_line_clear(self, cleared, t_spin);
+        apply_line_clear(self, cleared, t_spin, false);
     }
 
     pub fn is_lock_row(&self) -> bool {
-        self.active.y >= BOARD_HEIGHT as i32 - 1
+        self.active.y >= self.board.height() as i32 - 1
     }
 
     pub fn drop_interval_ms(&self, soft_drop: bool) -> u64 {
         drop_interval_ms(self, soft_drop)
     }
 
+    /// Advances the simulation by an arbitrary amount of time. Replays only
+    /// capture fixed `step`s, so recorded games must be driven through `step`.
     pub fn tick(&mut self, elapsed_ms: u64, soft_drop: bool) {
         tick(self, elapsed_ms, soft_drop);
     }
 
+    /// Runs one fixed simulation step of `tick_ms`.
+    pub fn step(&mut self, soft_drop: bool) {
+        if self.paused || self.game_over {
+            return;
+        }
+        if let Some(replay) = self.replay.as_mut() {
+            replay.record_step(self.frame, soft_drop);
+        }
+        tick(self, self.step_ms(), soft_drop);
+        self.frame += 1;
+    }
+
+    pub fn step_ms(&self) -> u64 {
+        self.tick_ms.max(1)
+    }
+
     pub fn apply_action(&mut self, action: GameAction) {
+        // Restart ends the recording; `reset` opens a fresh one for the next run.
+        if let Some(replay) = self.replay.as_mut()
+            && action != GameAction::Restart
+        {
+            replay.record_action(self.frame, action);
+        }
         apply_action(self, action);
     }
 
-    pub fn take_sound_events(&mut self) -> Vec<SoundEvent> {
-        std::mem::take(&mut self.sound_events)
+    /// Reports which rotate and hold buttons are down. Only changes are
+    /// recorded, like soft drop in `step`.
+    pub fn set_held_buttons(&mut self, held: HeldButtons) {
+        if held == self.held {
+            return;
+        }
+        if let Some(replay) = self.replay.as_mut() {
+            replay.record_held(self.frame, held);
+        }
+        self.held = held;
+    }
+
+    pub fn held_buttons(&self) -> HeldButtons {
+        self.held
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Starts logging every action and step from the current state. Only
+    /// meaningful on a freshly created game, since playback starts from the
+    /// seed and the starting field.
+    pub fn start_recording(&mut self) {
+        let mut replay = Replay::new(self.seed, self.config());
+        replay.field = self.start_field.clone();
+        self.replay = Some(replay);
+    }
+
+    /// Swaps the board of a freshly created game for `field`.
+    pub(crate) fn start_from_field(&mut self, field: Board) {
+        self.board = field.clone();
+        self.start_field = Some(field);
+        self.board_revision = self.board_revision.wrapping_add(1);
+        update_ghost_cache(self);
+    }
+
+    pub fn replay(&self) -> Option<&Replay> {
+        self.replay.as_ref()
+    }
+
+    pub fn take_replay(&mut self) -> Option<Replay> {
+        self.replay.take()
+    }
+
+    /// The upcoming pieces the player is allowed to see, soonest first.
+    pub fn preview(&self) -> &[TetrominoType] {
+        &self.next_queue[..self.preview_count.min(self.next_queue.len())]
+    }
+
+    /// Everything that happened since the last call, oldest first.
+    pub fn take_events(&mut self) -> Vec<GameEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    /// Queues `rows` garbage rows open at `hole_column`, clamped to the last
+    /// column. They rise when the next piece locks without clearing, and line
+    /// clears cancel them first.
+    pub fn queue_garbage(&mut self, rows: u32, hole_column: usize) {
+        let hole_column = hole_column.min(self.board.width() - 1);
+        if rows > 0 {
+            self.pending_garbage
+                .push(PendingGarbage { rows, hole_column });
+        }
+    }
+
+    pub fn pending_garbage(&self) -> &[PendingGarbage] {
+        &self.pending_garbage
+    }
+
+    pub fn pending_garbage_rows(&self) -> u32 {
+        self.pending_garbage
+            .iter()
+            .map(|garbage| garbage.rows)
+            .sum()
     }
 
     pub fn is_line_clear_active(&self) -> bool {
         self.line_clear_timer_ms > 0
     }
 
+    /// Rows the last lock cleared, top to bottom, with their contents from
+    /// before the clear. Empty once the line-clear pause is over.
+    pub fn cleared_rows(&self) -> &[ClearedRow] {
+        if self.is_line_clear_active() {
+            &self.cleared_rows
+        } else {
+            &[]
+        }
+    }
+
+    /// How far the line-clear pause has run, from 0 to 1.
+    pub fn line_clear_progress(&self) -> f32 {
+        if self.line_clear_total_ms == 0 {
+            return 1.0;
+        }
+        let left = self.line_clear_timer_ms.min(self.line_clear_total_ms);
+        1.0 - left as f32 / self.line_clear_total_ms as f32
+    }
+
+    /// The last piece has locked and the next one has not appeared yet.
+    pub fn is_spawn_delay_active(&self) -> bool {
+        self.spawn_pending
+    }
+
+    /// Entry, line-clear and effect timings for the current level.
+    pub fn current_timings(&self) -> LevelTimings {
+        match self.mode {
+            GameMode::Master => master::timings(self.level),
+            _ => self.timings.at(self.level),
+        }
+    }
+
+    /// Current fall speed when gravity is measured in G rather than taken
+    /// from the level-based drop intervals.
+    pub fn current_gravity(&self) -> Option<Gravity> {
+        match self.mode {
+            GameMode::Master => Some(master::gravity(self.level)),
+            _ => self.gravity,
+        }
+    }
+
+    /// Grade earned so far in master mode.
+    pub fn master_grade(&self) -> Option<&'static str> {
+        match self.mode {
+            GameMode::Master => Some(master::grade(self)),
+            _ => None,
+        }
+    }
+
+    /// Time left on the clock in timed modes.
+    pub fn time_remaining_ms(&self) -> Option<u64> {
+        match self.mode {
+            GameMode::Ultra { duration_ms } => Some(duration_ms.saturating_sub(self.sim_time_ms)),
+            GameMode::Marathon
+            | GameMode::Sprint { .. }
+            | GameMode::Dig { .. }
+            | GameMode::Master => None,
+        }
+    }
+
+    /// Starting garbage rows not yet dug out, in dig mode.
+    pub fn garbage_remaining(&self) -> Option<usize> {
+        match self.mode {
+            GameMode::Dig { .. } => Some(self.board.garbage_rows()),
+            _ => None,
+        }
+    }
+
+    pub fn time_warning(&self) -> bool {
+        self.time_remaining_ms()
+            .is_some_and(|left| left > 0 && left <= GameMode::TIME_WARNING_MS)
+    }
+
+    /// Pieces locked per second of simulated play.
+    pub fn pieces_per_second(&self) -> f64 {
+        if self.sim_time_ms == 0 {
+            return 0.0;
+        }
+        self.pieces_placed as f64 * 1000.0 / self.sim_time_ms as f64
+    }
+
+    pub fn perfect_clear_active(&self) -> bool {
+        self.perfect_clear_timer_ms > 0
+    }
+
     pub fn lock_reset_remaining(&self) -> u32 {
         self.lock_reset_limit.saturating_sub(self.lock_reset_count)
     }
@@ -159,8 +474,19 @@ impl GameState {
         !self.can_move_down()
     }
 
+    /// Classic and NES rules score lines without combos or back-to-back.
     pub fn is_classic_ruleset(&self) -> bool {
-        self.ruleset == Ruleset::Classic
+        self.ruleset != Ruleset::Modern
+    }
+
+    /// NES rules have no hold, so `can_hold` stays false.
+    pub fn hold_enabled(&self) -> bool {
+        self.ruleset != Ruleset::Nes
+    }
+
+    /// NES rules draw no ghost piece.
+    pub fn shows_ghost(&self) -> bool {
+        self.ruleset != Ruleset::Nes
     }
 
     pub fn lock_warning_active(&self) -> bool {
@@ -186,8 +512,26 @@ impl GameState {
     }
 
     pub fn reset(&mut self) {
+        self.reset_with(self.config());
+    }
+
+    /// Starts a new game on `config`, keeping the seed sequence and whether
+    /// games are recorded.
+    pub fn reset_with(&mut self, config: GameConfig) {
         let seed = self.rng.next_u32() as u64;
-        *self = GameState::new(seed, self.current_config());
+        let recording = self.replay.is_some();
+        *self = GameState::new(seed, config);
+        if recording {
+            self.start_recording();
+        }
+    }
+
+    pub fn to_snapshot(&self) -> GameSnapshot {
+        snapshot::encode(self)
+    }
+
+    pub fn from_snapshot(snapshot: &GameSnapshot) -> Result<Self, DecodeError> {
+        snapshot::decode(snapshot)
     }
 
     pub fn board_revision(&self) -> u64 {
@@ -202,15 +546,28 @@ impl GameState {
         self.soft_drop_active
     }
 
-    fn current_config(&self) -> GameConfig {
+    /// The configuration this game runs on, as `reset` would reuse it.
+    pub fn config(&self) -> GameConfig {
         GameConfig {
             tick_ms: self.tick_ms,
             soft_drop_multiplier: self.soft_drop_multiplier,
             lock_delay_ms: self.lock_delay_ms,
             lock_reset_limit: self.lock_reset_limit,
+            lock_down_mode: self.lock_down_mode,
             base_drop_ms: self.base_drop_ms,
             soft_drop_grace_ms: self.soft_drop_grace_ms,
             ruleset: self.ruleset,
+            start_level: self.start_level,
+            mode: self.mode,
+            randomizer: self.randomizer,
+            rotation_system: self.rotation_system,
+            all_spin: self.all_spin,
+            preview_count: self.preview_count,
+            partial_lock_out: self.partial_lock_out,
+            board: self.board.size(),
+            gravity: self.gravity,
+            timings: self.timings,
+            initial_actions: self.initial_actions,
             rules: self.rules,
         }
     }
@@ -219,6 +576,14 @@ impl GameState {
         ghost_blocks(self)
     }
 
+    fn take_next_kind(&mut self) -> TetrominoType {
+        let queue_len = queue_len(self.preview_count);
+        ensure_queue(&mut self.pieces, &mut self.next_queue, queue_len);
+        let kind = self.next_queue.remove(0);
+        ensure_queue(&mut self.pieces, &mut self.next_queue, queue_len);
+        kind
+    }
+
     fn try_move(&mut self, dx: i32, dy: i32) -> bool {
         try_move(self, dx, dy)
     }
@@ -232,15 +597,16 @@ impl GameState {
     }
 }
 
-fn init_next_queue(rng: &mut SimpleRng) -> Vec<TetrominoType> {
-    let mut next_queue = Vec::new();
-    refill_bag(rng, &mut next_queue);
-    ensure_queue(rng, &mut next_queue);
-    next_queue
+fn queue_len(preview_count: usize) -> usize {
+    preview_count.max(NEXT_QUEUE_SIZE)
 }
 
-fn spawn_first_piece(next_queue: &mut Vec<TetrominoType>) -> Tetromino {
+fn spawn_first_piece(
+    board: &Board,
+    next_queue: &mut Vec<TetrominoType>,
+    system: RotationSystem,
+) -> Tetromino {
     let first_kind = next_queue.remove(0);
-    let (spawn_x, spawn_y) = spawn_position();
-    Tetromino::new(first_kind, spawn_x, spawn_y)
+    let (spawn_x, spawn_y) = board.spawn_position();
+    Tetromino::new(first_kind, spawn_x, spawn_y).with_system(system)
 }
@@ -1,9 +1,11 @@
 use crate::game::input::GameAction;
-use crate::game::pieces::{Tetromino, TetrominoType, spawn_position};
+use crate::game::pieces::{Rotation, Tetromino, TetrominoType};
 
-use super::kicks::srs_kicks;
-use super::scoring::t_spin_kind;
-use super::{GameState, Ruleset, SoundEvent, TSpinKind};
+use super::finesse::{FinesseTracker, judge_placement, record_input};
+use super::garbage::{cancel_garbage, raise_garbage};
+use super::master;
+use super::scoring::{apply_line_clear, apply_perfect_clear, t_spin_kind};
+use super::{GameEvent, GameMode, GameState, LockDownMode, Ruleset, TSpinKind, TopOutReason};
 
 pub(super) fn apply_action(state: &mut GameState, action: GameAction) {
     if state.game_over && action != GameAction::Restart {
@@ -12,14 +14,25 @@ pub(super) fn apply_action(state: &mut GameState, action: GameAction) {
     if state.paused && action != GameAction::Pause && action != GameAction::Restart {
         return;
     }
+    // Nothing to control until the next piece spawns. Held moves keep
+    // charging DAS on the input side and land once it does.
+    if state.spawn_pending && action != GameAction::Pause && action != GameAction::Restart {
+        return;
+    }
 
+    record_input(state, action);
     match action {
         GameAction::MoveLeft => handle_move(state, -1),
         GameAction::MoveRight => handle_move(state, 1),
         GameAction::SoftDrop => handle_soft_drop(state),
         GameAction::HardDrop => handle_hard_drop(state),
-        GameAction::RotateCw => handle_rotate(state, true),
-        GameAction::RotateCcw => handle_rotate(state, false),
+        GameAction::RotateCw => handle_rotate(state, state.active.rotation.cw()),
+        GameAction::RotateCcw => handle_rotate(state, state.active.rotation.ccw()),
+        GameAction::Rotate180 => {
+            if state.active.system.supports_180() {
+                handle_rotate(state, state.active.rotation.flip());
+            }
+        }
         GameAction::Hold => handle_hold(state),
         GameAction::Pause => handle_pause(state),
         GameAction::Restart => handle_restart(state),
@@ -27,18 +40,25 @@ pub(super) fn apply_action(state: &mut GameState, action: GameAction) {
 }
 
 fn handle_move(state: &mut GameState, dx: i32) {
-    try_move(state, dx, 0);
-    state.last_action_rotate = false;
-    state.sound_events.push(SoundEvent::Move);
+    if try_move(state, dx, 0) {
+        state.events.push(GameEvent::Moved { dx });
+        sink_if_instant(state);
+    } else {
+        state.events.push(GameEvent::MoveBlocked { dx });
+    }
+    state.last_kick = None;
 }
 
 fn handle_soft_drop(state: &mut GameState) {
-    if try_move(state, 0, 1) {
+    let rows = if try_move(state, 0, 1) {
         state.score = state.score.saturating_add(1);
-    }
+        1
+    } else {
+        0
+    };
     activate_soft_drop(state);
-    state.last_action_rotate = false;
-    state.sound_events.push(SoundEvent::SoftDrop);
+    state.last_kick = None;
+    state.events.push(GameEvent::SoftDropped { rows });
 }
 
 fn handle_hard_drop(state: &mut GameState) {
@@ -46,18 +66,23 @@ fn handle_hard_drop(state: &mut GameState) {
     while try_move(state, 0, 1) {
         dropped += 1;
     }
-    if dropped > 0 {
+    // NES has no hard drop to reward.
+    if dropped > 0 && state.ruleset != Ruleset::Nes {
         state.score = state.score.saturating_add(dropped * 2);
     }
-    state.sound_events.push(SoundEvent::HardDrop);
+    state.events.push(GameEvent::HardDropped { rows: dropped });
     lock_active_piece(state);
     state.lock_timer_ms = 0;
     state.drop_timer_ms = 0;
 }
 
-fn handle_rotate(state: &mut GameState, clockwise: bool) {
-    state.last_action_rotate = try_rotate(state, clockwise);
-    state.sound_events.push(SoundEvent::Rotate);
+fn handle_rotate(state: &mut GameState, target: Rotation) {
+    state.last_kick_180 = target == state.active.rotation.flip();
+    state.last_kick = try_rotate(state, target);
+    if let Some(kick_index) = state.last_kick {
+        state.events.push(GameEvent::Rotated { kick_index });
+        sink_if_instant(state);
+    }
 }
 
 fn handle_hold(state: &mut GameState) {
@@ -66,20 +91,32 @@ fn handle_hold(state: &mut GameState) {
     }
 
     let current_kind = state.active.kind;
+    state.events.push(GameEvent::Held { kind: current_kind });
     if let Some(held_kind) = state.hold {
         state.hold = Some(current_kind);
         state.active = spawn_piece(state, held_kind);
+        state.lock_reset_count = 0;
+        state.lowest_y = state.active.y;
+        if !state.game_over {
+            sink_if_instant(state);
+            state.events.push(GameEvent::PieceSpawned {
+                kind: state.active.kind,
+            });
+        }
     } else {
         state.hold = Some(current_kind);
-        state.spawn_next();
+        state.spawn_from_queue(false);
     }
     state.can_hold = false;
-    state.last_action_rotate = false;
-    state.sound_events.push(SoundEvent::Hold);
+    state.last_kick = None;
+    state.finesse = FinesseTracker::default();
 }
 
 fn handle_pause(state: &mut GameState) {
     state.paused = !state.paused;
+    state.events.push(GameEvent::Paused {
+        paused: state.paused,
+    });
 }
 
 fn handle_restart(state: &mut GameState) {
@@ -87,19 +124,62 @@ fn handle_restart(state: &mut GameState) {
 }
 
 pub(super) fn spawn_piece(state: &mut GameState, kind: TetrominoType) -> Tetromino {
-    let (spawn_x, spawn_y) = spawn_position();
-    let piece = Tetromino::new(kind, spawn_x, spawn_y);
+    let (spawn_x, spawn_y) = state.board.spawn_position();
+    let piece = Tetromino::new(kind, spawn_x, spawn_y).with_system(state.rotation_system);
     if !state
         .board
         .can_place(&piece, piece.x, piece.y, piece.rotation)
     {
-        state.game_over = true;
-        state.sound_events.push(SoundEvent::GameOver);
+        top_out(state, TopOutReason::BlockOut);
     }
     update_ghost_cache(state);
     piece
 }
 
+/// IHS then IRS for a piece that is spawning: a held hold button swaps it
+/// out, and a held rotate button turns whatever comes out if it fits
+/// there. Neither uses kicks.
+pub(super) fn apply_initial_actions(state: &mut GameState) {
+    let held = state.held;
+    if held.hold && state.can_hold {
+        let current = state.active.kind;
+        let next = match state.hold {
+            Some(kind) => kind,
+            None => state.take_next_kind(),
+        };
+        state.hold = Some(current);
+        let (spawn_x, spawn_y) = state.board.spawn_position();
+        state.active = Tetromino::new(next, spawn_x, spawn_y).with_system(state.rotation_system);
+        state.can_hold = false;
+        state.events.push(GameEvent::Held { kind: current });
+    }
+
+    let piece = state.active;
+    let target = if held.rotate_cw {
+        Some(piece.rotation.cw())
+    } else if held.rotate_ccw {
+        Some(piece.rotation.ccw())
+    } else if held.rotate_180 && piece.system.supports_180() {
+        Some(piece.rotation.flip())
+    } else {
+        None
+    };
+    if let Some(target) = target
+        && state.board.can_place(&piece, piece.x, piece.y, target)
+    {
+        state.active.rotation = target;
+        state.events.push(GameEvent::Rotated { kick_index: 0 });
+    }
+}
+
+/// Under 20G the active piece never hangs in the air: it falls to the stack
+/// as soon as it spawns or moves.
+pub(super) fn sink_if_instant(state: &mut GameState) {
+    if state.current_gravity().is_some_and(|g| g.is_instant()) {
+        while try_move(state, 0, 1) {}
+    }
+}
+
 pub(super) fn activate_soft_drop(state: &mut GameState) {
     state.soft_drop_active = true;
     state.soft_drop_timeout_ms = state.soft_drop_grace_ms;
@@ -119,20 +199,26 @@ pub(super) fn try_move(state: &mut GameState, dx: i32, dy: i32) -> bool {
         state.active.x = new_x;
         state.active.y = new_y;
         update_ghost_cache(state);
-        handle_lock_reset(state);
+        handle_lock_reset(state, dy > 0);
         return true;
     }
     false
 }
 
-pub(super) fn try_rotate(state: &mut GameState, clockwise: bool) -> bool {
-    let next_rotation = if clockwise {
-        state.active.rotation.cw()
-    } else {
-        state.active.rotation.ccw()
-    };
-    let kicks = srs_kicks(state.active.kind, state.active.rotation, next_rotation);
-    for (dx, dy) in kicks.iter() {
+/// Returns the index of the kick that let the rotation through, if any.
+pub(super) fn try_rotate(state: &mut GameState, next_rotation: Rotation) -> Option<usize> {
+    let piece = state.active;
+    let kicks = piece
+        .system
+        .kicks(piece.kind, piece.rotation, next_rotation);
+    for (index, (dx, dy)) in kicks.iter().enumerate() {
+        if index == 1
+            && piece.system.blocks_kick(piece.kind, next_rotation, |x, y| {
+                state.board.is_occupied(piece.x + x, piece.y + y)
+            })
+        {
+            return None;
+        }
         let new_x = state.active.x + dx;
         let new_y = state.active.y + dy;
         if state
@@ -143,12 +229,12 @@ pub(super) fn try_rotate(state: &mut GameState, clockwise: bool) -> bool {
             state.active.y = new_y;
             state.active.rotation = next_rotation;
             update_ghost_cache(state);
-            handle_lock_reset(state);
-            return true;
+            handle_lock_reset(state, *dy > 0);
+            return Some(index);
         }
     }
 
-    false
+    None
 }
 
 pub(super) fn can_move_down(state: &GameState) -> bool {
@@ -160,16 +246,29 @@ pub(super) fn can_move_down(state: &GameState) -> bool {
     )
 }
 
-pub(super) fn handle_lock_reset(state: &mut GameState) {
-    if can_move_down(state) {
-        state.lock_timer_ms = 0;
+/// Runs after every successful move or rotation; `dropped` is set when the
+/// piece ended up lower than it was.
+pub(super) fn handle_lock_reset(state: &mut GameState, dropped: bool) {
+    let new_lowest = dropped && state.active.y > state.lowest_y;
+    if new_lowest {
+        state.lowest_y = state.active.y;
         state.lock_reset_count = 0;
-        return;
     }
-
-    if state.lock_reset_count < state.lock_reset_limit {
-        state.lock_timer_ms = 0;
-        state.lock_reset_count += 1;
+    match state.lock_down_mode {
+        LockDownMode::Classic => {
+            if dropped {
+                state.lock_timer_ms = 0;
+            }
+        }
+        LockDownMode::Infinite => state.lock_timer_ms = 0,
+        LockDownMode::ExtendedPlacement => {
+            if new_lowest || can_move_down(state) {
+                state.lock_timer_ms = 0;
+            } else if state.lock_reset_count < state.lock_reset_limit {
+                state.lock_timer_ms = 0;
+                state.lock_reset_count += 1;
+            }
+        }
     }
 }
 
@@ -179,21 +278,100 @@ pub(super) fn lock_active_piece(state: &mut GameState) {
     } else {
         TSpinKind::None
     };
-    set_landing_flash(state);
+    // Delays follow the level the piece locked at.
+    let timings = state.current_timings();
+    judge_placement(state);
+    state.pieces_placed += 1;
+    set_landing_flash(state, timings.landing_flash_ms);
+    state.events.push(GameEvent::PieceLocked {
+        cells: state.last_lock_cells,
+    });
     state.board.lock_piece(&state.active);
-    let cleared = state.board.clear_lines();
+    if let Some(reason) = lock_out(state) {
+        state.board_revision = state.board_revision.wrapping_add(1);
+        top_out(state, reason);
+        return;
+    }
+    let cleared_rows = state.board.clear_lines();
+    let cleared = cleared_rows.len();
     state.board_revision = state.board_revision.wrapping_add(1);
-    state.apply_line_clear(cleared, t_spin);
-    state.spawn_next();
-    state.last_action_rotate = false;
+    let perfect_clear = cleared > 0 && state.board.is_empty();
+    if perfect_clear {
+        apply_perfect_clear(state, cleared);
+    }
+    let level = state.level;
+    apply_line_clear(state, cleared, t_spin, perfect_clear);
+    if state.mode == GameMode::Master {
+        master::advance_level(state, cleared);
+    }
+    if state.level > level {
+        state.events.push(GameEvent::LevelUp { level: state.level });
+    }
+    if state.line_clear_timer_ms > 0 {
+        state.line_clear_total_ms = state.line_clear_timer_ms;
+        state.cleared_rows = cleared_rows;
+    }
+    if cleared > 0 {
+        cancel_garbage(state, cleared as u32);
+    } else if !raise_garbage(state) {
+        top_out(state, TopOutReason::GarbageOut);
+        return;
+    }
+    if goal_reached(state) {
+        state.game_over = true;
+        state.mode_complete = true;
+        return;
+    }
+    state.last_kick = None;
+    state.spawn_delay_ms = if cleared > 0 {
+        timings.line_are_ms
+    } else {
+        timings.are_ms
+    };
+    if state.line_clear_timer_ms == 0 && state.spawn_delay_ms == 0 {
+        state.spawn_next();
+    } else {
+        state.spawn_pending = true;
+    }
+}
+
+/// Checked on the cells just locked, before any lines clear.
+fn lock_out(state: &GameState) -> Option<TopOutReason> {
+    let hidden = state
+        .last_lock_cells
+        .iter()
+        .filter(|&&(_, y)| state.board.is_hidden_row(y))
+        .count();
+    if hidden == state.last_lock_cells.len() {
+        Some(TopOutReason::LockOut)
+    } else if hidden > 0 && state.partial_lock_out {
+        Some(TopOutReason::PartialLockOut)
+    } else {
+        None
+    }
+}
+
+pub(super) fn top_out(state: &mut GameState, reason: TopOutReason) {
+    state.game_over = true;
+    state.top_out = Some(reason);
+    state.events.push(GameEvent::TopOut { reason });
+}
+
+fn goal_reached(state: &GameState) -> bool {
+    match state.mode {
+        GameMode::Marathon | GameMode::Ultra { .. } => false,
+        GameMode::Sprint { lines } => state.lines >= lines,
+        GameMode::Dig { .. } => state.board.garbage_rows() == 0,
+        GameMode::Master => state.level >= master::MAX_LEVEL,
+    }
 }
 
-pub(super) fn set_landing_flash(state: &mut GameState) {
+pub(super) fn set_landing_flash(state: &mut GameState, duration_ms: u64) {
     let blocks = state.active.blocks(state.active.rotation);
     for (index, (dx, dy)) in blocks.iter().enumerate() {
         state.last_lock_cells[index] = (state.active.x + dx, state.active.y + dy);
     }
-    state.landing_flash_timer_ms = 120;
+    state.landing_flash_timer_ms = duration_ms;
 }
 
 pub(super) fn update_ghost_cache(state: &mut GameState) {
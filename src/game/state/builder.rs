@@ -0,0 +1,83 @@
+use crate::game::board::{AsciiError, Board};
+use crate::game::pieces::{Tetromino, TetrominoType};
+
+use super::actions::update_ghost_cache;
+use super::rng::ensure_queue;
+use super::{GameConfig, GameEvent, GameState, queue_len};
+
+/// Sets up a position to play from: a board in the `Board::from_ascii`
+/// format plus the active piece, hold and upcoming pieces. Anything left
+/// unset comes from a normal `GameState::new` with the same seed and config.
+/// Recordings of a built game start from its board but deal pieces from
+/// the seed.
+#[derive(Clone, Debug, Default)]
+pub struct GameStateBuilder {
+    seed: u64,
+    config: GameConfig,
+    board: Option<String>,
+    active: Option<Tetromino>,
+    hold: Option<TetrominoType>,
+    queue: Option<Vec<TetrominoType>>,
+}
+
+impl GameStateBuilder {
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    pub fn config(mut self, config: GameConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Rows for the bottom of the board, which keeps the config's size.
+    pub fn board(mut self, rows: &str) -> Self {
+        self.board = Some(rows.to_string());
+        self
+    }
+
+    /// Takes the config's rotation system.
+    pub fn active(mut self, piece: Tetromino) -> Self {
+        self.active = Some(piece);
+        self
+    }
+
+    pub fn hold(mut self, kind: TetrominoType) -> Self {
+        self.hold = Some(kind);
+        self
+    }
+
+    /// The next pieces, soonest first; the randomizer deals the rest.
+    pub fn queue(mut self, kinds: impl IntoIterator<Item = TetrominoType>) -> Self {
+        self.queue = Some(kinds.into_iter().collect());
+        self
+    }
+
+    pub fn build(self) -> Result<GameState, AsciiError> {
+        let mut state = GameState::new(self.seed, self.config);
+        if let Some(rows) = &self.board {
+            state.start_from_field(Board::from_ascii_sized(state.board.size(), rows)?);
+        }
+        if let Some(piece) = self.active {
+            state.active = piece.with_system(state.rotation_system);
+            state.lowest_y = state.active.y;
+            state.events = vec![GameEvent::PieceSpawned {
+                kind: state.active.kind,
+            }];
+        }
+        if let Some(kind) = self.hold {
+            state.hold = Some(kind);
+        }
+        if let Some(queue) = self.queue {
+            state.next_queue = queue;
+            ensure_queue(
+                &mut state.pieces,
+                &mut state.next_queue,
+                queue_len(state.preview_count),
+            );
+        }
+        update_ghost_cache(&mut state);
+        Ok(state)
+    }
+}
@@ -0,0 +1,147 @@
+use std::collections::VecDeque;
+
+use crate::game::board::{Board, BoardSize, VISIBLE_HEIGHT};
+use crate::game::input::GameAction;
+use crate::game::pieces::{Rotation, Tetromino, TetrominoType};
+use crate::game::rotation::RotationSystem;
+
+use super::GameState;
+
+/// Key presses spent on the active piece. A run of moves in one direction that
+/// ends against a wall or the stack counts as a single held (DAS) press.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(super) struct FinesseTracker {
+    pub(super) presses: u32,
+    pub(super) run_dir: i32,
+    pub(super) run_len: u32,
+    pub(super) soft_dropped: bool,
+}
+
+pub(super) fn record_input(state: &mut GameState, action: GameAction) {
+    match action {
+        GameAction::MoveLeft | GameAction::MoveRight => {
+            let dir = if action == GameAction::MoveLeft {
+                -1
+            } else {
+                1
+            };
+            if state.finesse.run_dir != dir {
+                flush_run(state);
+                state.finesse.run_dir = dir;
+            }
+            state.finesse.run_len += 1;
+        }
+        GameAction::RotateCw | GameAction::RotateCcw | GameAction::Rotate180 => {
+            flush_run(state);
+            state.finesse.presses += 1;
+        }
+        GameAction::SoftDrop => state.finesse.soft_dropped = true,
+        _ => {}
+    }
+}
+
+/// Adds this placement's wasted presses to `finesse_faults`. Soft-dropped
+/// pieces are skipped since tucks and spins have no fixed optimum.
+pub(super) fn judge_placement(state: &mut GameState) {
+    flush_run(state);
+    let tracker = std::mem::take(&mut state.finesse);
+    if tracker.soft_dropped {
+        return;
+    }
+    let piece = state.active;
+    let width = state.board.width();
+    if let Some(optimal) = min_presses(piece.system, piece.kind, piece.rotation, piece.x, width) {
+        state.finesse_faults += tracker.presses.saturating_sub(optimal);
+    }
+}
+
+fn flush_run(state: &mut GameState) {
+    let tracker = &mut state.finesse;
+    if tracker.run_len == 0 {
+        return;
+    }
+    let piece = &state.active;
+    let blocked = !state
+        .board
+        .can_place(piece, piece.x + tracker.run_dir, piece.y, piece.rotation);
+    tracker.presses += if blocked { 1 } else { tracker.run_len };
+    tracker.run_dir = 0;
+    tracker.run_len = 0;
+}
+
+/// Fewest presses (taps, DAS to a wall, rotations) that take a fresh piece
+/// from spawn to a spot covering the same columns as `rotation` at `x` on an
+/// empty board `width` cells wide.
+pub(super) fn min_presses(
+    system: RotationSystem,
+    kind: TetrominoType,
+    rotation: Rotation,
+    x: i32,
+    width: usize,
+) -> Option<u32> {
+    // Far enough from the top that no rotation is pushed around by the ceiling.
+    const Y: i32 = 8;
+    let board = Board::with_size(BoardSize::with_buffer(width, VISIBLE_HEIGHT));
+    let mut piece = Tetromino::new(kind, 0, Y).with_system(system);
+    let footprint = |piece: &Tetromino, rotation: Rotation, x: i32| {
+        let blocks = piece.blocks(rotation);
+        let top = blocks.iter().map(|&(_, dy)| dy).min().unwrap_or(0);
+        let mut cells = blocks.map(|(dx, dy)| (x + dx, dy - top));
+        cells.sort_unstable();
+        cells
+    };
+    let target = footprint(&piece, rotation, x);
+
+    let (spawn_x, _) = board.spawn_position();
+    let width = width as i32;
+    // Pieces sit at most 3 cells left of their box origin, so x spans -3..width.
+    let index =
+        |rotation: Rotation, x: i32| rotation.index() * (width as usize + 3) + (x + 3) as usize;
+    let mut dist = vec![u32::MAX; 4 * (width as usize + 3)];
+    let mut queue = VecDeque::new();
+    dist[index(Rotation::North, spawn_x)] = 0;
+    queue.push_back((Rotation::North, spawn_x));
+
+    while let Some((rot, px)) = queue.pop_front() {
+        let d = dist[index(rot, px)];
+        if footprint(&piece, rot, px) == target {
+            return Some(d);
+        }
+        piece.rotation = rot;
+        let fits = |x: i32, r: Rotation| board.can_place(&piece, x, Y, r);
+
+        let mut next = Vec::with_capacity(7);
+        for dir in [-1, 1] {
+            if fits(px + dir, rot) {
+                next.push((rot, px + dir));
+                let mut end = px + dir;
+                while fits(end + dir, rot) {
+                    end += dir;
+                }
+                next.push((rot, end));
+            }
+        }
+        let mut turns = vec![rot.cw(), rot.ccw()];
+        if system.supports_180() {
+            turns.push(rot.flip());
+        }
+        for to in turns {
+            let kick = system
+                .kicks(kind, rot, to)
+                .iter()
+                .find(|&&(dx, dy)| board.can_place(&piece, px + dx, Y + dy, to));
+            if let Some(&(dx, _)) = kick {
+                next.push((to, px + dx));
+            }
+        }
+
+        for (r, nx) in next {
+            let slot = &mut dist[index(r, nx)];
+            if *slot == u32::MAX {
+                *slot = d + 1;
+                queue.push_back((r, nx));
+            }
+        }
+    }
+    None
+}
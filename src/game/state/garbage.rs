@@ -0,0 +1,52 @@
+use super::GameState;
+
+/// Fills the bottom of the board with the starting rows of a dig game, at
+/// most one screen's worth. Holes come from the state's seeded RNG.
+pub(super) fn fill_dig_rows(state: &mut GameState, rows: u32, messiness: u8) {
+    let width = state.board.width();
+    let rows = (rows as usize).min(state.board.size().visible_height);
+    let mut hole = state.rng.next_range(width);
+    for row in 0..rows {
+        if row > 0 && state.rng.next_range(100) < messiness as usize {
+            hole = (hole + 1 + state.rng.next_range(width - 1)) % width;
+        }
+        state.board.insert_garbage(1, hole);
+    }
+}
+
+/// Uses `lines` cleared by the player to cancel pending garbage, oldest first.
+pub(super) fn cancel_garbage(state: &mut GameState, mut lines: u32) {
+    while lines > 0 {
+        let Some(first) = state.pending_garbage.first_mut() else {
+            return;
+        };
+        let cancelled = first.rows.min(lines);
+        first.rows -= cancelled;
+        lines -= cancelled;
+        if first.rows == 0 {
+            state.pending_garbage.remove(0);
+        }
+    }
+}
+
+/// Pushes every pending batch into the board. Returns false if the stack was
+/// pushed off the top.
+pub(super) fn raise_garbage(state: &mut GameState) -> bool {
+    if state.pending_garbage.is_empty() {
+        return true;
+    }
+    let mut fits = true;
+    let mut raised = 0;
+    for garbage in std::mem::take(&mut state.pending_garbage) {
+        fits &= state
+            .board
+            .insert_garbage(garbage.rows as usize, garbage.hole_column);
+        raised += garbage.rows as i32;
+    }
+    // Keep the landing highlight on the piece that just moved up with the stack.
+    for (_, y) in state.last_lock_cells.iter_mut() {
+        *y -= raised;
+    }
+    state.board_revision = state.board_revision.wrapping_add(1);
+    fits
+}
@@ -0,0 +1,146 @@
+use super::{GameEvent, GameState, Gravity, LevelTimings, TSpinKind};
+
+pub(super) const MAX_LEVEL: u32 = 999;
+
+/// Gravity from the level it starts at, in 1/256 G (TGM2 Master).
+const GRAVITY_CURVE: [(u32, u32); 30] = [
+    (0, 4),
+    (30, 6),
+    (35, 8),
+    (40, 10),
+    (50, 12),
+    (60, 16),
+    (70, 32),
+    (80, 48),
+    (90, 64),
+    (100, 80),
+    (120, 96),
+    (140, 112),
+    (160, 128),
+    (170, 144),
+    (200, 4),
+    (220, 32),
+    (230, 64),
+    (233, 96),
+    (236, 128),
+    (239, 160),
+    (243, 192),
+    (247, 224),
+    (251, 256),
+    (300, 512),
+    (330, 768),
+    (360, 1024),
+    (400, 1280),
+    (420, 1024),
+    (450, 768),
+    (500, 5120),
+];
+
+/// Delays in frames from the level they start at: ARE, ARE after a line
+/// clear, lock delay and line-clear delay.
+const DELAY_CURVE: [(u32, [u64; 4]); 6] = [
+    (0, [25, 25, 30, 40]),
+    (500, [25, 25, 30, 25]),
+    (600, [25, 16, 30, 16]),
+    (700, [16, 12, 30, 12]),
+    (800, [12, 6, 30, 6]),
+    (900, [12, 6, 17, 6]),
+];
+
+/// Score needed for each grade below GM, lowest first.
+const GRADES: [(u32, &str); 18] = [
+    (0, "9"),
+    (400, "8"),
+    (800, "7"),
+    (1400, "6"),
+    (2000, "5"),
+    (3500, "4"),
+    (5500, "3"),
+    (8000, "2"),
+    (12000, "1"),
+    (16000, "S1"),
+    (22000, "S2"),
+    (30000, "S3"),
+    (40000, "S4"),
+    (52000, "S5"),
+    (66000, "S6"),
+    (82000, "S7"),
+    (100_000, "S8"),
+    (120_000, "S9"),
+];
+
+/// GM also needs the run to reach level 999 within this time.
+const GM_SCORE: u32 = 126_000;
+const GM_TIME_MS: u64 = 13 * 60_000 + 30_000;
+
+fn step<T: Copy>(curve: &[(u32, T)], level: u32) -> T {
+    curve
+        .iter()
+        .rev()
+        .find(|(from, _)| level >= *from)
+        .map(|(_, value)| *value)
+        .unwrap_or(curve[0].1)
+}
+
+pub(super) fn gravity(level: u32) -> Gravity {
+    Gravity(step(&GRAVITY_CURVE, level))
+}
+
+fn delay_ms(level: u32, index: usize) -> u64 {
+    step(&DELAY_CURVE, level)[index] * 1000 / 60
+}
+
+pub(super) fn timings(level: u32) -> LevelTimings {
+    LevelTimings {
+        are_ms: delay_ms(level, 0),
+        line_are_ms: delay_ms(level, 1),
+        line_clear_ms: delay_ms(level, 3),
+        ..LevelTimings::default()
+    }
+}
+
+pub(super) fn lock_delay_ms(level: u32) -> u64 {
+    delay_ms(level, 2)
+}
+
+/// TGM scoring without the combo term: a quarter of the level reached,
+/// rounded up, per line, quadrupled for clearing the whole board. Drop
+/// bonuses are already added as the piece falls.
+pub(super) fn apply_line_clear(state: &mut GameState, cleared: usize, perfect_clear: bool) {
+    if cleared == 0 {
+        state.combo = -1;
+        return;
+    }
+    let lines = cleared as u32;
+    let bravo = if perfect_clear { 4 } else { 1 };
+    state.score += (state.level + lines).div_ceil(4) * lines * bravo;
+    state.line_clear_timer_ms = timings(state.level).line_clear_ms;
+    state.lines += lines;
+    state.combo += 1;
+    state.back_to_back = false;
+    state.events.push(GameEvent::LinesCleared {
+        rows: cleared as u8,
+        spin: TSpinKind::None,
+        b2b: false,
+        combo: state.combo,
+        perfect_clear,
+    });
+}
+
+/// Levels rise by one per piece and by one per cleared line. Pieces alone
+/// cannot pass the last level of a section (x99) or 998.
+pub(super) fn advance_level(state: &mut GameState, cleared: usize) {
+    let mut level = state.level;
+    if level % 100 != 99 && level != MAX_LEVEL - 1 {
+        level += 1;
+    }
+    state.level = (level + cleared as u32).min(MAX_LEVEL);
+    state.lock_delay_ms = lock_delay_ms(state.level);
+}
+
+pub(super) fn grade(state: &GameState) -> &'static str {
+    if state.level >= MAX_LEVEL && state.score >= GM_SCORE && state.sim_time_ms <= GM_TIME_MS {
+        return "GM";
+    }
+    step(&GRADES, state.score)
+}
@@ -0,0 +1,57 @@
+use super::LevelTimings;
+
+/// Frames per row for levels 0 to 29 (NTSC); 29 and above drop every frame.
+const GRAVITY_FRAMES: [u64; 30] = [
+    48, 43, 38, 33, 28, 23, 18, 13, 8, 6, 5, 5, 5, 4, 4, 4, 3, 3, 3, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2,
+    1,
+];
+
+/// Soft drop moves the piece one row every other frame.
+const SOFT_DROP_FRAMES: u64 = 2;
+
+/// Entry delay and line-clear animation, rounded to the middle of the NES
+/// ranges (ARE 10-18 frames depending on lock height, 17-20 frame clears).
+pub(super) const TIMINGS: LevelTimings = LevelTimings {
+    are_ms: frames_ms(14),
+    line_are_ms: frames_ms(14),
+    line_clear_ms: frames_ms(18),
+    landing_flash_ms: 120,
+    perfect_clear_ms: 1200,
+};
+
+const fn frames_ms(frames: u64) -> u64 {
+    frames * 1000 / 60
+}
+
+pub(super) fn drop_interval_ms(level: u32, soft_drop: bool) -> u64 {
+    let frames = GRAVITY_FRAMES
+        .get(level as usize)
+        .copied()
+        .unwrap_or(GRAVITY_FRAMES[GRAVITY_FRAMES.len() - 1]);
+    let frames = if soft_drop {
+        frames.min(SOFT_DROP_FRAMES)
+    } else {
+        frames
+    };
+    frames_ms(frames).max(1)
+}
+
+/// A piece locks on the first gravity step it cannot fall, so it sits on
+/// the stack for one drop interval.
+pub(super) fn lock_delay_ms(level: u32) -> u64 {
+    drop_interval_ms(level, false)
+}
+
+/// The first level-up waits for `min(start * 10 + 10, max(100, start * 10 - 50))`
+/// lines; every 10 lines after that advance one level.
+pub(super) fn level_for(start_level: u32, lines: u32) -> u32 {
+    let start_lines = start_level.saturating_mul(10);
+    let first = start_lines
+        .saturating_add(10)
+        .min(start_lines.saturating_sub(50).max(100));
+    if lines < first {
+        start_level
+    } else {
+        start_level + 1 + (lines - first) / 10
+    }
+}
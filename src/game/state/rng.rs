@@ -1,5 +1,8 @@
+use crate::game::codec::{ByteReader, ByteWriter, DecodeError};
 use crate::game::pieces::TetrominoType;
 
+use super::types::RandomizerKind;
+
 #[derive(Clone, Debug)]
 pub(super) struct SimpleRng {
     state: u64,
@@ -10,42 +13,284 @@ impl SimpleRng {
         Self { state: seed }
     }
 
+    pub(super) fn state(&self) -> u64 {
+        self.state
+    }
+
     pub(super) fn next_u32(&mut self) -> u32 {
         // LCG constants from Numerical Recipes.
         self.state = self.state.wrapping_mul(1664525).wrapping_add(1013904223);
         (self.state >> 16) as u32
     }
 
-    fn next_range(&mut self, upper: usize) -> usize {
+    pub(super) fn next_range(&mut self, upper: usize) -> usize {
         if upper == 0 {
             return 0;
         }
         (self.next_u32() as usize) % upper
     }
+
+    fn next_kind(&mut self) -> TetrominoType {
+        TetrominoType::ALL[self.next_range(TetrominoType::ALL.len())]
+    }
 }
 
-pub(super) fn refill_bag(rng: &mut SimpleRng, queue: &mut Vec<TetrominoType>) {
-    let mut bag = [
-        TetrominoType::I,
-        TetrominoType::O,
-        TetrominoType::T,
-        TetrominoType::S,
-        TetrominoType::Z,
-        TetrominoType::J,
-        TetrominoType::L,
-    ];
+/// Source of upcoming pieces. Implementations must be deterministic for a
+/// given seed so games and replays can be reproduced.
+pub trait Randomizer {
+    fn next_piece(&mut self) -> TetrominoType;
+}
 
-    // Fisher-Yates shuffle.
-    for i in (1..bag.len()).rev() {
-        let j = rng.next_range(i + 1);
-        bag.swap(i, j);
+/// Shuffles `copies` full sets of the seven pieces and deals them out in order.
+#[derive(Clone, Debug)]
+pub struct BagRandomizer {
+    rng: SimpleRng,
+    copies: usize,
+    bag: Vec<TetrominoType>,
+}
+
+impl BagRandomizer {
+    pub fn new(seed: u64, copies: usize) -> Self {
+        Self {
+            rng: SimpleRng::new(seed),
+            copies: copies.max(1),
+            bag: Vec::new(),
+        }
     }
 
-    queue.extend_from_slice(&bag);
+    fn refill(&mut self) {
+        let mut bag = Vec::with_capacity(TetrominoType::ALL.len() * self.copies);
+        for _ in 0..self.copies {
+            bag.extend_from_slice(&TetrominoType::ALL);
+        }
+
+        // Fisher-Yates shuffle.
+        for i in (1..bag.len()).rev() {
+            let j = self.rng.next_range(i + 1);
+            bag.swap(i, j);
+        }
+
+        // Dealt from the back so refills do not shift the whole bag.
+        bag.reverse();
+        self.bag = bag;
+    }
+}
+
+impl Randomizer for BagRandomizer {
+    fn next_piece(&mut self) -> TetrominoType {
+        if self.bag.is_empty() {
+            self.refill();
+        }
+        self.bag.pop().unwrap_or(TetrominoType::I)
+    }
+}
+
+/// Every piece is drawn independently with equal odds.
+#[derive(Clone, Debug)]
+pub struct PureRandomizer {
+    rng: SimpleRng,
+}
+
+impl PureRandomizer {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            rng: SimpleRng::new(seed),
+        }
+    }
+}
+
+impl Randomizer for PureRandomizer {
+    fn next_piece(&mut self) -> TetrominoType {
+        self.rng.next_kind()
+    }
+}
+
+/// NES Tetris: roll eight outcomes, and on a repeat or the spare eighth slot
+/// reroll once over the seven pieces and take whatever comes up.
+#[derive(Clone, Debug)]
+pub struct NesRandomizer {
+    rng: SimpleRng,
+    last: Option<TetrominoType>,
+}
+
+impl NesRandomizer {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            rng: SimpleRng::new(seed),
+            last: None,
+        }
+    }
+}
+
+impl Randomizer for NesRandomizer {
+    fn next_piece(&mut self) -> TetrominoType {
+        let roll = self.rng.next_range(TetrominoType::ALL.len() + 1);
+        let kind = match TetrominoType::from_index(roll) {
+            Some(kind) if Some(kind) != self.last => kind,
+            _ => self.rng.next_kind(),
+        };
+        self.last = Some(kind);
+        kind
+    }
+}
+
+/// TGM: keep the last four pieces and reroll up to six times to avoid them.
+/// The first piece is never S, Z or O.
+#[derive(Clone, Debug)]
+pub struct TgmRandomizer {
+    rng: SimpleRng,
+    history: [TetrominoType; 4],
+    first: bool,
+}
+
+impl TgmRandomizer {
+    pub const ROLLS: usize = 6;
+
+    pub fn new(seed: u64) -> Self {
+        Self {
+            rng: SimpleRng::new(seed),
+            history: [TetrominoType::Z; 4],
+            first: true,
+        }
+    }
+}
+
+impl Randomizer for TgmRandomizer {
+    fn next_piece(&mut self) -> TetrominoType {
+        let kind = if self.first {
+            self.first = false;
+            const OPENERS: [TetrominoType; 4] = [
+                TetrominoType::I,
+                TetrominoType::J,
+                TetrominoType::L,
+                TetrominoType::T,
+            ];
+            OPENERS[self.rng.next_range(OPENERS.len())]
+        } else {
+            let mut kind = self.rng.next_kind();
+            for _ in 1..Self::ROLLS {
+                if !self.history.contains(&kind) {
+                    break;
+                }
+                kind = self.rng.next_kind();
+            }
+            kind
+        };
+        self.history.rotate_right(1);
+        self.history[0] = kind;
+        kind
+    }
+}
+
+/// The built-in randomizers behind `RandomizerKind`, kept as an enum so the
+/// game state stays `Clone` and can be written into snapshots.
+#[derive(Clone, Debug)]
+pub(super) enum PieceGenerator {
+    Bag(BagRandomizer),
+    Random(PureRandomizer),
+    Nes(NesRandomizer),
+    Tgm(TgmRandomizer),
+}
+
+impl PieceGenerator {
+    pub(super) fn new(kind: RandomizerKind, seed: u64) -> Self {
+        match kind {
+            RandomizerKind::SevenBag => PieceGenerator::Bag(BagRandomizer::new(seed, 1)),
+            RandomizerKind::FourteenBag => PieceGenerator::Bag(BagRandomizer::new(seed, 2)),
+            RandomizerKind::Random => PieceGenerator::Random(PureRandomizer::new(seed)),
+            RandomizerKind::Nes => PieceGenerator::Nes(NesRandomizer::new(seed)),
+            RandomizerKind::Tgm => PieceGenerator::Tgm(TgmRandomizer::new(seed)),
+        }
+    }
+
+    pub(super) fn write(&self, w: &mut ByteWriter) {
+        match self {
+            PieceGenerator::Bag(bag) => {
+                w.u64(bag.rng.state());
+                write_kinds(w, &bag.bag);
+            }
+            PieceGenerator::Random(random) => w.u64(random.rng.state()),
+            PieceGenerator::Nes(nes) => {
+                w.u64(nes.rng.state());
+                write_kinds(w, nes.last.as_slice());
+            }
+            PieceGenerator::Tgm(tgm) => {
+                w.u64(tgm.rng.state());
+                write_kinds(w, &tgm.history);
+                w.bool(tgm.first);
+            }
+        }
+    }
+
+    /// Restores state written by `write` into a generator of the same kind.
+    pub(super) fn read(&mut self, r: &mut ByteReader<'_>) -> Result<(), DecodeError> {
+        match self {
+            PieceGenerator::Bag(bag) => {
+                bag.rng = SimpleRng::new(r.u64()?);
+                bag.bag = read_kinds(r)?;
+                if bag.bag.len() > TetrominoType::ALL.len() * bag.copies {
+                    return Err(DecodeError::InvalidValue("bag"));
+                }
+            }
+            PieceGenerator::Random(random) => random.rng = SimpleRng::new(r.u64()?),
+            PieceGenerator::Nes(nes) => {
+                nes.rng = SimpleRng::new(r.u64()?);
+                let last = read_kinds(r)?;
+                if last.len() > 1 {
+                    return Err(DecodeError::InvalidValue("nes history"));
+                }
+                nes.last = last.first().copied();
+            }
+            PieceGenerator::Tgm(tgm) => {
+                tgm.rng = SimpleRng::new(r.u64()?);
+                tgm.history = read_kinds(r)?
+                    .try_into()
+                    .map_err(|_| DecodeError::InvalidValue("tgm history"))?;
+                tgm.first = r.bool()?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Randomizer for PieceGenerator {
+    fn next_piece(&mut self) -> TetrominoType {
+        match self {
+            PieceGenerator::Bag(bag) => bag.next_piece(),
+            PieceGenerator::Random(random) => random.next_piece(),
+            PieceGenerator::Nes(nes) => nes.next_piece(),
+            PieceGenerator::Tgm(tgm) => tgm.next_piece(),
+        }
+    }
+}
+
+pub(super) fn ensure_queue(
+    randomizer: &mut impl Randomizer,
+    queue: &mut Vec<TetrominoType>,
+    len: usize,
+) {
+    while queue.len() < len {
+        queue.push(randomizer.next_piece());
+    }
+}
+
+fn write_kinds(w: &mut ByteWriter, kinds: &[TetrominoType]) {
+    w.varint(kinds.len() as u64);
+    for kind in kinds {
+        w.u8(kind.index() as u8);
+    }
 }
 
-pub(super) fn ensure_queue(rng: &mut SimpleRng, queue: &mut Vec<TetrominoType>) {
-    while queue.len() < super::NEXT_QUEUE_SIZE {
-        refill_bag(rng, queue);
+fn read_kinds(r: &mut ByteReader<'_>) -> Result<Vec<TetrominoType>, DecodeError> {
+    let len = r.varint()? as usize;
+    // The 14-bag is the longest list any built-in randomizer keeps.
+    if len > TetrominoType::ALL.len() * 2 {
+        return Err(DecodeError::InvalidValue("piece list"));
     }
+    (0..len)
+        .map(|_| {
+            TetrominoType::from_index(r.u8()? as usize)
+                .ok_or(DecodeError::InvalidValue("piece kind"))
+        })
+        .collect()
 }
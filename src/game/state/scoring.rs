@@ -1,11 +1,21 @@
-use crate::game::pieces::{Rotation, TetrominoType};
+use crate::game::pieces::TetrominoType;
 
-use super::{GameState, Ruleset, SoundEvent, TSpinKind};
+use super::{GameEvent, GameMode, GameState, Ruleset, TSpinKind, master, nes};
 
-pub(super) fn apply_line_clear(state: &mut GameState, cleared: usize, t_spin: TSpinKind) {
+pub(super) fn apply_line_clear(
+    state: &mut GameState,
+    cleared: usize,
+    t_spin: TSpinKind,
+    perfect_clear: bool,
+) {
+    if state.mode == GameMode::Master {
+        master::apply_line_clear(state, cleared, perfect_clear);
+        return;
+    }
+    // All-spins are scored like minis, so like minis they stay out of B2B.
     let qualifies_b2b = (t_spin == TSpinKind::Full && cleared > 0) || cleared == 4;
     let level = state.level + 1;
-    let mut points = if state.ruleset == Ruleset::Classic {
+    let mut points = if state.ruleset != Ruleset::Modern {
         match cleared {
             1 => state.rules.classic_line_scores[0],
             2 => state.rules.classic_line_scores[1],
@@ -22,13 +32,10 @@ pub(super) fn apply_line_clear(state: &mut GameState, cleared: usize, t_spin: TS
                 3 => state.rules.t_spin_full[3],
                 _ => 0,
             },
-            TSpinKind::Mini => match cleared {
-                0 => state.rules.t_spin_mini[0],
-                1 => state.rules.t_spin_mini[1],
-                2 => state.rules.t_spin_mini[2],
-                _ => 0,
-            },
-            TSpinKind::None => match cleared {
+            // All-spins pay like minis; I-spin triples and quads fall back to
+            // the plain line scores.
+            TSpinKind::Mini | TSpinKind::AllSpin if cleared < 3 => state.rules.t_spin_mini[cleared],
+            TSpinKind::Mini | TSpinKind::AllSpin | TSpinKind::None => match cleared {
                 1 => state.rules.classic_line_scores[0],
                 2 => state.rules.classic_line_scores[1],
                 3 => state.rules.classic_line_scores[2],
@@ -38,15 +45,13 @@ pub(super) fn apply_line_clear(state: &mut GameState, cleared: usize, t_spin: TS
         }
     };
 
-    if state.ruleset == Ruleset::Modern && qualifies_b2b && state.back_to_back {
+    let b2b = state.ruleset == Ruleset::Modern && qualifies_b2b && state.back_to_back;
+    if b2b {
         points = points * state.rules.b2b_bonus_num / state.rules.b2b_bonus_den;
     }
 
     if cleared > 0 {
-        state.line_clear_timer_ms = 180;
-        state
-            .sound_events
-            .push(SoundEvent::LineClear(cleared as u8));
+        state.line_clear_timer_ms = state.current_timings().line_clear_ms;
         state.lines += cleared as u32;
         if state.ruleset == Ruleset::Modern {
             state.combo += 1;
@@ -59,8 +64,14 @@ pub(super) fn apply_line_clear(state: &mut GameState, cleared: usize, t_spin: TS
             state.back_to_back = false;
         }
 
-        // Classic progression: advance level every 10 lines.
-        state.level = state.lines / 10;
+        // Classic progression: advance level every 10 lines, with NES
+        // holding the start level longer before the first level-up.
+        if state.ruleset == Ruleset::Nes {
+            state.level = nes::level_for(state.start_level, state.lines);
+            state.lock_delay_ms = nes::lock_delay_ms(state.level);
+        } else {
+            state.level = state.start_level.max(state.lines / 10);
+        }
     } else {
         state.combo = -1;
         state.back_to_back = false;
@@ -69,43 +80,110 @@ pub(super) fn apply_line_clear(state: &mut GameState, cleared: usize, t_spin: TS
     if points > 0 {
         state.score += points * level;
     }
+    if cleared > 0 || t_spin != TSpinKind::None {
+        state.events.push(GameEvent::LinesCleared {
+            rows: cleared as u8,
+            spin: t_spin,
+            b2b,
+            combo: state.combo,
+            perfect_clear,
+        });
+    }
+}
+
+/// Runs before `apply_line_clear` so the bonus sees the back-to-back state
+/// and level from before this clear.
+pub(super) fn apply_perfect_clear(state: &mut GameState, cleared: usize) {
+    state.perfect_clear_timer_ms = state.current_timings().perfect_clear_ms;
+    if state.ruleset != Ruleset::Modern || state.mode == GameMode::Master {
+        return;
+    }
+
+    let points = if cleared >= 4 && state.back_to_back {
+        state.rules.perfect_clear_b2b
+    } else {
+        state.rules.perfect_clear[cleared.clamp(1, 4) - 1]
+    };
+    state.score += points * (state.level + 1);
 }
 
 pub(super) fn t_spin_kind(state: &GameState) -> TSpinKind {
-    if state.active.kind != TetrominoType::T || !state.last_action_rotate {
+    let Some(kick) = state.last_kick else {
         return TSpinKind::None;
+    };
+    match state.active.kind {
+        TetrominoType::T => t_spin_corners(state, kick),
+        TetrominoType::O => TSpinKind::None,
+        _ if state.all_spin && is_immobile(state) => TSpinKind::AllSpin,
+        _ => TSpinKind::None,
     }
+}
 
-    let center_x = state.active.x + 1;
-    let center_y = state.active.y + 1;
-    let corners = [
-        (center_x - 1, center_y - 1),
-        (center_x + 1, center_y - 1),
-        (center_x - 1, center_y + 1),
-        (center_x + 1, center_y + 1),
-    ];
+/// Guideline three-corner rule: three of the four corners around the T centre
+/// must be filled. Both front corners make a full T-spin, as does a mini that
+/// needed the TST/fin kick of a 90° rotation to get in.
+fn t_spin_corners(state: &GameState, kick: usize) -> TSpinKind {
+    let ((center_x, center_y), (nub_x, nub_y)) = t_center_and_nub(state);
     let mut filled = 0;
-    for (x, y) in corners.iter() {
-        if state.board.is_occupied(*x, *y) {
-            filled += 1;
+    for dx in [-1, 1] {
+        for dy in [-1, 1] {
+            if state.board.is_occupied(center_x + dx, center_y + dy) {
+                filled += 1;
+            }
         }
     }
     if filled < 3 {
         return TSpinKind::None;
     }
 
-    let (front_a, front_b) = match state.active.rotation {
-        Rotation::North => ((center_x - 1, center_y - 1), (center_x + 1, center_y - 1)),
-        Rotation::East => ((center_x + 1, center_y - 1), (center_x + 1, center_y + 1)),
-        Rotation::South => ((center_x - 1, center_y + 1), (center_x + 1, center_y + 1)),
-        Rotation::West => ((center_x - 1, center_y - 1), (center_x - 1, center_y + 1)),
-    };
+    // The front corners flank the nub the T points with.
+    let front_a = (center_x + nub_x + nub_y, center_y + nub_y + nub_x);
+    let front_b = (center_x + nub_x - nub_y, center_y + nub_y - nub_x);
     let front_filled = state.board.is_occupied(front_a.0, front_a.1) as u8
         + state.board.is_occupied(front_b.0, front_b.1) as u8;
 
-    if front_filled == 2 {
+    let tst = !state.last_kick_180 && state.active.system.tst_kick() == Some(kick);
+    if front_filled == 2 || tst {
         TSpinKind::Full
     } else {
         TSpinKind::Mini
     }
 }
+
+/// Board position of the active T's centre block and the direction of its
+/// nub, read from the rotation system's shape: ARS and NES do not keep the
+/// centre in the middle of the 3x3 box.
+fn t_center_and_nub(state: &GameState) -> ((i32, i32), (i32, i32)) {
+    let piece = &state.active;
+    let blocks = piece.blocks(piece.rotation);
+    let touches =
+        |(ax, ay): (i32, i32), (bx, by): (i32, i32)| (ax - bx).abs() + (ay - by).abs() == 1;
+    let center = blocks
+        .iter()
+        .copied()
+        .find(|&block| {
+            blocks
+                .iter()
+                .filter(|&&other| touches(block, other))
+                .count()
+                == 3
+        })
+        .unwrap_or((1, 1));
+    // The nub is the arm with nothing opposite it.
+    let nub = blocks
+        .iter()
+        .map(|&(x, y)| (x - center.0, y - center.1))
+        .find(|&(dx, dy)| (dx, dy) != (0, 0) && !blocks.contains(&(center.0 - dx, center.1 - dy)))
+        .unwrap_or((0, -1));
+    ((piece.x + center.0, piece.y + center.1), nub)
+}
+
+/// The piece can no longer shift left, right or up.
+fn is_immobile(state: &GameState) -> bool {
+    let piece = &state.active;
+    [(-1, 0), (1, 0), (0, -1)].iter().all(|(dx, dy)| {
+        !state
+            .board
+            .can_place(piece, piece.x + dx, piece.y + dy, piece.rotation)
+    })
+}
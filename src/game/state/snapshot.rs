@@ -0,0 +1,506 @@
+use crate::game::board::{BoardSize, Cell, ClearedRow};
+use crate::game::codec::{ByteReader, ByteWriter, DecodeError};
+use crate::game::input::HeldButtons;
+use crate::game::pieces::{Rotation, Tetromino, TetrominoType};
+use crate::game::rotation::RotationSystem;
+
+use super::finesse::FinesseTracker;
+use super::rng::SimpleRng;
+use super::{
+    GameConfig, GameMode, GameState, Gravity, LevelTimings, LockDownMode, MAX_PREVIEW_COUNT,
+    MAX_TIMING_STEPS, PendingGarbage, RandomizerKind, RulesConfig, Ruleset, TimingCurve,
+    TopOutReason,
+};
+
+pub const SNAPSHOT_VERSION: u32 = 1;
+const SNAPSHOT_MAGIC: &[u8; 4] = b"GTSV";
+
+/// Serialized copy of an in-progress game, suitable for writing to disk.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GameSnapshot {
+    data: Vec<u8>,
+}
+
+impl GameSnapshot {
+    pub fn from_bytes(data: Vec<u8>) -> Result<Self, DecodeError> {
+        let mut reader = ByteReader::new(&data);
+        read_header(&mut reader)?;
+        Ok(Self { data })
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.data
+    }
+}
+
+pub(super) fn encode(state: &GameState) -> GameSnapshot {
+    let mut w = ByteWriter::new();
+    w.bytes(SNAPSHOT_MAGIC);
+    w.u32(SNAPSHOT_VERSION);
+    w.u64(state.seed);
+
+    write_config(&mut w, &state.config());
+
+    for row in state.board.cells() {
+        for cell in row.iter() {
+            w.u8(encode_cell(cell));
+        }
+    }
+
+    write_kind(&mut w, Some(state.active.kind));
+    w.u8(state.active.rotation.index() as u8);
+    w.i32(state.active.x);
+    w.i32(state.active.y);
+    write_kind(&mut w, state.hold);
+    w.bool(state.can_hold);
+    w.varint(state.next_queue.len() as u64);
+    for kind in state.next_queue.iter() {
+        write_kind(&mut w, Some(*kind));
+    }
+
+    w.u32(state.score);
+    w.u32(state.level);
+    w.u32(state.lines);
+    w.i32(state.combo);
+    w.bool(state.back_to_back);
+    w.bool(state.game_over);
+    w.bool(state.mode_complete);
+    w.u8(match state.top_out {
+        None => 0,
+        Some(TopOutReason::BlockOut) => 1,
+        Some(TopOutReason::LockOut) => 2,
+        Some(TopOutReason::PartialLockOut) => 3,
+        Some(TopOutReason::GarbageOut) => 4,
+    });
+    w.bool(state.paused);
+
+    w.u32(state.lock_reset_count);
+    w.i32(state.lowest_y);
+    w.bool(state.soft_drop_active);
+    w.u64(state.soft_drop_timeout_ms);
+    w.u64(state.drop_timer_ms);
+    w.u64(state.lock_timer_ms);
+    w.u64(state.line_clear_timer_ms);
+    w.u64(state.line_clear_total_ms);
+    w.varint(state.cleared_rows.len() as u64);
+    for row in &state.cleared_rows {
+        w.varint(row.y as u64);
+        for cell in &row.cells {
+            w.u8(encode_cell(cell));
+        }
+    }
+    w.u64(state.spawn_delay_ms);
+    w.bool(state.spawn_pending);
+    w.u64(state.perfect_clear_timer_ms);
+    w.u64(state.landing_flash_timer_ms);
+    w.u64(state.sim_time_ms);
+    w.u64(state.frame);
+    w.u32(state.pieces_placed);
+    w.u32(state.finesse_faults);
+    w.u32(state.finesse.presses);
+    w.i32(state.finesse.run_dir);
+    w.u32(state.finesse.run_len);
+    w.bool(state.finesse.soft_dropped);
+    for (x, y) in state.last_lock_cells.iter() {
+        w.i32(*x);
+        w.i32(*y);
+    }
+    w.u64(state.board_revision);
+    w.varint(state.pending_garbage.len() as u64);
+    for garbage in &state.pending_garbage {
+        w.u32(garbage.rows);
+        w.u32(garbage.hole_column as u32);
+    }
+    w.u8(state.held.bits());
+    w.u8(state.last_kick.map(|index| index as u8 + 1).unwrap_or(0));
+    w.bool(state.last_kick_180);
+    w.u64(state.rng.state());
+    state.pieces.write(&mut w);
+
+    GameSnapshot { data: w.finish() }
+}
+
+pub(super) fn decode(snapshot: &GameSnapshot) -> Result<GameState, DecodeError> {
+    let mut r = ByteReader::new(&snapshot.data);
+    read_header(&mut r)?;
+
+    let seed = r.u64()?;
+    let config = read_config(&mut r)?;
+    let mut state = GameState::new(seed, config);
+
+    let size = state.board.size();
+    for y in 0..size.height {
+        for x in 0..size.width {
+            state.board.set_cell(x, y, decode_cell(r.u8()?)?);
+        }
+    }
+
+    let kind = read_kind(&mut r)?.ok_or(DecodeError::InvalidValue("active piece"))?;
+    let rotation = Rotation::from_index(r.u8()? as usize)
+        .ok_or(DecodeError::InvalidValue("active rotation"))?;
+    let x = r.i32()?;
+    let y = r.i32()?;
+    state.active = Tetromino::new(kind, x, y).with_system(state.rotation_system);
+    state.active.rotation = rotation;
+    state.hold = read_kind(&mut r)?;
+    state.can_hold = r.bool()?;
+    let queue_len = r.varint()? as usize;
+    state.next_queue.clear();
+    for _ in 0..queue_len {
+        let kind = read_kind(&mut r)?.ok_or(DecodeError::InvalidValue("next queue"))?;
+        state.next_queue.push(kind);
+    }
+
+    state.score = r.u32()?;
+    state.level = r.u32()?;
+    if state.mode == GameMode::Master {
+        state.lock_delay_ms = super::master::lock_delay_ms(state.level);
+    } else if state.ruleset == Ruleset::Nes {
+        state.lock_delay_ms = super::nes::lock_delay_ms(state.level);
+    }
+    state.lines = r.u32()?;
+    state.combo = r.i32()?;
+    state.back_to_back = r.bool()?;
+    state.game_over = r.bool()?;
+    state.mode_complete = r.bool()?;
+    state.top_out = match r.u8()? {
+        0 => None,
+        1 => Some(TopOutReason::BlockOut),
+        2 => Some(TopOutReason::LockOut),
+        3 => Some(TopOutReason::PartialLockOut),
+        4 => Some(TopOutReason::GarbageOut),
+        _ => return Err(DecodeError::InvalidValue("top out")),
+    };
+    state.paused = r.bool()?;
+
+    state.lock_reset_count = r.u32()?;
+    state.lowest_y = r.i32()?;
+    state.soft_drop_active = r.bool()?;
+    state.soft_drop_timeout_ms = r.u64()?;
+    state.drop_timer_ms = r.u64()?;
+    state.lock_timer_ms = r.u64()?;
+    state.line_clear_timer_ms = r.u64()?;
+    state.line_clear_total_ms = r.u64()?;
+    let cleared = r.varint()? as usize;
+    if cleared > state.board.height() {
+        return Err(DecodeError::InvalidValue("cleared rows"));
+    }
+    state.cleared_rows = (0..cleared)
+        .map(|_| {
+            let y = r.varint()? as usize;
+            if y >= state.board.height() {
+                return Err(DecodeError::InvalidValue("cleared row"));
+            }
+            let cells = (0..state.board.width())
+                .map(|_| decode_cell(r.u8()?))
+                .collect::<Result<_, DecodeError>>()?;
+            Ok(ClearedRow { y, cells })
+        })
+        .collect::<Result<_, DecodeError>>()?;
+    state.spawn_delay_ms = r.u64()?;
+    state.spawn_pending = r.bool()?;
+    state.perfect_clear_timer_ms = r.u64()?;
+    state.landing_flash_timer_ms = r.u64()?;
+    state.sim_time_ms = r.u64()?;
+    state.frame = r.u64()?;
+    state.pieces_placed = r.u32()?;
+    state.finesse_faults = r.u32()?;
+    state.finesse = FinesseTracker {
+        presses: r.u32()?,
+        run_dir: r.i32()?,
+        run_len: r.u32()?,
+        soft_dropped: r.bool()?,
+    };
+    for cell in state.last_lock_cells.iter_mut() {
+        *cell = (r.i32()?, r.i32()?);
+    }
+    state.board_revision = r.u64()?;
+    let pending = r.varint()? as usize;
+    let width = state.board.width();
+    state.pending_garbage = (0..pending)
+        .map(|_| {
+            let rows = r.u32()?;
+            let hole_column = r.u32()? as usize;
+            if hole_column >= width {
+                return Err(DecodeError::InvalidValue("garbage hole column"));
+            }
+            Ok(PendingGarbage { rows, hole_column })
+        })
+        .collect::<Result<_, DecodeError>>()?;
+    state.held = HeldButtons::from_bits(r.u8()?);
+    state.last_kick = r.u8()?.checked_sub(1).map(usize::from);
+    state.last_kick_180 = r.bool()?;
+    state.rng = SimpleRng::new(r.u64()?);
+    state.pieces.read(&mut r)?;
+
+    if !r.is_empty() {
+        return Err(DecodeError::InvalidValue("trailing data"));
+    }
+
+    super::actions::update_ghost_cache(&mut state);
+    Ok(state)
+}
+
+fn read_header(r: &mut ByteReader<'_>) -> Result<(), DecodeError> {
+    if r.bytes(SNAPSHOT_MAGIC.len())? != SNAPSHOT_MAGIC {
+        return Err(DecodeError::BadMagic);
+    }
+    let version = r.u32()?;
+    if version != SNAPSHOT_VERSION {
+        return Err(DecodeError::UnsupportedVersion(version));
+    }
+    Ok(())
+}
+
+pub(crate) fn write_config(w: &mut ByteWriter, config: &GameConfig) {
+    w.u64(config.tick_ms);
+    w.u64(config.soft_drop_multiplier);
+    w.u64(config.lock_delay_ms);
+    w.u32(config.lock_reset_limit);
+    w.u8(match config.lock_down_mode {
+        LockDownMode::ExtendedPlacement => 0,
+        LockDownMode::Infinite => 1,
+        LockDownMode::Classic => 2,
+    });
+    w.u64(config.base_drop_ms);
+    w.u64(config.soft_drop_grace_ms);
+    w.u8(match config.ruleset {
+        Ruleset::Classic => 0,
+        Ruleset::Modern => 1,
+        Ruleset::Nes => 2,
+    });
+    w.u32(config.start_level);
+    match config.mode {
+        GameMode::Marathon => w.u8(0),
+        GameMode::Sprint { lines } => {
+            w.u8(1);
+            w.u32(lines);
+        }
+        GameMode::Ultra { duration_ms } => {
+            w.u8(2);
+            w.u64(duration_ms);
+        }
+        GameMode::Dig { rows, messiness } => {
+            w.u8(3);
+            w.u32(rows);
+            w.u8(messiness);
+        }
+        GameMode::Master => w.u8(4),
+    }
+    w.u8(match config.randomizer {
+        RandomizerKind::SevenBag => 0,
+        RandomizerKind::FourteenBag => 1,
+        RandomizerKind::Random => 2,
+        RandomizerKind::Nes => 3,
+        RandomizerKind::Tgm => 4,
+    });
+    w.u8(match config.rotation_system {
+        RotationSystem::Srs => 0,
+        RotationSystem::Ars => 1,
+        RotationSystem::Nes => 2,
+        RotationSystem::SrsPlus => 3,
+    });
+    w.bool(config.all_spin);
+    w.u8(config.preview_count as u8);
+    w.bool(config.partial_lock_out);
+    w.u32(config.board.width as u32);
+    w.u32(config.board.height as u32);
+    w.u32(config.board.visible_height as u32);
+    w.bool(config.gravity.is_some());
+    w.u32(config.gravity.map(|gravity| gravity.0).unwrap_or(0));
+    w.bool(config.initial_actions);
+    let steps = config.timings.steps();
+    w.varint(steps.len() as u64);
+    for (level, timings) in steps {
+        w.u32(*level);
+        w.u64(timings.are_ms);
+        w.u64(timings.line_are_ms);
+        w.u64(timings.line_clear_ms);
+        w.u64(timings.landing_flash_ms);
+        w.u64(timings.perfect_clear_ms);
+    }
+
+    let rules = &config.rules;
+    for value in rules
+        .classic_line_scores
+        .iter()
+        .chain(rules.t_spin_full.iter())
+        .chain(rules.t_spin_mini.iter())
+        .chain(rules.perfect_clear.iter())
+    {
+        w.u32(*value);
+    }
+    w.u32(rules.perfect_clear_b2b);
+    w.u32(rules.combo_base);
+    w.u32(rules.b2b_bonus_num);
+    w.u32(rules.b2b_bonus_den);
+}
+
+pub(crate) fn read_config(r: &mut ByteReader<'_>) -> Result<GameConfig, DecodeError> {
+    let tick_ms = r.u64()?;
+    let soft_drop_multiplier = r.u64()?;
+    let lock_delay_ms = r.u64()?;
+    let lock_reset_limit = r.u32()?;
+    let lock_down_mode = match r.u8()? {
+        0 => LockDownMode::ExtendedPlacement,
+        1 => LockDownMode::Infinite,
+        2 => LockDownMode::Classic,
+        _ => return Err(DecodeError::InvalidValue("lock down mode")),
+    };
+    let base_drop_ms = r.u64()?;
+    let soft_drop_grace_ms = r.u64()?;
+    let ruleset = match r.u8()? {
+        0 => Ruleset::Classic,
+        1 => Ruleset::Modern,
+        2 => Ruleset::Nes,
+        _ => return Err(DecodeError::InvalidValue("ruleset")),
+    };
+    let start_level = r.u32()?;
+    let mode = match r.u8()? {
+        0 => GameMode::Marathon,
+        1 => GameMode::Sprint { lines: r.u32()? },
+        2 => GameMode::Ultra {
+            duration_ms: r.u64()?,
+        },
+        3 => GameMode::Dig {
+            rows: r.u32()?,
+            messiness: r.u8()?,
+        },
+        4 => GameMode::Master,
+        _ => return Err(DecodeError::InvalidValue("game mode")),
+    };
+    let randomizer = match r.u8()? {
+        0 => RandomizerKind::SevenBag,
+        1 => RandomizerKind::FourteenBag,
+        2 => RandomizerKind::Random,
+        3 => RandomizerKind::Nes,
+        4 => RandomizerKind::Tgm,
+        _ => return Err(DecodeError::InvalidValue("randomizer")),
+    };
+    let rotation_system = match r.u8()? {
+        0 => RotationSystem::Srs,
+        1 => RotationSystem::Ars,
+        2 => RotationSystem::Nes,
+        3 => RotationSystem::SrsPlus,
+        _ => return Err(DecodeError::InvalidValue("rotation system")),
+    };
+    let all_spin = r.bool()?;
+    let preview_count = r.u8()? as usize;
+    if preview_count > MAX_PREVIEW_COUNT {
+        return Err(DecodeError::InvalidValue("preview count"));
+    }
+    let partial_lock_out = r.bool()?;
+    let board = BoardSize {
+        width: r.u32()? as usize,
+        height: r.u32()? as usize,
+        visible_height: r.u32()? as usize,
+    };
+    if !board.is_valid() {
+        return Err(DecodeError::InvalidValue("board size"));
+    }
+    let has_gravity = r.bool()?;
+    let gravity = Some(Gravity(r.u32()?)).filter(|_| has_gravity);
+    let initial_actions = r.bool()?;
+    let steps = r.varint()? as usize;
+    if steps == 0 || steps > MAX_TIMING_STEPS {
+        return Err(DecodeError::InvalidValue("timing steps"));
+    }
+    let mut timings = TimingCurve::default();
+    for index in 0..steps {
+        let level = r.u32()?;
+        let step = LevelTimings {
+            are_ms: r.u64()?,
+            line_are_ms: r.u64()?,
+            line_clear_ms: r.u64()?,
+            landing_flash_ms: r.u64()?,
+            perfect_clear_ms: r.u64()?,
+        };
+        timings = match index {
+            0 if level == 0 => TimingCurve::fixed(step),
+            _ if level > timings.steps()[index - 1].0 => timings.from_level(level, step),
+            _ => return Err(DecodeError::InvalidValue("timing steps")),
+        };
+    }
+
+    let mut rules = RulesConfig::default();
+    for value in rules.classic_line_scores.iter_mut() {
+        *value = r.u32()?;
+    }
+    for value in rules.t_spin_full.iter_mut() {
+        *value = r.u32()?;
+    }
+    for value in rules.t_spin_mini.iter_mut() {
+        *value = r.u32()?;
+    }
+    for value in rules.perfect_clear.iter_mut() {
+        *value = r.u32()?;
+    }
+    rules.perfect_clear_b2b = r.u32()?;
+    rules.combo_base = r.u32()?;
+    rules.b2b_bonus_num = r.u32()?;
+    rules.b2b_bonus_den = r.u32()?;
+    if rules.b2b_bonus_den == 0 {
+        return Err(DecodeError::InvalidValue("b2b bonus"));
+    }
+
+    Ok(GameConfig {
+        tick_ms,
+        soft_drop_multiplier,
+        lock_delay_ms,
+        lock_reset_limit,
+        lock_down_mode,
+        base_drop_ms,
+        soft_drop_grace_ms,
+        ruleset,
+        start_level,
+        mode,
+        randomizer,
+        rotation_system,
+        all_spin,
+        preview_count,
+        partial_lock_out,
+        board,
+        gravity,
+        timings,
+        initial_actions,
+        rules,
+    })
+}
+
+fn write_kind(w: &mut ByteWriter, kind: Option<TetrominoType>) {
+    w.u8(kind.map(|kind| kind.index() as u8 + 1).unwrap_or(0));
+}
+
+fn read_kind(r: &mut ByteReader<'_>) -> Result<Option<TetrominoType>, DecodeError> {
+    match r.u8()? {
+        0 => Ok(None),
+        value => TetrominoType::from_index(value as usize - 1)
+            .map(Some)
+            .ok_or(DecodeError::InvalidValue("piece kind")),
+    }
+}
+
+// 0 = empty, 1..=7 = filled with a piece colour, 0xfe = garbage,
+// 0xff = filled without a colour.
+pub(crate) fn encode_cell(cell: &Cell) -> u8 {
+    match (cell.filled, cell.kind) {
+        (false, _) => 0,
+        (true, _) if cell.garbage => 0xfe,
+        (true, Some(kind)) => kind.index() as u8 + 1,
+        (true, None) => 0xff,
+    }
+}
+
+pub(crate) fn decode_cell(value: u8) -> Result<Cell, DecodeError> {
+    match value {
+        0 => Ok(Cell::default()),
+        0xfe => Ok(Cell::GARBAGE),
+        0xff => Ok(Cell {
+            filled: true,
+            ..Cell::default()
+        }),
+        value => TetrominoType::from_index(value as usize - 1)
+            .map(Cell::piece)
+            .ok_or(DecodeError::InvalidValue("board cell")),
+    }
+}
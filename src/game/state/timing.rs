@@ -1,13 +1,31 @@
-use super::GameState;
+use super::actions::sink_if_instant;
+use super::{GameEvent, GameMode, GameState, Gravity, Ruleset, nes};
 
 const DROP_INTERVALS_MS: [u64; 9] = [1000, 800, 650, 500, 400, 320, 250, 200, 160];
 
 pub(super) fn drop_interval_ms(state: &GameState, soft_drop: bool) -> u64 {
+    if let Some(gravity) = state.current_gravity() {
+        // Soft drop is never slower than 1G.
+        let gravity = if soft_drop {
+            Gravity(
+                gravity
+                    .0
+                    .saturating_mul(state.soft_drop_multiplier.max(1) as u32),
+            )
+            .max(Gravity::ONE_G)
+        } else {
+            gravity
+        };
+        return gravity.interval_ms();
+    }
+    if state.ruleset == Ruleset::Nes {
+        return nes::drop_interval_ms(state.level, soft_drop);
+    }
     let mut interval = DROP_INTERVALS_MS
         .get(state.level as usize)
         .copied()
         .unwrap_or(120);
-    interval = interval.min(state.base_drop_ms).max(100);
+    interval = interval.min(state.base_drop_ms).max(1);
     if soft_drop {
         let adjusted = interval / state.soft_drop_multiplier.max(1);
         return adjusted.max(1);
@@ -20,8 +38,15 @@ pub(super) fn tick(state: &mut GameState, elapsed_ms: u64, soft_drop: bool) {
         return;
     }
 
+    let before_ms = state.sim_time_ms;
+    state.sim_time_ms = state.sim_time_ms.saturating_add(elapsed_ms);
+    if step_time_limit(state, before_ms) {
+        return;
+    }
     step_landing_flash(state, elapsed_ms);
-    if step_line_clear_pause(state, elapsed_ms) {
+    let elapsed_ms = step_line_clear_pause(state, elapsed_ms);
+    let elapsed_ms = step_spawn_delay(state, elapsed_ms);
+    if state.line_clear_timer_ms > 0 || state.spawn_pending || state.game_over {
         return;
     }
     update_drop_timers(state, elapsed_ms);
@@ -29,18 +54,62 @@ pub(super) fn tick(state: &mut GameState, elapsed_ms: u64, soft_drop: bool) {
     update_lock_timer(state, elapsed_ms);
 }
 
+/// Ends timed modes when the clock runs out and ticks through the last seconds.
+fn step_time_limit(state: &mut GameState, before_ms: u64) -> bool {
+    let GameMode::Ultra { duration_ms } = state.mode else {
+        return false;
+    };
+    if state.sim_time_ms >= duration_ms {
+        state.sim_time_ms = duration_ms;
+        state.game_over = true;
+        state.mode_complete = true;
+        return true;
+    }
+
+    let left_before = duration_ms.saturating_sub(before_ms).div_ceil(1000);
+    let left_after = (duration_ms - state.sim_time_ms).div_ceil(1000);
+    if left_after != left_before && left_after * 1000 <= GameMode::TIME_WARNING_MS {
+        state.events.push(GameEvent::CountdownTick);
+    }
+    false
+}
+
 fn step_landing_flash(state: &mut GameState, elapsed_ms: u64) {
     if state.landing_flash_timer_ms > 0 {
         state.landing_flash_timer_ms = state.landing_flash_timer_ms.saturating_sub(elapsed_ms);
     }
+    if state.perfect_clear_timer_ms > 0 {
+        state.perfect_clear_timer_ms = state.perfect_clear_timer_ms.saturating_sub(elapsed_ms);
+    }
 }
 
-fn step_line_clear_pause(state: &mut GameState, elapsed_ms: u64) -> bool {
-    if state.line_clear_timer_ms > 0 {
-        state.line_clear_timer_ms = state.line_clear_timer_ms.saturating_sub(elapsed_ms);
-        return state.line_clear_timer_ms > 0;
+/// Each phase uses up part of the elapsed time and hands back the rest.
+fn step_line_clear_pause(state: &mut GameState, elapsed_ms: u64) -> u64 {
+    if state.line_clear_timer_ms == 0 {
+        return elapsed_ms;
     }
-    false
+    let paused = state.line_clear_timer_ms.min(elapsed_ms);
+    state.line_clear_timer_ms -= paused;
+    if state.line_clear_timer_ms == 0 {
+        state.cleared_rows.clear();
+    }
+    elapsed_ms - paused
+}
+
+/// The spawn phase between a lock and the next piece: the line clear runs
+/// first, then the entry delay, and only then does the piece appear.
+fn step_spawn_delay(state: &mut GameState, elapsed_ms: u64) -> u64 {
+    if !state.spawn_pending || state.line_clear_timer_ms > 0 {
+        return elapsed_ms;
+    }
+    let waited = state.spawn_delay_ms.min(elapsed_ms);
+    state.spawn_delay_ms -= waited;
+    if state.spawn_delay_ms > 0 {
+        return 0;
+    }
+    state.drop_timer_ms = 0;
+    state.spawn_next();
+    elapsed_ms - waited
 }
 
 fn update_drop_timers(state: &mut GameState, elapsed_ms: u64) {
@@ -54,6 +123,11 @@ fn update_drop_timers(state: &mut GameState, elapsed_ms: u64) {
 }
 
 fn apply_gravity_steps(state: &mut GameState, soft_drop: bool) {
+    if state.current_gravity().is_some_and(|g| g.is_instant()) {
+        state.drop_timer_ms = 0;
+        sink_if_instant(state);
+        return;
+    }
     let interval = drop_interval_ms(state, soft_drop || state.soft_drop_active);
     while state.drop_timer_ms >= interval {
         state.drop_timer_ms -= interval;
@@ -62,9 +136,10 @@ fn apply_gravity_steps(state: &mut GameState, soft_drop: bool) {
 }
 
 fn update_lock_timer(state: &mut GameState, elapsed_ms: u64) {
+    // The delay only runs on the ground; the reset count is left to
+    // `handle_lock_reset`, which clears it on a new lowest row.
     if state.can_move_down() {
         state.lock_timer_ms = 0;
-        state.lock_reset_count = 0;
     } else {
         state.lock_timer_ms = state.lock_timer_ms.saturating_add(elapsed_ms);
         if state.lock_timer_ms >= state.lock_delay_ms {
@@ -1,3 +1,7 @@
+use crate::game::board::BoardSize;
+use crate::game::pieces::TetrominoType;
+use crate::game::rotation::RotationSystem;
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum SoundEvent {
     Move,
@@ -5,14 +9,159 @@ pub enum SoundEvent {
     SoftDrop,
     HardDrop,
     LineClear(u8),
+    PerfectClear,
+    /// Once a second through the final stretch of a timed mode.
+    CountdownTick,
     GameOver,
     Hold,
 }
 
+/// What happened during play, in order. Drained with
+/// `GameState::take_events` by audio, the UI and anything else following a
+/// game.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GameEvent {
+    PieceSpawned {
+        kind: TetrominoType,
+    },
+    /// The active piece shifted sideways by `dx`.
+    Moved {
+        dx: i32,
+    },
+    /// A sideways shift ran into a wall or the stack.
+    MoveBlocked {
+        dx: i32,
+    },
+    SoftDropped {
+        rows: u32,
+    },
+    HardDropped {
+        rows: u32,
+    },
+    /// A rotation went through on kick test `kick_index`; 0 is no kick.
+    Rotated {
+        kick_index: usize,
+    },
+    /// `kind` went into hold.
+    Held {
+        kind: TetrominoType,
+    },
+    PieceLocked {
+        cells: [(i32, i32); 4],
+    },
+    /// A lock that cleared rows or scored a spin. `b2b` is set when the
+    /// back-to-back bonus applied; `combo` is -1 outside a combo.
+    LinesCleared {
+        rows: u8,
+        spin: TSpinKind,
+        b2b: bool,
+        combo: i32,
+        perfect_clear: bool,
+    },
+    LevelUp {
+        level: u32,
+    },
+    TopOut {
+        reason: TopOutReason,
+    },
+    Paused {
+        paused: bool,
+    },
+    /// Once a second through the final stretch of a timed mode.
+    CountdownTick,
+}
+
+impl GameEvent {
+    /// The sound effect for this event, if it has one.
+    pub fn sound(&self) -> Option<SoundEvent> {
+        match *self {
+            GameEvent::Moved { .. } => Some(SoundEvent::Move),
+            GameEvent::SoftDropped { rows } if rows > 0 => Some(SoundEvent::SoftDrop),
+            GameEvent::HardDropped { .. } => Some(SoundEvent::HardDrop),
+            GameEvent::Rotated { .. } => Some(SoundEvent::Rotate),
+            GameEvent::Held { .. } => Some(SoundEvent::Hold),
+            GameEvent::LinesCleared {
+                perfect_clear: true,
+                ..
+            } => Some(SoundEvent::PerfectClear),
+            GameEvent::LinesCleared { rows, .. } if rows > 0 => Some(SoundEvent::LineClear(rows)),
+            GameEvent::TopOut { .. } => Some(SoundEvent::GameOver),
+            GameEvent::CountdownTick => Some(SoundEvent::CountdownTick),
+            GameEvent::PieceSpawned { .. }
+            | GameEvent::MoveBlocked { .. }
+            | GameEvent::SoftDropped { .. }
+            | GameEvent::PieceLocked { .. }
+            | GameEvent::LinesCleared { .. }
+            | GameEvent::LevelUp { .. }
+            | GameEvent::Paused { .. } => None,
+        }
+    }
+}
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum Ruleset {
     Classic,
     Modern,
+    /// NES Tetris: frame-based gravity to level 29, the start-level
+    /// transition, classic line scores, no hold and unscored hard drops.
+    Nes,
+}
+
+/// Built-in piece randomizers selectable from `GameConfig`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RandomizerKind {
+    #[default]
+    SevenBag,
+    FourteenBag,
+    Random,
+    Nes,
+    Tgm,
+}
+
+/// What ends a game besides topping out.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum GameMode {
+    /// Endless play.
+    #[default]
+    Marathon,
+    /// Race to clear `lines` lines.
+    Sprint { lines: u32 },
+    /// Score as much as possible before `duration_ms` of play runs out.
+    Ultra { duration_ms: u64 },
+    /// Dig out `rows` starting garbage rows. `messiness` is the percent chance
+    /// that each row's hole moves away from the one below it.
+    Dig { rows: u32, messiness: u8 },
+    /// TGM-style run to level 999 with rising gravity and shrinking delays,
+    /// graded on score and time.
+    Master,
+}
+
+impl GameMode {
+    pub const SPRINT_LINES: u32 = 40;
+    pub const ULTRA_MS: u64 = 120_000;
+    pub const DIG_ROWS: u32 = 10;
+    pub const DIG_MESSINESS: u8 = 100;
+    /// Remaining time below which timed modes warn the player.
+    pub const TIME_WARNING_MS: u64 = 10_000;
+
+    pub fn sprint() -> Self {
+        GameMode::Sprint {
+            lines: Self::SPRINT_LINES,
+        }
+    }
+
+    pub fn ultra() -> Self {
+        GameMode::Ultra {
+            duration_ms: Self::ULTRA_MS,
+        }
+    }
+
+    pub fn dig() -> Self {
+        GameMode::Dig {
+            rows: Self::DIG_ROWS,
+            messiness: Self::DIG_MESSINESS,
+        }
+    }
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -20,6 +169,10 @@ pub struct RulesConfig {
     pub classic_line_scores: [u32; 4],
     pub t_spin_full: [u32; 4],
     pub t_spin_mini: [u32; 3],
+    /// Perfect clear bonus for a single, double, triple and tetris.
+    pub perfect_clear: [u32; 4],
+    /// Replaces the tetris bonus when back-to-back is active.
+    pub perfect_clear_b2b: u32,
     pub combo_base: u32,
     pub b2b_bonus_num: u32,
     pub b2b_bonus_den: u32,
@@ -31,6 +184,8 @@ impl Default for RulesConfig {
             classic_line_scores: [40, 100, 300, 1200],
             t_spin_full: [400, 800, 1200, 1600],
             t_spin_mini: [100, 200, 400],
+            perfect_clear: [800, 1200, 1800, 2000],
+            perfect_clear_b2b: 3200,
             combo_base: 50,
             b2b_bonus_num: 3,
             b2b_bonus_den: 2,
@@ -38,15 +193,167 @@ impl Default for RulesConfig {
     }
 }
 
+/// How moving a grounded piece affects its lock delay.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LockDownMode {
+    /// Guideline: moves and rotations restart the delay up to
+    /// `lock_reset_limit` times, and the count only clears when the piece
+    /// reaches a row lower than any it has been on.
+    #[default]
+    ExtendedPlacement,
+    /// Every move or rotation restarts the delay.
+    Infinite,
+    /// Only falling a row restarts the delay (step reset).
+    Classic,
+}
+
+/// Fall speed in 1/256 of a row per 60 Hz frame, so `Gravity::ONE_G` moves a
+/// piece one row every frame.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Gravity(pub u32);
+
+impl Gravity {
+    pub const ONE_G: Gravity = Gravity(256);
+    /// Fast enough that pieces land the moment they appear.
+    pub const TWENTY_G: Gravity = Gravity(20 * 256);
+
+    pub fn is_instant(self) -> bool {
+        self >= Self::TWENTY_G
+    }
+
+    /// Milliseconds per row, rounded down to at least 1.
+    pub fn interval_ms(self) -> u64 {
+        (256_000 / (60 * self.0.max(1) as u64)).max(1)
+    }
+}
+
+/// Delays and effect durations in milliseconds that apply from some level on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LevelTimings {
+    /// Entry delay between a lock and the next spawn.
+    pub are_ms: u64,
+    /// Entry delay after a lock that cleared lines, counted once the clear ends.
+    pub line_are_ms: u64,
+    pub line_clear_ms: u64,
+    pub landing_flash_ms: u64,
+    pub perfect_clear_ms: u64,
+}
+
+impl Default for LevelTimings {
+    fn default() -> Self {
+        Self {
+            are_ms: 0,
+            line_are_ms: 0,
+            line_clear_ms: 180,
+            landing_flash_ms: 120,
+            perfect_clear_ms: 1200,
+        }
+    }
+}
+
+/// Most level steps a `TimingCurve` holds.
+pub const MAX_TIMING_STEPS: usize = 16;
+
+/// `LevelTimings` keyed by the level each set starts at.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TimingCurve {
+    steps: [(u32, LevelTimings); MAX_TIMING_STEPS],
+    len: usize,
+}
+
+impl TimingCurve {
+    /// The same timings at every level.
+    pub fn fixed(timings: LevelTimings) -> Self {
+        Self {
+            steps: [(0, timings); MAX_TIMING_STEPS],
+            len: 1,
+        }
+    }
+
+    /// Switches to `timings` from `level` on. Levels at or below the last
+    /// step, and steps past `MAX_TIMING_STEPS`, are ignored.
+    pub fn from_level(mut self, level: u32, timings: LevelTimings) -> Self {
+        if self.len < MAX_TIMING_STEPS && level > self.steps[self.len - 1].0 {
+            self.steps[self.len] = (level, timings);
+            self.len += 1;
+        }
+        self
+    }
+
+    pub fn at(&self, level: u32) -> LevelTimings {
+        self.steps()
+            .iter()
+            .rev()
+            .find(|(from, _)| level >= *from)
+            .map(|(_, timings)| *timings)
+            .unwrap_or(self.steps[0].1)
+    }
+
+    pub fn steps(&self) -> &[(u32, LevelTimings)] {
+        &self.steps[..self.len]
+    }
+}
+
+impl Default for TimingCurve {
+    fn default() -> Self {
+        Self::fixed(LevelTimings::default())
+    }
+}
+
+/// Why a game ended by topping out.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TopOutReason {
+    /// A new piece overlapped the stack as it spawned.
+    BlockOut,
+    /// A piece locked entirely inside the vanish zone.
+    LockOut,
+    /// A piece locked with some of its cells inside the vanish zone.
+    PartialLockOut,
+    /// Rising garbage pushed blocks off the top of the board.
+    GarbageOut,
+}
+
+/// Garbage rows waiting to rise from the bottom of the board.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PendingGarbage {
+    pub rows: u32,
+    pub hole_column: usize,
+}
+
+/// Most upcoming pieces the next queue can show.
+pub const MAX_PREVIEW_COUNT: usize = 7;
+
 #[derive(Clone, Copy, Debug)]
 pub struct GameConfig {
     pub tick_ms: u64,
     pub soft_drop_multiplier: u64,
     pub lock_delay_ms: u64,
     pub lock_reset_limit: u32,
+    pub lock_down_mode: LockDownMode,
     pub base_drop_ms: u64,
     pub soft_drop_grace_ms: u64,
     pub ruleset: Ruleset,
+    /// Level the game starts on. Master mode always starts at 0.
+    pub start_level: u32,
+    pub mode: GameMode,
+    pub randomizer: RandomizerKind,
+    pub rotation_system: RotationSystem,
+    /// Score immobile S/Z/J/L/I rotations as spins (modern ruleset only).
+    pub all_spin: bool,
+    /// Upcoming pieces shown to the player, clamped to `MAX_PREVIEW_COUNT`.
+    pub preview_count: usize,
+    /// Also top out when only part of a piece locks above the visible field.
+    pub partial_lock_out: bool,
+    /// Field dimensions, clamped to the supported range.
+    pub board: BoardSize,
+    /// Fixed fall speed replacing the level-based drop intervals.
+    pub gravity: Option<Gravity>,
+    /// Entry, line-clear and effect timings by level. Master mode uses its
+    /// own curve.
+    pub timings: TimingCurve,
+    /// Initial rotation and hold (IRS/IHS): rotate or hold buttons held as a
+    /// piece spawns act on it before it appears.
+    pub initial_actions: bool,
     pub rules: RulesConfig,
 }
 
@@ -57,17 +364,96 @@ impl Default for GameConfig {
             soft_drop_multiplier: 10,
             lock_delay_ms: 450,
             lock_reset_limit: 15,
+            lock_down_mode: LockDownMode::ExtendedPlacement,
             base_drop_ms: 1000,
             soft_drop_grace_ms: 150,
             ruleset: Ruleset::Classic,
+            start_level: 0,
+            mode: GameMode::Marathon,
+            randomizer: RandomizerKind::SevenBag,
+            rotation_system: RotationSystem::Srs,
+            all_spin: false,
+            preview_count: 5,
+            partial_lock_out: false,
+            board: BoardSize::default(),
+            gravity: None,
+            timings: TimingCurve::default(),
+            initial_actions: false,
             rules: RulesConfig::default(),
         }
     }
 }
 
+/// Bundles of rules that set up a `GameConfig` for a style of play.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RulesetPreset {
+    /// The default configuration: classic scoring with SRS and 7-bag.
+    #[default]
+    Classic,
+    /// Modern guideline play: combos, back-to-back and T-spins.
+    Guideline,
+    /// NES Tetris with its piece RNG, rotation, timings and a single preview.
+    Nes,
+}
+
+impl RulesetPreset {
+    pub const ALL: [RulesetPreset; 3] = [
+        RulesetPreset::Classic,
+        RulesetPreset::Guideline,
+        RulesetPreset::Nes,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            RulesetPreset::Classic => "Classic",
+            RulesetPreset::Guideline => "Guideline",
+            RulesetPreset::Nes => "NES",
+        }
+    }
+
+    /// The preset after this one in `ALL`, wrapping around.
+    pub fn next(self) -> Self {
+        let index = Self::ALL.iter().position(|preset| *preset == self);
+        Self::ALL[index.map_or(0, |index| (index + 1) % Self::ALL.len())]
+    }
+
+    /// Replaces the rules in `config`, keeping its mode, board, start level,
+    /// step length, score tables and IRS/IHS setting.
+    pub fn apply(self, config: GameConfig) -> GameConfig {
+        let base = GameConfig {
+            tick_ms: config.tick_ms,
+            start_level: config.start_level,
+            mode: config.mode,
+            board: config.board,
+            initial_actions: config.initial_actions,
+            rules: config.rules,
+            ..GameConfig::default()
+        };
+        match self {
+            RulesetPreset::Classic => base,
+            RulesetPreset::Guideline => GameConfig {
+                ruleset: Ruleset::Modern,
+                ..base
+            },
+            RulesetPreset::Nes => GameConfig {
+                ruleset: Ruleset::Nes,
+                randomizer: RandomizerKind::Nes,
+                rotation_system: RotationSystem::Nes,
+                preview_count: 1,
+                lock_down_mode: LockDownMode::Classic,
+                lock_reset_limit: 0,
+                timings: TimingCurve::fixed(super::nes::TIMINGS),
+                ..base
+            },
+        }
+    }
+}
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum TSpinKind {
     None,
     Mini,
     Full,
+    /// A non-T piece locked in place after a rotation, with all-spin enabled.
+    AllSpin,
 }
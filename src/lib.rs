@@ -1,2 +1,3 @@
+#[cfg(feature = "audio")]
 pub mod audio;
 pub mod game;
@@ -23,6 +23,7 @@ actions!(
         HardDrop,
         RotateCw,
         RotateCcw,
+        Rotate180,
         Hold,
         Pause,
         Restart
@@ -50,10 +51,10 @@ pub fn run() {
             }
         };
 
-        cx.on_action(|_: &Quit, cx| cx.quit());
         cx.bind_keys([KeyBinding::new("cmd-q", Quit, None)]);
         cx.bind_keys([
             KeyBinding::new("up", RotateCw, None),
+            KeyBinding::new("a", Rotate180, None),
             KeyBinding::new("space", HardDrop, None),
             KeyBinding::new("c", Hold, None),
             KeyBinding::new("p", Pause, None),
@@ -100,12 +101,21 @@ pub fn run() {
         ]);
         let view = window.update(cx, |_, _, cx| cx.entity()).unwrap();
 
+        cx.on_action({
+            let view = view.clone();
+            move |_: &Quit, cx| {
+                view.update(cx, |view, _| view.autosave());
+                cx.quit();
+            }
+        });
+
         register_action::<MoveLeft>(cx, view.clone(), GameAction::MoveLeft);
         register_action::<MoveRight>(cx, view.clone(), GameAction::MoveRight);
         register_action::<SoftDrop>(cx, view.clone(), GameAction::SoftDrop);
         register_action::<HardDrop>(cx, view.clone(), GameAction::HardDrop);
         register_action::<RotateCw>(cx, view.clone(), GameAction::RotateCw);
         register_action::<RotateCcw>(cx, view.clone(), GameAction::RotateCcw);
+        register_action::<Rotate180>(cx, view.clone(), GameAction::Rotate180);
         register_action::<Hold>(cx, view.clone(), GameAction::Hold);
         register_action::<Pause>(cx, view.clone(), GameAction::Pause);
         register_action::<Restart>(cx, view, GameAction::Restart);
@@ -1,5 +1,5 @@
 use gilrs::{Axis, Button, EventType, GamepadId, Gilrs};
-use gpui_tetris::game::input::{GameAction, RepeatConfig, RepeatState};
+use gpui_tetris::game::input::{GameAction, HeldButtons, RepeatConfig, RepeatState};
 
 use crate::ui::style::CONTROLLER_AXIS_THRESHOLD;
 
@@ -30,6 +30,8 @@ pub struct InputState {
     controller_left_held: bool,
     controller_right_held: bool,
     controller_down_held: bool,
+    keyboard_buttons: HeldButtons,
+    controller_buttons: HeldButtons,
     temp_actions: Vec<InputAction>,
 }
 
@@ -85,6 +87,8 @@ impl InputState {
             controller_left_held: false,
             controller_right_held: false,
             controller_down_held: false,
+            keyboard_buttons: HeldButtons::default(),
+            controller_buttons: HeldButtons::default(),
             temp_actions: Vec::with_capacity(4),
         }
     }
@@ -116,10 +120,26 @@ impl InputState {
         std::mem::take(&mut self.temp_actions)
     }
 
+    /// Tracks rotate and hold keys for IRS/IHS; they act on their own key
+    /// down, so nothing is emitted here.
+    pub fn set_keyboard_button(&mut self, action: GameAction, held: bool) {
+        self.keyboard_buttons.set(action, held);
+    }
+
+    pub fn held_buttons(&self) -> HeldButtons {
+        self.keyboard_buttons.union(self.controller_buttons)
+    }
+
+    /// Switches the left/right autorepeat timing, e.g. to NES DAS.
+    pub fn set_repeat_config(&mut self, config: RepeatConfig) {
+        self.repeat_config = config;
+    }
+
     pub fn clear_focus_state(&mut self) {
         self.keyboard_left_held = false;
         self.keyboard_right_held = false;
         self.keyboard_down_held = false;
+        self.keyboard_buttons = HeldButtons::default();
         self.clear_controller_state();
     }
 
@@ -218,9 +238,13 @@ impl InputState {
             Button::DPadLeft => self.controller_left_button = pressed,
             Button::DPadRight => self.controller_right_button = pressed,
             Button::DPadDown => self.controller_down_button = pressed,
-            Button::South if pressed => temp.push(InputAction::silent(GameAction::RotateCw)),
-            Button::East if pressed => temp.push(InputAction::silent(GameAction::RotateCcw)),
-            Button::West if pressed => temp.push(InputAction::silent(GameAction::Hold)),
+            Button::South | Button::East | Button::West => {
+                let action = controller_action(button);
+                self.controller_buttons.set(action, pressed);
+                if pressed {
+                    temp.push(InputAction::silent(action));
+                }
+            }
             Button::North if pressed => temp.push(InputAction::silent(GameAction::HardDrop)),
             Button::Start if pressed => temp.push(InputAction::silent(GameAction::Pause)),
             Button::Select | Button::Mode if pressed => {
@@ -317,6 +341,7 @@ impl InputState {
         self.controller_left_held = false;
         self.controller_right_held = false;
         self.controller_down_held = false;
+        self.controller_buttons = HeldButtons::default();
         let mut temp = std::mem::take(&mut self.temp_actions);
         temp.clear();
         self.sync_movement_holds_append(&mut temp);
@@ -339,6 +364,14 @@ impl InputState {
     }
 }
 
+fn controller_action(button: Button) -> GameAction {
+    match button {
+        Button::East => GameAction::RotateCcw,
+        Button::West => GameAction::Hold,
+        _ => GameAction::RotateCw,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::InputState;
@@ -367,4 +400,21 @@ mod tests {
         assert_eq!(actions.len(), 1);
         assert_eq!(actions[0].action, GameAction::MoveLeft);
     }
+
+    #[test]
+    fn rotate_and_hold_keys_stay_held_until_released() {
+        let mut input = InputState::new();
+        input.set_keyboard_button(GameAction::RotateCw, true);
+        input.set_keyboard_button(GameAction::Hold, true);
+        input.set_keyboard_button(GameAction::HardDrop, true);
+
+        let held = input.held_buttons();
+        assert!(held.rotate_cw && held.hold);
+        assert!(!held.rotate_ccw && !held.rotate_180);
+
+        input.set_keyboard_button(GameAction::RotateCw, false);
+        assert!(!input.held_buttons().rotate_cw);
+        input.clear_focus_state();
+        assert_eq!(input.held_buttons(), Default::default());
+    }
 }
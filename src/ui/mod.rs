@@ -1,6 +1,7 @@
 mod app;
 mod input;
 mod render;
+mod storage;
 mod style;
 mod ui_state;
 mod view;
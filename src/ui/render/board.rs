@@ -1,16 +1,11 @@
-use gpui::{IntoElement, div, prelude::*, px};
+use gpui::{IntoElement, Rgba, div, prelude::*, px};
 
 use crate::ui::render::theme;
+use crate::ui::style::BASE_TITLE_TEXT;
 use crate::ui::ui_state::UiState;
 use gpui_tetris::game::pieces::TetrominoType;
 
-pub fn render_cell(
-    kind: Option<TetrominoType>,
-    ghost: bool,
-    flash: bool,
-    cell_size: f32,
-) -> impl IntoElement {
-    let fill = theme::piece_fill(kind, ghost);
+pub fn render_cell(fill: Rgba, flash: bool, cell_size: f32) -> impl IntoElement {
     let border = if flash {
         theme::flash_border()
     } else {
@@ -87,6 +82,24 @@ pub fn render_preview(
         .child(div().flex().flex_col().children(rows))
 }
 
+/// Upcoming pieces stacked top to bottom, with the next one drawn larger.
+pub fn render_next_queue(ui: &mut UiState, cell_size: f32, gap: f32) -> impl IntoElement + use<> {
+    const LATER_SCALE: f32 = 0.6;
+    let queue = ui.state.preview().to_vec();
+
+    let mut slots = Vec::with_capacity(queue.len());
+    for (index, kind) in queue.into_iter().enumerate() {
+        let size = if index == 0 {
+            cell_size
+        } else {
+            cell_size * LATER_SCALE
+        };
+        slots.push(render_preview(ui, Some(kind), size));
+    }
+
+    div().flex().flex_col().gap(px(gap)).children(slots)
+}
+
 fn render_preview_cell(kind: Option<TetrominoType>, cell_size: f32) -> impl IntoElement {
     let size = cell_size * 0.6;
     let color = theme::piece_fill(kind, false);
@@ -99,7 +112,29 @@ fn render_preview_cell(kind: Option<TetrominoType>, cell_size: f32) -> impl Into
         .border_color(theme::ghost_fill())
 }
 
-pub fn render_line_clear_flash(active: bool) -> impl IntoElement {
+/// A cell of a row being cleared: it flashes white for the first third of
+/// the pause, then fades out.
+pub fn render_clearing_cell(fill: Rgba, progress: f32, cell_size: f32) -> impl IntoElement {
+    const FLASH_END: f32 = 1.0 / 3.0;
+    let flash = (1.0 - progress / FLASH_END).clamp(0.0, 1.0);
+    let fade = ((1.0 - progress) / (1.0 - FLASH_END)).clamp(0.0, 1.0);
+
+    div()
+        .w(px(cell_size))
+        .h(px(cell_size))
+        .bg(fill)
+        .border(px(1.0))
+        .border_color(theme::flash_border())
+        .opacity(fade)
+        .child(
+            div()
+                .size_full()
+                .bg(gpui::rgb(0xffffff))
+                .opacity(flash * 0.8),
+        )
+}
+
+pub fn render_perfect_clear_callout(active: bool, scale: f32) -> impl IntoElement {
     if !active {
         return div().hidden();
     }
@@ -110,8 +145,12 @@ pub fn render_line_clear_flash(active: bool) -> impl IntoElement {
         .left_0()
         .right_0()
         .bottom_0()
-        .bg(gpui::rgb(0xffffff))
-        .opacity(0.12)
+        .flex()
+        .justify_center()
+        .items_center()
+        .text_size(px((BASE_TITLE_TEXT * scale).max(16.0)))
+        .text_color(theme::perfect_clear_text())
+        .child("PERFECT CLEAR")
 }
 
 pub fn render_game_over_tint(active: bool) -> impl IntoElement {
@@ -1,14 +1,14 @@
 use gpui::{IntoElement, div, prelude::*, px};
+use gpui_tetris::game::board::{BOARD_WIDTH, BoardSize, VISIBLE_HEIGHT};
 
 use crate::ui::render::theme;
 use crate::ui::render::{
-    OverlayState, render_active_piece, render_cell, render_game_over_tint,
-    render_line_clear_flash, render_lock_bar, render_lock_warning, render_overlay,
-    render_preview,
+    OverlayState, render_active_piece, render_cell, render_clearing_cell, render_game_over_tint,
+    render_lock_bar, render_lock_warning, render_next_queue, render_overlay,
+    render_perfect_clear_callout, render_preview,
 };
 use crate::ui::style::{
-    BASE_CELL_SIZE, BASE_GAP, BASE_PADDING, BASE_PANEL_TEXT, BASE_WINDOW_WIDTH, BOARD_COLS,
-    BOARD_COLS_USIZE, BOARD_ROWS, BOARD_ROWS_USIZE,
+    BASE_CELL_SIZE, BASE_GAP, BASE_PADDING, BASE_PANEL_TEXT, BASE_WINDOW_WIDTH,
 };
 use crate::ui::ui_state::UiState;
 
@@ -20,15 +20,23 @@ pub struct RenderLayout {
     pub board_width: f32,
     pub board_height: f32,
     pub panel_width: f32,
+    pub cols: usize,
+    pub rows: usize,
+    /// Vanish zone rows above the first drawn row.
+    pub hidden_rows: usize,
 }
 
 impl RenderLayout {
-    pub fn new(scale: f32) -> Self {
-        let cell_size = BASE_CELL_SIZE * scale;
+    pub fn new(scale: f32, size: BoardSize) -> Self {
+        // Larger fields shrink their cells to fit the standard 10x20 footprint.
+        let fit = (BOARD_WIDTH as f32 / size.width as f32)
+            .min(VISIBLE_HEIGHT as f32 / size.visible_height as f32)
+            .min(1.0);
+        let cell_size = BASE_CELL_SIZE * scale * fit;
         let padding = BASE_PADDING * scale;
         let gap = BASE_GAP * scale;
-        let board_width = cell_size * BOARD_COLS;
-        let board_height = cell_size * BOARD_ROWS;
+        let board_width = cell_size * size.width as f32;
+        let board_height = cell_size * size.visible_height as f32;
         let panel_width = (BASE_WINDOW_WIDTH * scale) - board_width - (padding * 2.0) - gap;
         Self {
             scale,
@@ -38,6 +46,9 @@ impl RenderLayout {
             board_width,
             board_height,
             panel_width,
+            cols: size.width,
+            rows: size.visible_height,
+            hidden_rows: size.buffer_height(),
         }
     }
 }
@@ -48,13 +59,17 @@ pub fn render_board(
     focused: bool,
     now: std::time::Instant,
 ) -> impl IntoElement + use<> {
+    ui.update_line_clear_animation(now);
     ui.sync_board_cache();
-    let show_active = !ui.state.is_line_clear_active();
-    let cols = BOARD_COLS_USIZE as i32;
-    let rows = BOARD_ROWS_USIZE as i32;
+    let show_active = !ui.state.is_line_clear_active() && !ui.state.is_spawn_delay_active();
+    let cols = layout.cols as i32;
+    let rows = layout.rows as i32;
+    let hidden = layout.hidden_rows as i32;
     ui.clear_render_masks();
 
+    // Board rows are counted from the top of the vanish zone.
     let set_mask = |mask: &mut [bool], x: i32, y: i32| {
+        let y = y - hidden;
         if x >= 0 && x < cols && y >= 0 && y < rows {
             let idx = (y as usize * cols as usize) + x as usize;
             mask[idx] = true;
@@ -67,28 +82,34 @@ pub fn render_board(
         }
     }
 
-    if show_active {
+    if show_active && ui.state.shows_ghost() {
         for (x, y) in ui.state.ghost_blocks().iter() {
             set_mask(&mut ui.ghost_mask, *x, *y);
         }
     }
 
-    let mut rows = Vec::with_capacity(BOARD_ROWS_USIZE);
-    for y in 0..BOARD_ROWS_USIZE {
+    let mut rows = Vec::with_capacity(layout.rows);
+    for y in 0..layout.rows {
         let mut row = div().flex();
-        let row_base = y * BOARD_COLS_USIZE;
-        for x in 0..BOARD_COLS_USIZE {
+        let collapse = ui.collapse_offset_rows(y, now);
+        if collapse > 0.0 {
+            row = row.relative().top(px(-collapse * layout.cell_size));
+        }
+        let clearing = ui.clearing_row_progress(y);
+        let row_base = y * layout.cols;
+        for x in 0..layout.cols {
             let idx = row_base + x;
-            let mut cell_kind = ui.board_cache[idx];
-            let mut is_ghost = false;
             let is_flash = ui.flash_mask[idx];
+            let fill = if show_active && ui.ghost_mask[idx] {
+                theme::piece_fill(Some(ui.state.active.kind), true)
+            } else {
+                theme::cell_fill(&ui.board_cache[idx])
+            };
 
-            if show_active && ui.ghost_mask[idx] {
-                cell_kind = Some(ui.state.active.kind);
-                is_ghost = true;
-            }
-
-            row = row.child(render_cell(cell_kind, is_ghost, is_flash, layout.cell_size));
+            row = match clearing {
+                Some(progress) => row.child(render_clearing_cell(fill, progress, layout.cell_size)),
+                None => row.child(render_cell(fill, is_flash, layout.cell_size)),
+            };
         }
         rows.push(row);
     }
@@ -96,13 +117,17 @@ pub fn render_board(
     div()
         .w(px(layout.board_width))
         .h(px(layout.board_height))
+        .mt(px(layout.cell_size * ABOVE_FIELD_ROWS))
         .bg(theme::board_bg())
         .border(px(1.0))
         .border_color(theme::border())
         .relative()
-        .child(div().flex().flex_col().children(rows))
+        .child(div().flex().flex_col().overflow_hidden().children(rows))
         .child(render_active_overlay(ui, layout, show_active, now))
-        .child(render_line_clear_flash(ui.state.line_clear_timer_ms > 0))
+        .child(render_perfect_clear_callout(
+            ui.state.perfect_clear_active(),
+            layout.scale,
+        ))
         .child(render_lock_warning(if ui.state.is_grounded() {
             0.0
         } else {
@@ -115,12 +140,23 @@ pub fn render_board(
             paused: ui.state.paused,
             game_over: ui.state.game_over,
             focused,
+            can_continue: ui.saved_game.is_some(),
+            can_replay: ui.can_open_replay(),
+            replay_label: ui.replay_label(),
+            mode_label: ui.mode_label(),
+            results: ui.mode_results(),
             sfx_label: ui.sfx_volume_label(),
             muted: ui.sfx_muted,
+            initial_actions_label: ui.initial_actions_label(),
+            preset_label: ui.preset.name(),
             scale: layout.scale,
         }))
 }
 
+/// Room left above the board for the part of a spawning piece that sits in
+/// the vanish zone.
+const ABOVE_FIELD_ROWS: f32 = 2.0;
+
 fn render_active_overlay(
     ui: &UiState,
     layout: &RenderLayout,
@@ -132,7 +168,9 @@ fn render_active_overlay(
     }
 
     let active = ui.active_snapshot();
+    let system = ui.state.rotation_system;
     let cell_size = layout.cell_size;
+    let hidden = layout.hidden_rows as f32;
     let mut layer = div().absolute().top_0().left_0().right_0().bottom_0();
 
     if let Some(anim) = ui.active_animation_state(now) {
@@ -140,24 +178,21 @@ fn render_active_overlay(
         let dx = (anim.from_x - anim.to_x) as f32;
         let dy = (anim.from_y - anim.to_y) as f32;
         let offset_x = ((anim.to_x as f32) + dx * (1.0 - progress)) * cell_size;
-        let offset_y = ((anim.to_y as f32) + dy * (1.0 - progress)) * cell_size;
+        let offset_y = ((anim.to_y as f32) + dy * (1.0 - progress) - hidden) * cell_size;
 
-        let to_piece = gpui_tetris::game::pieces::Tetromino::new(anim.kind, 0, 0);
+        let to_piece =
+            gpui_tetris::game::pieces::Tetromino::new(anim.kind, 0, 0).with_system(system);
         let to_blocks = to_piece.blocks(anim.to_rotation);
         layer = layer.child(render_active_piece(
-            anim.kind,
-            &to_blocks,
-            offset_x,
-            offset_y,
-            cell_size,
-            1.0,
+            anim.kind, &to_blocks, offset_x, offset_y, cell_size, 1.0,
         ));
 
         if anim.rotation_changed && anim.from_rotation != anim.to_rotation {
-            let from_piece = gpui_tetris::game::pieces::Tetromino::new(anim.kind, 0, 0);
+            let from_piece =
+                gpui_tetris::game::pieces::Tetromino::new(anim.kind, 0, 0).with_system(system);
             let from_blocks = from_piece.blocks(anim.from_rotation);
             let from_offset_x = anim.from_x as f32 * cell_size;
-            let from_offset_y = anim.from_y as f32 * cell_size;
+            let from_offset_y = (anim.from_y as f32 - hidden) * cell_size;
             layer = layer.child(render_active_piece(
                 anim.kind,
                 &from_blocks,
@@ -168,10 +203,11 @@ fn render_active_overlay(
             ));
         }
     } else {
-        let piece = gpui_tetris::game::pieces::Tetromino::new(active.kind, 0, 0);
+        let piece =
+            gpui_tetris::game::pieces::Tetromino::new(active.kind, 0, 0).with_system(system);
         let blocks = piece.blocks(active.rotation);
         let offset_x = active.x as f32 * cell_size;
-        let offset_y = active.y as f32 * cell_size;
+        let offset_y = (active.y as f32 - hidden) * cell_size;
         layer = layer.child(render_active_piece(
             active.kind,
             &blocks,
@@ -211,7 +247,22 @@ pub fn render_panel(ui: &mut UiState, layout: &RenderLayout) -> impl IntoElement
                 .child(ui.panel_labels.score.clone())
                 .child(ui.panel_labels.level.clone())
                 .child(ui.panel_labels.lines.clone())
+                .child(if ui.panel_labels.timer.is_empty() {
+                    div().hidden()
+                } else if ui.state.time_warning() {
+                    div()
+                        .text_color(theme::time_warning())
+                        .child(format!("{} · Hurry!", ui.panel_labels.timer))
+                } else {
+                    div().child(ui.panel_labels.timer.clone())
+                })
+                .child(if ui.panel_labels.grade.is_empty() {
+                    div().hidden()
+                } else {
+                    div().child(ui.panel_labels.grade.clone())
+                })
                 .child(ui.panel_labels.status.clone())
+                .child(ui.panel_labels.mode.clone())
                 .child(ui.panel_labels.ruleset.clone())
                 .child(ui.panel_labels.hold.clone())
                 .child(ui.panel_labels.grounded.clone())
@@ -242,7 +293,9 @@ pub fn render_panel(ui: &mut UiState, layout: &RenderLayout) -> impl IntoElement
                     layout.scale,
                 )),
         )
-        .child(
+        .child(if !ui.state.hold_enabled() {
+            div().hidden()
+        } else {
             div()
                 .flex()
                 .flex_col()
@@ -252,9 +305,11 @@ pub fn render_panel(ui: &mut UiState, layout: &RenderLayout) -> impl IntoElement
                         .text_size(px(BASE_PANEL_TEXT * layout.scale * 0.95))
                         .child("Hold"),
                 )
-                .child(render_preview(ui, ui.state.hold, layout.cell_size)),
-        )
-        .child(
+                .child(render_preview(ui, ui.state.hold, layout.cell_size))
+        })
+        .child(if ui.state.preview_count == 0 {
+            div().hidden()
+        } else {
             div()
                 .flex()
                 .flex_col()
@@ -264,10 +319,6 @@ pub fn render_panel(ui: &mut UiState, layout: &RenderLayout) -> impl IntoElement
                         .text_size(px(BASE_PANEL_TEXT * layout.scale * 0.95))
                         .child("Next"),
                 )
-                .child(render_preview(
-                    ui,
-                    ui.state.next_queue.first().copied(),
-                    layout.cell_size,
-                )),
-        )
+                .child(render_next_queue(ui, layout.cell_size, layout.gap * 0.2))
+        })
 }
@@ -5,8 +5,8 @@ mod panel;
 pub mod theme;
 
 pub use board::{
-    render_active_piece, render_cell, render_game_over_tint, render_line_clear_flash,
-    render_lock_warning, render_preview,
+    render_active_piece, render_cell, render_clearing_cell, render_game_over_tint,
+    render_lock_warning, render_next_queue, render_perfect_clear_callout, render_preview,
 };
 pub use layout::RenderLayout;
 pub use layout::{render_board, render_panel};
@@ -1,15 +1,34 @@
-use gpui::{IntoElement, div, prelude::*, px};
+use gpui::{Div, IntoElement, div, prelude::*, px};
 
 use crate::ui::render::theme;
 use crate::ui::style::{BASE_HINT_TEXT, BASE_TITLE_TEXT};
 use crate::ui::ui_state::{
-    FOCUS_HINT, GAME_OVER_HINT, PAUSED_HINT, SETTINGS_BACK, SETTINGS_SHORTCUTS, TITLE_HINT,
-    TITLE_SETTINGS,
+    FOCUS_HINT, FUMEN_EXPORT_HINT, GAME_OVER_HINT, PAUSED_HINT, REPLAY_AVAILABLE_HINT,
+    REPLAY_CONTROLS, SETTINGS_BACK, SETTINGS_SHORTCUTS, TITLE_CONTINUE, TITLE_FUMEN, TITLE_HINT,
+    TITLE_MODES, TITLE_SETTINGS,
 };
 
 pub fn render_overlay(state: &OverlayState) -> impl IntoElement {
     let title_size = (BASE_TITLE_TEXT * state.scale).max(16.0);
     let hint_size = (BASE_HINT_TEXT * state.scale).max(10.0);
+    if let Some(replay_label) = &state.replay_label {
+        return div()
+            .absolute()
+            .top_0()
+            .left_0()
+            .right_0()
+            .bg(theme::overlay_bg())
+            .opacity(0.86)
+            .p_2()
+            .flex()
+            .flex_col()
+            .items_center()
+            .text_color(theme::overlay_text())
+            .text_size(px(hint_size))
+            .child(replay_label.clone())
+            .child(REPLAY_CONTROLS);
+    }
+
     if state.show_settings {
         return div()
             .absolute()
@@ -32,6 +51,15 @@ pub fn render_overlay(state: &OverlayState) -> impl IntoElement {
                 state.sfx_label,
                 if state.muted { " (M)" } else { "" }
             )))
+            .child(div().text_size(px(hint_size)).child(format!(
+                "IRS/IHS: {} (next game)",
+                state.initial_actions_label
+            )))
+            .child(
+                div()
+                    .text_size(px(hint_size))
+                    .child(format!("Rules: {} (next game)", state.preset_label)),
+            )
             .child(div().text_size(px(hint_size)).child(SETTINGS_SHORTCUTS))
             .child(div().text_size(px(hint_size)).child(SETTINGS_BACK));
     }
@@ -53,8 +81,21 @@ pub fn render_overlay(state: &OverlayState) -> impl IntoElement {
             .text_color(theme::overlay_text())
             .text_size(px(title_size))
             .child("gpui‑tetris")
+            .child(if state.can_continue {
+                div().text_size(px(hint_size)).child(TITLE_CONTINUE)
+            } else {
+                div().hidden()
+            })
+            .child(
+                div()
+                    .text_size(px(hint_size))
+                    .child(format!("Mode: {}", state.mode_label)),
+            )
+            .child(div().text_size(px(hint_size)).child(TITLE_MODES))
             .child(div().text_size(px(hint_size)).child(TITLE_HINT))
-            .child(div().text_size(px(hint_size)).child(TITLE_SETTINGS));
+            .child(div().text_size(px(hint_size)).child(TITLE_SETTINGS))
+            .child(div().text_size(px(hint_size)).child(TITLE_FUMEN))
+            .child(replay_hint(state.can_replay, hint_size));
     }
 
     if !state.paused && !state.game_over {
@@ -79,11 +120,18 @@ pub fn render_overlay(state: &OverlayState) -> impl IntoElement {
             .child(FOCUS_HINT);
     }
 
-    let label = if state.game_over {
+    let label = if let Some((title, _)) = &state.results {
+        *title
+    } else if state.game_over {
         "Game Over"
     } else {
         "Paused"
     };
+    let details = state
+        .results
+        .iter()
+        .flat_map(|(_, lines)| lines.iter())
+        .map(|line| div().text_size(px(hint_size)).child(line.clone()));
     let hint = if state.game_over {
         GAME_OVER_HINT
     } else {
@@ -106,7 +154,18 @@ pub fn render_overlay(state: &OverlayState) -> impl IntoElement {
         .text_color(theme::overlay_text())
         .text_size(px(title_size))
         .child(label)
+        .children(details)
         .child(div().text_size(px(hint_size)).child(hint))
+        .child(div().text_size(px(hint_size)).child(FUMEN_EXPORT_HINT))
+        .child(replay_hint(state.game_over && state.can_replay, hint_size))
+}
+
+fn replay_hint(visible: bool, hint_size: f32) -> Div {
+    if visible {
+        div().text_size(px(hint_size)).child(REPLAY_AVAILABLE_HINT)
+    } else {
+        div().hidden()
+    }
 }
 
 pub struct OverlayState {
@@ -115,7 +174,14 @@ pub struct OverlayState {
     pub paused: bool,
     pub game_over: bool,
     pub focused: bool,
+    pub can_continue: bool,
+    pub can_replay: bool,
+    pub replay_label: Option<String>,
+    pub mode_label: String,
+    pub results: Option<(&'static str, Vec<String>)>,
     pub sfx_label: String,
     pub muted: bool,
+    pub initial_actions_label: &'static str,
+    pub preset_label: &'static str,
     pub scale: f32,
 }
@@ -1,6 +1,7 @@
 use gpui::{Rgba, rgb};
 
 use crate::ui::style::piece_color;
+use gpui_tetris::game::board::Cell;
 use gpui_tetris::game::pieces::TetrominoType;
 
 pub fn app_bg() -> Rgba {
@@ -71,6 +72,29 @@ pub fn b2b_text() -> Rgba {
     rgb(0xfacc15)
 }
 
+pub fn time_warning() -> Rgba {
+    rgb(0xf87171)
+}
+
+pub fn perfect_clear_text() -> Rgba {
+    rgb(0x67e8f9)
+}
+
+pub fn garbage_fill() -> Rgba {
+    rgb(0x6b7280)
+}
+
+/// Fill for a settled board cell.
+pub fn cell_fill(cell: &Cell) -> Rgba {
+    if cell.garbage {
+        garbage_fill()
+    } else if cell.filled {
+        cell.kind.map(piece_color).unwrap_or_else(garbage_fill)
+    } else {
+        app_bg()
+    }
+}
+
 pub fn piece_fill(kind: Option<TetrominoType>, ghost: bool) -> Rgba {
     match kind {
         Some(piece) => {
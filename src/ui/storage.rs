@@ -0,0 +1,83 @@
+use gpui_tetris::game::records::PersonalBests;
+use gpui_tetris::game::replay::Replay;
+use gpui_tetris::game::state::GameSnapshot;
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+const SAVE_FILE: &str = "autosave.bin";
+const REPLAY_FILE: &str = "last_replay.gtr";
+const RECORDS_FILE: &str = "records.txt";
+
+pub fn data_dir() -> Option<PathBuf> {
+    if let Ok(dir) = env::var("TETRIS_DATA_DIR") {
+        return Some(PathBuf::from(dir));
+    }
+
+    let home = PathBuf::from(env::var_os("HOME")?);
+    if cfg!(target_os = "macos") {
+        Some(home.join("Library/Application Support/gpui-tetris"))
+    } else {
+        Some(home.join(".local/share/gpui-tetris"))
+    }
+}
+
+pub fn load_saved_game() -> Option<GameSnapshot> {
+    let path = data_dir()?.join(SAVE_FILE);
+    let bytes = fs::read(&path).ok()?;
+    match GameSnapshot::from_bytes(bytes) {
+        Ok(snapshot) => Some(snapshot),
+        Err(err) => {
+            eprintln!("discarding saved game: {err}");
+            let _ = fs::remove_file(path);
+            None
+        }
+    }
+}
+
+pub fn save_game(snapshot: &GameSnapshot) -> std::io::Result<()> {
+    write_data_file(SAVE_FILE, snapshot.as_bytes())
+}
+
+pub fn clear_saved_game() {
+    if let Some(dir) = data_dir() {
+        let _ = fs::remove_file(dir.join(SAVE_FILE));
+    }
+}
+
+pub fn load_replay() -> Option<Replay> {
+    let bytes = fs::read(data_dir()?.join(REPLAY_FILE)).ok()?;
+    match Replay::from_bytes(&bytes) {
+        Ok(replay) => Some(replay),
+        Err(err) => {
+            eprintln!("ignoring saved replay: {err}");
+            None
+        }
+    }
+}
+
+pub fn save_replay(replay: &Replay) -> std::io::Result<()> {
+    write_data_file(REPLAY_FILE, &replay.to_bytes())
+}
+
+pub fn load_bests() -> PersonalBests {
+    data_dir()
+        .and_then(|dir| fs::read_to_string(dir.join(RECORDS_FILE)).ok())
+        .map(|text| PersonalBests::from_text(&text))
+        .unwrap_or_default()
+}
+
+pub fn save_bests(bests: &PersonalBests) -> std::io::Result<()> {
+    write_data_file(RECORDS_FILE, bests.to_text().as_bytes())
+}
+
+fn write_data_file(name: &str, bytes: &[u8]) -> std::io::Result<()> {
+    let Some(dir) = data_dir() else {
+        return Ok(());
+    };
+    fs::create_dir_all(&dir)?;
+    // Write then rename so a crash mid-write never leaves a truncated file.
+    let tmp = dir.join(format!("{name}.tmp"));
+    fs::write(&tmp, bytes)?;
+    fs::rename(tmp, dir.join(name))
+}
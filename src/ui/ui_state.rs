@@ -1,12 +1,19 @@
 use gpui_tetris::audio::AudioEngine;
-use gpui_tetris::game::input::GameAction;
+use gpui_tetris::game::board::Cell;
+use gpui_tetris::game::fumen::{self, FumenPage};
+use gpui_tetris::game::input::{GameAction, HeldButtons};
 use gpui_tetris::game::pieces::{Rotation, Tetromino, TetrominoType};
-use gpui_tetris::game::state::GameState;
+use gpui_tetris::game::records::PersonalBests;
+use gpui_tetris::game::replay::{Replay, ReplayPlayer};
+use gpui_tetris::game::rotation::RotationSystem;
+use gpui_tetris::game::state::{
+    GameConfig, GameEvent, GameMode, GameSnapshot, GameState, LockDownMode, Ruleset, RulesetPreset,
+    TopOutReason,
+};
 use std::time::Instant;
 
-use crate::ui::style::{BOARD_COLS_USIZE, BOARD_ROWS_USIZE, DEFAULT_SFX_VOLUME};
-
-const BOARD_CELLS: usize = BOARD_COLS_USIZE * BOARD_ROWS_USIZE;
+use crate::ui::storage;
+use crate::ui::style::DEFAULT_SFX_VOLUME;
 
 pub struct UiState {
     pub last_action: Option<GameAction>,
@@ -15,15 +22,30 @@ pub struct UiState {
     pub show_settings: bool,
     pub sfx_volume: f32,
     pub sfx_muted: bool,
+    /// IRS/IHS setting, applied from the next game started.
+    pub initial_actions: bool,
+    /// Rules preset, applied from the next game started.
+    pub preset: RulesetPreset,
     pub audio: Option<AudioEngine>,
-    pub(crate) flash_mask: [bool; BOARD_CELLS],
-    pub(crate) active_mask: [bool; BOARD_CELLS],
-    pub(crate) ghost_mask: [bool; BOARD_CELLS],
+    pub saved_game: Option<GameSnapshot>,
+    pub last_replay: Option<Replay>,
+    replay_viewer: Option<ReplayViewer>,
+    pub mode: GameMode,
+    pub bests: PersonalBests,
+    result_recorded: bool,
+    new_best: bool,
+    /// Per visible cell, row-major; resized whenever the board size changes.
+    pub(crate) flash_mask: Vec<bool>,
+    pub(crate) active_mask: Vec<bool>,
+    pub(crate) ghost_mask: Vec<bool>,
     pub(crate) panel_labels: PanelLabels,
     labels_dirty: LabelDirty,
     pub(crate) preview_cache: PreviewCache,
-    pub(crate) board_cache: [Option<TetrominoType>; BOARD_CELLS],
+    pub(crate) board_cache: Vec<Cell>,
     board_revision: u64,
+    /// The cache holds the board from before the running line clear.
+    board_cache_restored: bool,
+    line_clear: Option<LineClearAnimation>,
     active_snapshot: Option<ActiveSnapshot>,
     active_anim: Option<ActiveAnimation>,
 }
@@ -34,7 +56,10 @@ pub struct PanelLabels {
     pub score: String,
     pub level: String,
     pub lines: String,
+    pub timer: String,
+    pub grade: String,
     pub status: String,
+    pub mode: String,
     pub ruleset: String,
     pub hold: String,
     pub grounded: String,
@@ -85,13 +110,22 @@ impl LabelDirty {
     }
 }
 
-pub const SETTINGS_SHORTCUTS: &str = "M: mute · +/-: volume · 0: reset";
+pub const SETTINGS_SHORTCUTS: &str = "M: mute · +/-: volume · I: IRS/IHS · G: rules · 0: reset";
 pub const SETTINGS_BACK: &str = "S or Esc: back";
 pub const TITLE_HINT: &str = "Press Enter or Space to Start";
 pub const TITLE_SETTINGS: &str = "S: Settings";
+pub const TITLE_FUMEN: &str = "F: Start from Fumen in Clipboard";
+pub const TITLE_CONTINUE: &str = "Press C to Continue";
+pub const TITLE_MODES: &str = "1: Marathon · 2: Sprint 40L · 3: Ultra 2:00 · 4: Dig 10 · 5: Master";
 pub const FOCUS_HINT: &str = "Click to Focus";
 pub const PAUSED_HINT: &str = "Press P to resume";
 pub const GAME_OVER_HINT: &str = "Press R to restart";
+pub const REPLAY_AVAILABLE_HINT: &str = "V: Watch Replay";
+pub const FUMEN_EXPORT_HINT: &str = "E: Copy Board as Fumen";
+pub const REPLAY_CONTROLS: &str =
+    "Space: pause · ←/→: seek · ↑/↓: speed · R: rewind · E: fumen · Esc: exit";
+
+const REPLAY_SEEK_MS: u64 = 5_000;
 
 const PREVIEW_SIZE: usize = 4;
 
@@ -104,10 +138,19 @@ impl UiState {
             show_settings: false,
             sfx_volume: DEFAULT_SFX_VOLUME,
             sfx_muted: false,
+            initial_actions: false,
+            preset: RulesetPreset::default(),
             audio,
-            flash_mask: [false; BOARD_CELLS],
-            active_mask: [false; BOARD_CELLS],
-            ghost_mask: [false; BOARD_CELLS],
+            saved_game: None,
+            last_replay: None,
+            replay_viewer: None,
+            mode: GameMode::Marathon,
+            bests: PersonalBests::new(),
+            result_recorded: false,
+            new_best: false,
+            flash_mask: Vec::new(),
+            active_mask: Vec::new(),
+            ghost_mask: Vec::new(),
             panel_labels: PanelLabels::default(),
             labels_dirty: {
                 let mut dirty = LabelDirty {
@@ -119,8 +162,10 @@ impl UiState {
                 dirty
             },
             preview_cache: PreviewCache::new(),
-            board_cache: [None; BOARD_CELLS],
+            board_cache: Vec::new(),
             board_revision: 0,
+            board_cache_restored: false,
+            line_clear: None,
             active_snapshot: None,
             active_anim: None,
         };
@@ -139,9 +184,15 @@ impl UiState {
             self.last_action = Some(action);
             self.labels_dirty.input = true;
         }
+        if self.replay_viewer.is_some() {
+            self.apply_replay_action(action);
+            return;
+        }
         if !self.started {
-            if matches!(action, GameAction::Restart | GameAction::HardDrop) {
-                self.start_game();
+            match action {
+                GameAction::Restart | GameAction::HardDrop => self.start_game(),
+                GameAction::Hold if self.saved_game.is_some() => self.continue_game(),
+                _ => {}
             }
             return;
         }
@@ -149,21 +200,359 @@ impl UiState {
             return;
         }
 
-        self.state.apply_action(action);
+        if action == GameAction::Restart {
+            self.archive_replay();
+            self.state.reset_with(self.next_config());
+        } else {
+            self.state.apply_action(action);
+        }
         if action == GameAction::Restart {
             self.started = true;
+            self.clear_mode_result();
+            if self.state.replay().is_none() {
+                self.state.start_recording();
+            }
+            if self.saved_game.take().is_some() {
+                storage::clear_saved_game();
+            }
         }
+        self.record_mode_result();
         self.labels_dirty.mark_game_dirty();
     }
 
     pub fn start_game(&mut self) {
+        self.archive_replay();
         self.started = true;
         self.show_settings = false;
-        self.state.reset();
+        self.clear_mode_result();
+        self.state.reset_with(self.next_config());
+        self.state.start_recording();
         self.state.paused = false;
         self.labels_dirty.mark_game_dirty();
         self.active_snapshot = None;
         self.active_anim = None;
+        if self.saved_game.take().is_some() {
+            storage::clear_saved_game();
+        }
+    }
+
+    /// Starts a game on the field of a fumen's first page; the pieces are
+    /// dealt as usual. A field that does not fit leaves a normal game.
+    pub fn start_from_fumen(&mut self, text: &str) {
+        let page = match fumen::decode(text) {
+            Ok(pages) => pages.into_iter().next(),
+            Err(err) => {
+                eprintln!("fumen not imported: {err}");
+                return;
+            }
+        };
+        let Some(page) = page else {
+            return;
+        };
+        self.start_game();
+        let built = GameState::builder()
+            .seed(self.state.seed())
+            .config(self.state.config())
+            .board(&page.board.to_ascii())
+            .build();
+        match built {
+            Ok(state) => {
+                self.state = state;
+                self.state.start_recording();
+                self.reset_render_tracking();
+            }
+            Err(err) => eprintln!("fumen field does not fit the board: {err}"),
+        }
+    }
+
+    /// The board on screen, with the falling piece, as a fumen.
+    pub fn export_fumen(&self) -> Option<String> {
+        fumen::encode(&[FumenPage::from_state(&self.state)])
+            .inspect_err(|err| eprintln!("fumen not exported: {err}"))
+            .ok()
+    }
+
+    pub fn continue_game(&mut self) {
+        let Some(snapshot) = self.saved_game.take() else {
+            return;
+        };
+        match GameState::from_snapshot(&snapshot) {
+            Ok(state) => {
+                self.state = state;
+                self.state.paused = true;
+                self.started = true;
+                self.mode = self.state.mode;
+                self.clear_mode_result();
+                self.show_settings = false;
+                self.board_revision = 0;
+                self.line_clear = None;
+                self.labels_dirty.mark_game_dirty();
+                self.active_snapshot = None;
+                self.active_anim = None;
+            }
+            Err(err) => {
+                eprintln!("saved game could not be restored: {err}");
+                storage::clear_saved_game();
+            }
+        }
+    }
+
+    pub fn autosave(&mut self) {
+        if !self.started || self.replay_viewer.is_some() {
+            return;
+        }
+        if self.state.game_over {
+            self.archive_replay();
+            self.saved_game = None;
+            storage::clear_saved_game();
+            return;
+        }
+        let snapshot = self.state.to_snapshot();
+        if let Err(err) = storage::save_game(&snapshot) {
+            eprintln!("autosave failed: {err}");
+        }
+        self.saved_game = Some(snapshot);
+    }
+
+    pub fn select_mode(&mut self, mode: GameMode) {
+        if self.started {
+            return;
+        }
+        self.mode = mode;
+        self.labels_dirty.ruleset = true;
+    }
+
+    /// Checks a finished mode against the personal bests once per run.
+    pub fn record_mode_result(&mut self) {
+        if self.result_recorded || self.replay_viewer.is_some() || !self.state.mode_complete {
+            return;
+        }
+        self.result_recorded = true;
+        self.new_best = match self.state.mode {
+            GameMode::Marathon | GameMode::Dig { .. } | GameMode::Master => false,
+            GameMode::Sprint { lines } => self.bests.record_sprint(lines, self.state.sim_time_ms),
+            GameMode::Ultra { duration_ms } => {
+                self.bests.record_ultra(duration_ms, self.state.score)
+            }
+        };
+        if self.new_best
+            && let Err(err) = storage::save_bests(&self.bests)
+        {
+            eprintln!("personal best not saved: {err}");
+        }
+    }
+
+    fn clear_mode_result(&mut self) {
+        self.result_recorded = false;
+        self.new_best = false;
+    }
+
+    /// Title and detail lines for the results screen of a finished mode.
+    pub fn mode_results(&self) -> Option<(&'static str, Vec<String>)> {
+        if self.replay_viewer.is_some() || !self.state.mode_complete {
+            return None;
+        }
+        match self.state.mode {
+            GameMode::Marathon => None,
+            GameMode::Sprint { lines } => {
+                let best = if self.new_best {
+                    "New personal best!".to_string()
+                } else {
+                    self.bests
+                        .sprint_best(lines)
+                        .map(|best| format!("Best {}", format_millis(best)))
+                        .unwrap_or_default()
+                };
+                Some((
+                    "Sprint Complete",
+                    vec![
+                        format!("Time {}", format_millis(self.state.sim_time_ms)),
+                        format!(
+                            "PPS {:.2} · Finesse faults {}",
+                            self.state.pieces_per_second(),
+                            self.state.finesse_faults
+                        ),
+                        best,
+                    ],
+                ))
+            }
+            GameMode::Ultra { duration_ms } => {
+                let best = if self.new_best {
+                    "New personal best!".to_string()
+                } else {
+                    self.bests
+                        .ultra_best(duration_ms)
+                        .map(|best| format!("Best {best}"))
+                        .unwrap_or_default()
+                };
+                Some((
+                    "Time Up",
+                    vec![
+                        format!("Score {}", self.state.score),
+                        format!(
+                            "Lines {} · PPS {:.2}",
+                            self.state.lines,
+                            self.state.pieces_per_second()
+                        ),
+                        best,
+                    ],
+                ))
+            }
+            GameMode::Dig { rows, .. } => Some((
+                "Dug Out",
+                vec![
+                    format!("{rows} rows in {}", format_millis(self.state.sim_time_ms)),
+                    format!(
+                        "Pieces {} · PPS {:.2}",
+                        self.state.pieces_placed,
+                        self.state.pieces_per_second()
+                    ),
+                ],
+            )),
+            GameMode::Master => Some((
+                "Master Complete",
+                vec![
+                    format!("Grade {}", self.state.master_grade().unwrap_or("-")),
+                    format!("Time {}", format_millis(self.state.sim_time_ms)),
+                    format!("Score {}", self.state.score),
+                ],
+            )),
+        }
+    }
+
+    /// Keeps the finished run's input log so it can be watched after a restart.
+    fn archive_replay(&mut self) {
+        let Some(replay) = self.state.replay() else {
+            return;
+        };
+        if replay.is_empty() {
+            return;
+        }
+        if let Err(err) = storage::save_replay(replay) {
+            eprintln!("replay not saved: {err}");
+        }
+        self.last_replay = Some(replay.clone());
+    }
+
+    pub fn can_open_replay(&self) -> bool {
+        if self.replay_viewer.is_some() || self.show_settings {
+            return false;
+        }
+        if self.started && self.state.game_over {
+            return self.state.replay().is_some_and(|replay| !replay.is_empty());
+        }
+        !self.started && self.last_replay.is_some()
+    }
+
+    pub fn open_replay_viewer(&mut self) {
+        if !self.can_open_replay() {
+            return;
+        }
+        let replay = if self.started {
+            self.state.replay().cloned()
+        } else {
+            self.last_replay.clone()
+        };
+        let Some(replay) = replay else {
+            return;
+        };
+
+        let player = ReplayPlayer::new(replay);
+        let resume = std::mem::replace(&mut self.state, player.state().clone());
+        self.replay_viewer = Some(ReplayViewer { player, resume });
+        self.reset_render_tracking();
+    }
+
+    pub fn close_replay_viewer(&mut self) {
+        if let Some(viewer) = self.replay_viewer.take() {
+            self.state = viewer.resume;
+            self.reset_render_tracking();
+        }
+    }
+
+    pub fn is_replay_active(&self) -> bool {
+        self.replay_viewer.is_some()
+    }
+
+    pub fn advance_replay(&mut self, elapsed_ms: u64) {
+        let Some(viewer) = self.replay_viewer.as_mut() else {
+            return;
+        };
+        let before = viewer.player.position_ms();
+        viewer.player.advance(elapsed_ms);
+        if viewer.player.position_ms() != before {
+            self.sync_replay_state();
+        }
+    }
+
+    pub fn seek_replay(&mut self, forward: bool) {
+        let Some(viewer) = self.replay_viewer.as_mut() else {
+            return;
+        };
+        let position = viewer.player.position_ms();
+        let target = if forward {
+            position.saturating_add(REPLAY_SEEK_MS)
+        } else {
+            position.saturating_sub(REPLAY_SEEK_MS)
+        };
+        viewer.player.seek(target);
+        self.sync_replay_state();
+        self.state.take_events();
+        self.active_anim = None;
+    }
+
+    pub fn scale_replay_speed(&mut self, faster: bool) {
+        if let Some(viewer) = self.replay_viewer.as_mut() {
+            let speed = viewer.player.speed();
+            viewer
+                .player
+                .set_speed(if faster { speed * 2.0 } else { speed / 2.0 });
+        }
+    }
+
+    pub fn replay_label(&self) -> Option<String> {
+        let viewer = self.replay_viewer.as_ref()?;
+        let player = &viewer.player;
+        Some(format!(
+            "Replay {} / {} · {}x{}",
+            format_clock(player.position_ms()),
+            format_clock(player.duration_ms()),
+            player.speed(),
+            if player.is_paused() { " · Paused" } else { "" }
+        ))
+    }
+
+    fn apply_replay_action(&mut self, action: GameAction) {
+        let Some(viewer) = self.replay_viewer.as_mut() else {
+            return;
+        };
+        match action {
+            GameAction::Pause | GameAction::HardDrop => viewer.player.toggle_pause(),
+            GameAction::RotateCw => self.scale_replay_speed(true),
+            GameAction::Restart => {
+                viewer.player.seek(0);
+                self.sync_replay_state();
+                self.state.take_events();
+                self.active_anim = None;
+            }
+            _ => {}
+        }
+    }
+
+    fn sync_replay_state(&mut self) {
+        if let Some(viewer) = self.replay_viewer.as_mut() {
+            self.state.clone_from(viewer.player.state());
+            viewer.player.take_events();
+            self.labels_dirty.mark_game_dirty();
+        }
+    }
+
+    fn reset_render_tracking(&mut self) {
+        self.board_revision = 0;
+        self.line_clear = None;
+        self.active_snapshot = None;
+        self.active_anim = None;
+        self.labels_dirty.mark_game_dirty();
     }
 
     pub fn toggle_settings(&mut self) {
@@ -196,7 +585,38 @@ impl UiState {
         self.labels_dirty.sfx = true;
     }
 
+    pub fn toggle_initial_actions(&mut self) {
+        self.initial_actions = !self.initial_actions;
+    }
+
+    pub fn initial_actions_label(&self) -> &'static str {
+        if self.initial_actions { "On" } else { "Off" }
+    }
+
+    pub fn cycle_preset(&mut self) {
+        self.preset = self.preset.next();
+    }
+
+    /// The current game's settings with the selected mode and the settings
+    /// that wait for the next game.
+    fn next_config(&self) -> GameConfig {
+        self.preset.apply(GameConfig {
+            mode: self.mode,
+            initial_actions: self.initial_actions,
+            ..self.state.config()
+        })
+    }
+
+    /// Passes the held rotate and hold buttons on to the game for IRS/IHS.
+    pub fn set_held_buttons(&mut self, held: HeldButtons) {
+        if self.can_accept_game_input() {
+            self.state.set_held_buttons(held);
+        }
+    }
+
     pub fn reset_settings(&mut self) {
+        self.initial_actions = false;
+        self.preset = RulesetPreset::default();
         self.sfx_muted = false;
         self.sfx_volume = DEFAULT_SFX_VOLUME;
         self.apply_audio_volume();
@@ -211,14 +631,28 @@ impl UiState {
     }
 
     pub fn can_accept_game_input(&self) -> bool {
-        self.started && !self.show_settings && !self.state.paused && !self.state.game_over
+        self.started
+            && !self.show_settings
+            && self.replay_viewer.is_none()
+            && !self.state.paused
+            && !self.state.game_over
     }
 
     pub fn status_label(&self) -> &'static str {
-        if !self.started {
+        if self.replay_viewer.is_some() {
+            "Replay"
+        } else if !self.started {
             "Title"
+        } else if self.state.mode_complete {
+            "Finished"
         } else if self.state.game_over {
-            "Game Over"
+            match self.state.top_out {
+                Some(TopOutReason::BlockOut) => "Block Out",
+                Some(TopOutReason::LockOut) => "Lock Out",
+                Some(TopOutReason::PartialLockOut) => "Partial Lock Out",
+                Some(TopOutReason::GarbageOut) => "Garbage Out",
+                None => "Game Over",
+            }
         } else if self.show_settings {
             "Settings"
         } else if self.state.paused {
@@ -229,10 +663,25 @@ impl UiState {
     }
 
     pub fn ruleset_label(&self) -> &'static str {
-        if self.state.is_classic_ruleset() {
-            "Classic"
+        match self.state.ruleset {
+            Ruleset::Classic => "Classic",
+            Ruleset::Modern => "Modern",
+            Ruleset::Nes => "NES",
+        }
+    }
+
+    pub fn mode_label(&self) -> String {
+        let mode = if self.started {
+            self.state.mode
         } else {
-            "Modern"
+            self.mode
+        };
+        match mode {
+            GameMode::Marathon => "Marathon".to_string(),
+            GameMode::Sprint { lines } => format!("Sprint {lines}L"),
+            GameMode::Ultra { duration_ms } => format!("Ultra {}", format_clock(duration_ms)),
+            GameMode::Dig { rows, .. } => format!("Dig {rows}"),
+            GameMode::Master => "Master".to_string(),
         }
     }
 
@@ -245,12 +694,25 @@ impl UiState {
     }
 
     pub fn clear_render_masks(&mut self) {
-        self.flash_mask.fill(false);
-        self.active_mask.fill(false);
-        self.ghost_mask.fill(false);
+        let cells = self.visible_cells();
+        for mask in [
+            &mut self.flash_mask,
+            &mut self.active_mask,
+            &mut self.ghost_mask,
+        ] {
+            mask.clear();
+            mask.resize(cells, false);
+        }
     }
 
-    pub fn update_active_animation(&mut self, now: Instant) {
+    fn visible_cells(&self) -> usize {
+        let size = self.state.board.size();
+        size.width * size.visible_height
+    }
+
+    /// Slides or turns the active piece for the moves and rotations in
+    /// `events`; a new piece or a lock cancels any animation in flight.
+    pub fn update_active_animation(&mut self, events: &[GameEvent], now: Instant) {
         let current = self.snapshot_active();
         let Some(previous) = self.active_snapshot else {
             self.active_snapshot = Some(current);
@@ -261,7 +723,18 @@ impl UiState {
             return;
         }
 
-        if previous.kind != current.kind {
+        let new_piece = events.iter().any(|event| {
+            matches!(
+                event,
+                GameEvent::PieceSpawned { .. }
+                    | GameEvent::Held { .. }
+                    | GameEvent::PieceLocked { .. }
+            )
+        });
+        let animated = events
+            .iter()
+            .any(|event| matches!(event, GameEvent::Moved { .. } | GameEvent::Rotated { .. }));
+        if new_piece || !animated || previous.kind != current.kind {
             self.active_snapshot = Some(current);
             self.active_anim = None;
             return;
@@ -322,7 +795,7 @@ impl UiState {
         &mut self,
         kind: Option<TetrominoType>,
     ) -> &[[bool; PREVIEW_SIZE]; PREVIEW_SIZE] {
-        self.preview_cache.mask(kind)
+        self.preview_cache.mask(kind, self.state.rotation_system)
     }
 
     pub fn sync_panel_labels(&mut self) {
@@ -338,24 +811,107 @@ impl UiState {
     }
 
     pub fn pause_from_focus_loss(&mut self) {
-        if self.started && !self.state.game_over {
+        if self.started && self.replay_viewer.is_none() && !self.state.game_over {
             self.state.paused = true;
             self.labels_dirty.mark_game_dirty();
+            self.autosave();
         }
     }
 
+    /// While a line clear pauses the game the cache shows the board as it
+    /// was before the clear, so the cleared rows can play out in place.
     pub fn sync_board_cache(&mut self) {
         let revision = self.state.board_revision();
-        if self.board_revision == revision {
+        let cells = self.visible_cells();
+        let cleared = self.state.cleared_rows();
+        let restored = !cleared.is_empty();
+        if self.board_revision == revision
+            && self.board_cache_restored == restored
+            && self.board_cache.len() == cells
+        {
             return;
         }
-        for (y, row) in self.state.board.cells.iter().enumerate() {
+        let before_clear = restored.then(|| {
+            let mut board = self.state.board.clone();
+            board.restore_lines(cleared);
+            board
+        });
+        let board = before_clear.as_ref().unwrap_or(&self.state.board);
+        self.board_cache.resize(cells, Cell::default());
+        let size = board.size();
+        let visible = board.cells().iter().skip(size.buffer_height());
+        for (y, row) in visible.enumerate() {
             for (x, cell) in row.iter().enumerate() {
-                let idx = y * BOARD_COLS_USIZE + x;
-                self.board_cache[idx] = if cell.filled { cell.kind } else { None };
+                let idx = y * size.width + x;
+                self.board_cache[idx] = *cell;
             }
         }
         self.board_revision = revision;
+        self.board_cache_restored = restored;
+    }
+
+    /// Follows the game's line-clear pause and starts the collapse of the
+    /// rows above once it ends.
+    pub fn update_line_clear_animation(&mut self, now: Instant) {
+        let cleared = self.state.cleared_rows();
+        if !cleared.is_empty() {
+            let same_clear = self.line_clear.as_ref().is_some_and(|anim| {
+                anim.collapse_started.is_none()
+                    && anim
+                        .rows
+                        .iter()
+                        .copied()
+                        .eq(cleared.iter().map(|row| row.y))
+            });
+            if !same_clear {
+                self.line_clear = Some(LineClearAnimation {
+                    rows: cleared.iter().map(|row| row.y).collect(),
+                    collapse_started: None,
+                });
+            }
+            return;
+        }
+        let Some(anim) = self.line_clear.as_mut() else {
+            return;
+        };
+        match anim.collapse_started {
+            None => anim.collapse_started = Some(now),
+            Some(started) => {
+                if now.duration_since(started).as_millis() as u64 >= LINE_COLLAPSE_MS {
+                    self.line_clear = None;
+                }
+            }
+        }
+    }
+
+    /// Progress of the line-clear pause if visible row `y` is being cleared.
+    pub(crate) fn clearing_row_progress(&self, y: usize) -> Option<f32> {
+        let anim = self.line_clear.as_ref()?;
+        if anim.collapse_started.is_some() {
+            return None;
+        }
+        let row = y + self.state.board.size().buffer_height();
+        anim.rows
+            .contains(&row)
+            .then(|| self.state.line_clear_progress())
+    }
+
+    /// Rows visible row `y` still sits above its place while the stack
+    /// collapses after a line clear.
+    pub(crate) fn collapse_offset_rows(&self, y: usize, now: Instant) -> f32 {
+        let Some(anim) = self.line_clear.as_ref() else {
+            return 0.0;
+        };
+        let Some(started) = anim.collapse_started else {
+            return 0.0;
+        };
+        let elapsed = now.duration_since(started).as_millis() as u64;
+        if elapsed >= LINE_COLLAPSE_MS {
+            return 0.0;
+        }
+        let progress = elapsed as f32 / LINE_COLLAPSE_MS as f32;
+        let row = y + self.state.board.size().buffer_height();
+        collapse_shift(&anim.rows, row) as f32 * (1.0 - progress)
     }
 
     fn update_panel_labels(&mut self) {
@@ -369,19 +925,58 @@ impl UiState {
         }
         if self.labels_dirty.stats {
             self.panel_labels.score = format!("Score: {}", self.state.score);
-            self.panel_labels.level = format!("Level: {}", self.state.level);
-            self.panel_labels.lines = format!("Lines: {}", self.state.lines);
+            self.panel_labels.level = match self.state.mode {
+                // The level that stops the piece counter for this section.
+                GameMode::Master => format!(
+                    "Level: {}/{}",
+                    self.state.level,
+                    ((self.state.level / 100 + 1) * 100).min(999)
+                ),
+                _ => format!("Level: {}", self.state.level),
+            };
+            self.panel_labels.lines = match self.state.mode {
+                GameMode::Sprint { lines } => format!("Lines: {}/{}", self.state.lines, lines),
+                GameMode::Dig { .. } => format!(
+                    "Garbage left: {}",
+                    self.state.garbage_remaining().unwrap_or(0)
+                ),
+                GameMode::Marathon | GameMode::Ultra { .. } | GameMode::Master => {
+                    format!("Lines: {}", self.state.lines)
+                }
+            };
+            self.panel_labels.timer = match self.state.mode {
+                GameMode::Sprint { .. } | GameMode::Dig { .. } | GameMode::Master => {
+                    format!("Time: {}", format_millis(self.state.sim_time_ms))
+                }
+                GameMode::Ultra { .. } => format!(
+                    "Time left: {}",
+                    format_millis(self.state.time_remaining_ms().unwrap_or(0))
+                ),
+                GameMode::Marathon => String::new(),
+            };
+            self.panel_labels.grade = self
+                .state
+                .master_grade()
+                .map(|grade| format!("Grade: {grade}"))
+                .unwrap_or_default();
         }
         if self.labels_dirty.status {
             self.panel_labels.status = format!("Status: {}", self.status_label());
         }
         if self.labels_dirty.ruleset {
             self.panel_labels.ruleset = format!("Rules: {}", self.ruleset_label());
+            self.panel_labels.mode = format!("Mode: {}", self.mode_label());
         }
         if self.labels_dirty.hold {
             self.panel_labels.hold = format!(
                 "Hold: {}",
-                if self.state.can_hold { "Ready" } else { "Used" }
+                if !self.state.hold_enabled() {
+                    "Off"
+                } else if self.state.can_hold {
+                    "Ready"
+                } else {
+                    "Used"
+                }
             );
         }
         if self.labels_dirty.grounded {
@@ -395,11 +990,15 @@ impl UiState {
             );
         }
         if self.labels_dirty.lock {
-            self.panel_labels.lock_resets = format!(
-                "Lock resets: {}/{}",
-                self.state.lock_reset_remaining(),
-                self.state.lock_reset_limit
-            );
+            self.panel_labels.lock_resets = match self.state.lock_down_mode {
+                LockDownMode::ExtendedPlacement => format!(
+                    "Lock resets: {}/{}",
+                    self.state.lock_reset_remaining(),
+                    self.state.lock_reset_limit
+                ),
+                LockDownMode::Infinite => "Lock resets: ∞".to_string(),
+                LockDownMode::Classic => "Lock resets: step only".to_string(),
+            };
         }
         if self.labels_dirty.sfx {
             self.panel_labels.sfx = format!("SFX: {}", self.sfx_volume_label());
@@ -430,6 +1029,20 @@ impl UiState {
     }
 }
 
+struct ReplayViewer {
+    player: ReplayPlayer,
+    resume: GameState,
+}
+
+fn format_clock(ms: u64) -> String {
+    let seconds = ms / 1000;
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+}
+
+fn format_millis(ms: u64) -> String {
+    format!("{}.{:03}", format_clock(ms), ms % 1000)
+}
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub(crate) struct ActiveSnapshot {
     pub kind: TetrominoType,
@@ -438,6 +1051,26 @@ pub(crate) struct ActiveSnapshot {
     pub rotation: Rotation,
 }
 
+/// How long the stack takes to fall into the cleared rows.
+const LINE_COLLAPSE_MS: u64 = 90;
+
+struct LineClearAnimation {
+    /// Board rows before the clear, top to bottom.
+    rows: Vec<usize>,
+    collapse_started: Option<Instant>,
+}
+
+/// Rows that board row `row` fell by in a clear of `cleared` (sorted, in
+/// pre-clear coordinates): the number of cleared rows that were below it.
+fn collapse_shift(cleared: &[usize], row: usize) -> usize {
+    let count = cleared.len();
+    cleared
+        .iter()
+        .enumerate()
+        .find(|&(index, &y)| row < y + count - index)
+        .map_or(0, |(index, _)| count - index)
+}
+
 #[derive(Clone, Copy, Debug)]
 struct ActiveAnimation {
     from: ActiveSnapshot,
@@ -461,20 +1094,30 @@ pub(crate) struct ActiveAnimationState {
 #[derive(Clone)]
 pub struct PreviewCache {
     masks: [Option<[[bool; PREVIEW_SIZE]; PREVIEW_SIZE]>; 7],
+    system: RotationSystem,
 }
 
 impl PreviewCache {
     pub fn new() -> Self {
         Self {
             masks: std::array::from_fn(|_| None),
+            system: RotationSystem::default(),
         }
     }
 
-    pub fn mask(&mut self, kind: Option<TetrominoType>) -> &[[bool; PREVIEW_SIZE]; PREVIEW_SIZE] {
+    pub fn mask(
+        &mut self,
+        kind: Option<TetrominoType>,
+        system: RotationSystem,
+    ) -> &[[bool; PREVIEW_SIZE]; PREVIEW_SIZE] {
+        if system != self.system {
+            self.masks = std::array::from_fn(|_| None);
+            self.system = system;
+        }
         if let Some(kind) = kind {
             let idx = kind as usize;
             if self.masks[idx].is_none() {
-                self.masks[idx] = Some(build_preview_mask(kind));
+                self.masks[idx] = Some(build_preview_mask(kind, system));
             }
             return self.masks[idx].as_ref().expect("cached mask");
         }
@@ -484,9 +1127,12 @@ impl PreviewCache {
     }
 }
 
-fn build_preview_mask(kind: TetrominoType) -> [[bool; PREVIEW_SIZE]; PREVIEW_SIZE] {
+fn build_preview_mask(
+    kind: TetrominoType,
+    system: RotationSystem,
+) -> [[bool; PREVIEW_SIZE]; PREVIEW_SIZE] {
     let mut filled = [[false; PREVIEW_SIZE]; PREVIEW_SIZE];
-    let piece = Tetromino::new(kind, 0, 0);
+    let piece = Tetromino::new(kind, 0, 0).with_system(system);
     for (x, y) in piece.blocks(piece.rotation).iter() {
         let ux = *x as usize;
         let uy = *y as usize;
@@ -505,6 +1151,7 @@ fn action_label(action: &GameAction) -> &'static str {
         GameAction::HardDrop => "Hard Drop",
         GameAction::RotateCw => "Rotate CW",
         GameAction::RotateCcw => "Rotate CCW",
+        GameAction::Rotate180 => "Rotate 180",
         GameAction::Hold => "Hold",
         GameAction::Pause => "Pause",
         GameAction::Restart => "Restart",
@@ -513,10 +1160,12 @@ fn action_label(action: &GameAction) -> &'static str {
 
 #[cfg(test)]
 mod tests {
-    use super::UiState;
+    use super::{UiState, collapse_shift};
+    use gpui_tetris::game::board::Board;
+    use gpui_tetris::game::fumen::{self, FumenPage};
     use gpui_tetris::game::input::GameAction;
     use gpui_tetris::game::pieces::TetrominoType;
-    use gpui_tetris::game::state::GameState;
+    use gpui_tetris::game::state::{GameMode, GameSnapshot, GameState, Ruleset, RulesetPreset};
 
     #[test]
     fn start_game_sets_started_and_unpauses() {
@@ -530,6 +1179,85 @@ mod tests {
         assert!(!ui.state.paused);
     }
 
+    #[test]
+    fn fumen_import_starts_on_the_field() {
+        let mut ui = UiState::new(GameState::new(1, Default::default()), None);
+        let field = Board::from_ascii("GGGG.GGGGG").expect("parses");
+        let text = fumen::encode(&[FumenPage {
+            board: field.clone(),
+            ..FumenPage::default()
+        }])
+        .expect("encodes");
+
+        ui.start_from_fumen(&text);
+
+        assert!(ui.started);
+        assert_eq!(ui.state.board, field);
+        assert_eq!(
+            ui.state.replay().and_then(|replay| replay.field.as_ref()),
+            Some(&field)
+        );
+        assert!(
+            ui.export_fumen()
+                .is_some_and(|text| text.starts_with("v115@"))
+        );
+    }
+
+    #[test]
+    fn start_game_uses_selected_mode() {
+        let state = GameState::new(1, Default::default());
+        let mut ui = UiState::new(state, None);
+
+        ui.select_mode(GameMode::sprint());
+        ui.start_game();
+
+        assert_eq!(ui.state.mode, GameMode::sprint());
+        assert_eq!(ui.mode_label(), "Sprint 40L");
+        ui.select_mode(GameMode::Marathon);
+        assert_eq!(ui.mode, GameMode::sprint());
+    }
+
+    #[test]
+    fn ultra_panel_counts_down() {
+        let mut ui = UiState::new(GameState::new(1, Default::default()), None);
+        ui.select_mode(GameMode::ultra());
+        assert_eq!(ui.mode_label(), "Ultra 2:00");
+
+        ui.start_game();
+        for _ in 0..10 {
+            ui.state.step(false);
+        }
+        ui.mark_game_dirty();
+        ui.sync_panel_labels();
+        assert_eq!(ui.panel_labels.timer, "Time left: 1:59.840");
+    }
+
+    #[test]
+    fn dig_panel_shows_garbage_left() {
+        let mut ui = UiState::new(GameState::new(1, Default::default()), None);
+        ui.select_mode(GameMode::dig());
+        ui.start_game();
+        ui.mark_game_dirty();
+        ui.sync_panel_labels();
+
+        assert_eq!(ui.mode_label(), "Dig 10");
+        assert_eq!(ui.panel_labels.lines, "Garbage left: 10");
+        assert_eq!(ui.panel_labels.timer, "Time: 0:00.000");
+    }
+
+    #[test]
+    fn master_panel_shows_section_and_grade() {
+        let mut ui = UiState::new(GameState::new(1, Default::default()), None);
+        ui.select_mode(GameMode::Master);
+        ui.start_game();
+        ui.mark_game_dirty();
+        ui.sync_panel_labels();
+
+        assert_eq!(ui.mode_label(), "Master");
+        assert_eq!(ui.panel_labels.level, "Level: 0/100");
+        assert_eq!(ui.panel_labels.grade, "Grade: 9");
+    }
+
     #[test]
     fn toggle_settings_pauses_when_opened() {
         let state = GameState::new(1, Default::default());
@@ -542,6 +1270,41 @@ mod tests {
         assert!(ui.state.paused);
     }
 
+    #[test]
+    fn initial_actions_setting_applies_to_the_next_game() {
+        let mut ui = UiState::new(GameState::new(1, Default::default()), None);
+        ui.toggle_initial_actions();
+        assert_eq!(ui.initial_actions_label(), "On");
+        assert!(!ui.state.initial_actions);
+
+        ui.start_game();
+        assert!(ui.state.initial_actions);
+
+        ui.reset_settings();
+        ui.apply_action(GameAction::Restart, true);
+        assert!(!ui.state.initial_actions);
+    }
+
+    #[test]
+    fn rules_preset_applies_to_the_next_game() {
+        let mut ui = UiState::new(GameState::new(1, Default::default()), None);
+        ui.cycle_preset();
+        ui.cycle_preset();
+        assert_eq!(ui.preset, RulesetPreset::Nes);
+        assert_eq!(ui.state.ruleset, Ruleset::Classic);
+
+        ui.select_mode(GameMode::sprint());
+        ui.start_game();
+        assert_eq!(ui.state.ruleset, Ruleset::Nes);
+        assert_eq!(ui.state.mode, GameMode::sprint());
+        assert_eq!(ui.ruleset_label(), "NES");
+
+        ui.reset_settings();
+        ui.apply_action(GameAction::Restart, true);
+        assert_eq!(ui.state.ruleset, Ruleset::Classic);
+        assert_eq!(ui.state.mode, GameMode::sprint());
+    }
+
     #[test]
     fn volume_label_reflects_muted_state() {
         let state = GameState::new(1, Default::default());
@@ -576,4 +1339,54 @@ mod tests {
         let any_empty_filled = empty.iter().flatten().any(|filled| *filled);
         assert!(!any_empty_filled);
     }
+
+    #[test]
+    fn continue_game_restores_paused_snapshot() {
+        let mut saved = GameState::new(4, Default::default());
+        saved.apply_action(GameAction::HardDrop);
+        saved.score = 900;
+        let mut ui = UiState::new(GameState::new(1, Default::default()), None);
+        ui.saved_game = Some(saved.to_snapshot());
+
+        ui.apply_action(GameAction::Hold, true);
+
+        assert!(ui.started);
+        assert!(ui.state.paused);
+        assert_eq!(ui.state.score, 900);
+        assert_eq!(ui.saved_game, None::<GameSnapshot>);
+    }
+
+    #[test]
+    fn replay_viewer_plays_last_replay_and_restores_state() {
+        let mut recorded = GameState::new(8, Default::default());
+        recorded.start_recording();
+        for _ in 0..4 {
+            recorded.step(false);
+            recorded.apply_action(GameAction::HardDrop);
+        }
+        let mut ui = UiState::new(GameState::new(1, Default::default()), None);
+        ui.last_replay = recorded.take_replay();
+
+        ui.open_replay_viewer();
+        assert!(ui.is_replay_active());
+        assert!(!ui.can_accept_game_input());
+
+        ui.advance_replay(1000);
+        assert_eq!(ui.state.score, recorded.score);
+        assert_eq!(ui.state.active, recorded.active);
+
+        ui.close_replay_viewer();
+        assert!(!ui.is_replay_active());
+        assert_eq!(ui.state.score, 0);
+    }
+
+    #[test]
+    fn collapse_shift_counts_cleared_rows_below() {
+        let cleared = [36, 37, 39];
+        // Row 38 survived and now sits at 39; everything above 36 fell by three.
+        assert_eq!(collapse_shift(&cleared, 39), 1);
+        assert_eq!(collapse_shift(&cleared, 38), 3);
+        assert_eq!(collapse_shift(&cleared, 10), 3);
+        assert_eq!(collapse_shift(&[], 39), 0);
+    }
 }
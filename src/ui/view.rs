@@ -2,12 +2,14 @@ use gpui::{
     Context, FocusHandle, IntoElement, MouseButton, Render, Window, div, prelude::*, px, rgb,
 };
 use gpui_tetris::audio::AudioEngine;
-use gpui_tetris::game::input::GameAction;
-use gpui_tetris::game::state::{GameConfig, GameState};
-use std::time::Instant;
+use gpui_tetris::game::clock::FixedTimestep;
+use gpui_tetris::game::input::{GameAction, RepeatConfig};
+use gpui_tetris::game::state::{GameConfig, GameEvent, GameState};
+use std::time::{Duration, Instant};
 
 use crate::ui::input::{InputAction, InputState};
 use crate::ui::render::{RenderLayout, render_board, render_panel};
+use crate::ui::storage;
 use crate::ui::style::{BASE_WINDOW_HEIGHT, BASE_WINDOW_WIDTH, MIN_SCALE};
 use crate::ui::ui_state::UiState;
 
@@ -16,6 +18,7 @@ mod events;
 pub struct TetrisView {
     ui: UiState,
     last_tick: Option<Instant>,
+    clock: FixedTimestep,
     focus_handle: FocusHandle,
     input: InputState,
     was_focused: bool,
@@ -26,9 +29,15 @@ impl TetrisView {
     pub fn new(cx: &mut Context<Self>, audio: Option<AudioEngine>) -> Self {
         let state = GameState::new(1, GameConfig::default());
         let focus_handle = cx.focus_handle();
+        let clock = FixedTimestep::new(state.step_ms());
+        let mut ui = UiState::new(state, audio);
+        ui.saved_game = storage::load_saved_game();
+        ui.last_replay = storage::load_replay();
+        ui.bests = storage::load_bests();
         Self {
-            ui: UiState::new(state, audio),
+            ui,
             last_tick: None,
+            clock,
             focus_handle,
             input: InputState::new(),
             was_focused: true,
@@ -40,21 +49,28 @@ impl TetrisView {
         &self.focus_handle
     }
 
+    /// Key bindings only report presses; the matching key up in
+    /// `on_key_up` releases the button again.
     pub fn receive_action(&mut self, action: GameAction) {
+        self.input.set_keyboard_button(action, true);
         self.ui.receive_action(action);
     }
+
+    pub fn autosave(&mut self) {
+        self.ui.autosave();
+    }
 }
 
 impl Render for TetrisView {
     fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
         let scale = compute_scale(window);
-        let layout = RenderLayout::new(scale);
+        let layout = RenderLayout::new(scale, self.ui.state.board.size());
         let now = Instant::now();
         let focused = self.update_focus(window);
         self.advance_frame(now);
 
         window.request_animation_frame();
-        self.play_sound_events();
+        self.handle_game_events(now);
         self.ui.sync_panel_labels();
 
         let board = render_board(&mut self.ui, &layout, focused, now);
@@ -75,13 +91,15 @@ impl Render for TetrisView {
 }
 
 impl TetrisView {
-    fn play_sound_events(&mut self) {
-        let events = self.ui.state.take_sound_events();
+    /// Drains the game's events into audio and the piece animation.
+    fn handle_game_events(&mut self, now: Instant) {
+        let events = self.ui.state.take_events();
         if let Some(audio) = &self.ui.audio {
-            for event in events {
-                audio.play(event);
+            for sound in events.iter().filter_map(GameEvent::sound) {
+                audio.play(sound);
             }
         }
+        self.ui.update_active_animation(&events, now);
     }
 
     fn update_focus(&mut self, window: &Window) -> bool {
@@ -98,21 +116,40 @@ impl TetrisView {
         self.apply_buffered_actions();
 
         if let Some(prev) = self.last_tick {
-            let elapsed_ms = now.duration_since(prev).as_millis() as u64;
-            if elapsed_ms > 0 && self.ui.started && !self.ui.show_settings {
-                self.ui.state.tick(elapsed_ms, false);
-                self.ui.mark_game_dirty();
-                self.input.apply_repeats_into(
-                    elapsed_ms,
-                    self.ui.can_accept_game_input(),
-                    &mut self.input_actions,
-                );
-                self.apply_buffered_actions();
+            let elapsed = now.duration_since(prev);
+            if self.ui.is_replay_active() {
+                self.ui.advance_replay(elapsed.as_millis() as u64);
+            } else if self.ui.started && !self.ui.show_settings {
+                self.run_fixed_steps(elapsed);
+            } else {
+                self.clock.reset();
             }
         }
-        self.ui.update_active_animation(now);
         self.last_tick = Some(now);
     }
+
+    /// Input repeats and buffered actions land on the step they belong to, so
+    /// the outcome does not depend on the display refresh rate.
+    fn run_fixed_steps(&mut self, elapsed: Duration) {
+        let steps = self.clock.advance(elapsed);
+        let step_ms = self.clock.step_ms();
+        self.input
+            .set_repeat_config(RepeatConfig::for_ruleset(self.ui.state.ruleset));
+        for _ in 0..steps {
+            self.input.apply_repeats_into(
+                step_ms,
+                self.ui.can_accept_game_input(),
+                &mut self.input_actions,
+            );
+            self.ui.set_held_buttons(self.input.held_buttons());
+            self.apply_buffered_actions();
+            self.ui.state.step(false);
+        }
+        if steps > 0 {
+            self.ui.record_mode_result();
+            self.ui.mark_game_dirty();
+        }
+    }
 }
 
 fn compute_scale(window: &Window) -> f32 {
@@ -1,21 +1,50 @@
 use crate::ui::input::InputAction;
 use crate::ui::style::SFX_VOLUME_STEP;
 use crate::ui::view::TetrisView;
-use gpui::{Context, KeyDownEvent, KeyUpEvent, Window};
+use gpui::{ClipboardItem, Context, KeyDownEvent, KeyUpEvent, Window};
+use gpui_tetris::game::input::GameAction;
+use gpui_tetris::game::state::GameMode;
 
 impl TetrisView {
     pub(super) fn on_key_down(
         &mut self,
         event: &KeyDownEvent,
         window: &mut Window,
-        _cx: &mut Context<Self>,
+        cx: &mut Context<Self>,
     ) {
+        if event.keystroke.key == "e"
+            && !event.keystroke.modifiers.modified()
+            && let Some(text) = self.ui.export_fumen()
+        {
+            cx.write_to_clipboard(ClipboardItem::new_string(text));
+        }
+
+        if self.ui.is_replay_active() {
+            self.on_replay_key_down(event.keystroke.key.as_str());
+            return;
+        }
+
         match event.keystroke.key.as_str() {
             "enter" | "return" => {
                 if !self.ui.started {
                     self.ui.start_game();
                 }
             }
+            "1" => {
+                self.ui.select_mode(GameMode::Marathon);
+            }
+            "2" => {
+                self.ui.select_mode(GameMode::sprint());
+            }
+            "3" => {
+                self.ui.select_mode(GameMode::ultra());
+            }
+            "4" => {
+                self.ui.select_mode(GameMode::dig());
+            }
+            "5" => {
+                self.ui.select_mode(GameMode::Master);
+            }
             "s" => {
                 self.ui.toggle_settings();
             }
@@ -31,6 +60,22 @@ impl TetrisView {
             "0" => {
                 self.ui.reset_settings();
             }
+            "i" => {
+                self.ui.toggle_initial_actions();
+            }
+            "g" => {
+                self.ui.cycle_preset();
+            }
+            "v" => {
+                self.ui.open_replay_viewer();
+            }
+            "f" if !event.keystroke.modifiers.modified() => {
+                if !self.ui.started
+                    && let Some(text) = cx.read_from_clipboard().and_then(|item| item.text())
+                {
+                    self.ui.start_from_fumen(&text);
+                }
+            }
             "escape" => {
                 self.ui.close_settings();
             }
@@ -71,6 +116,16 @@ impl TetrisView {
         }
     }
 
+    fn on_replay_key_down(&mut self, key: &str) {
+        match key {
+            "escape" | "v" => self.ui.close_replay_viewer(),
+            "left" => self.ui.seek_replay(false),
+            "right" => self.ui.seek_replay(true),
+            "down" => self.ui.scale_replay_speed(false),
+            _ => {}
+        }
+    }
+
     pub(super) fn on_key_up(
         &mut self,
         event: &KeyUpEvent,
@@ -90,6 +145,9 @@ impl TetrisView {
                 let actions = self.input.set_keyboard_down(false);
                 self.apply_input_actions(&actions);
             }
+            "up" => self.input.set_keyboard_button(GameAction::RotateCw, false),
+            "a" => self.input.set_keyboard_button(GameAction::Rotate180, false),
+            "c" => self.input.set_keyboard_button(GameAction::Hold, false),
             _ => {}
         }
     }
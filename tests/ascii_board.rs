@@ -0,0 +1,130 @@
+use gpui_tetris::assert_board_eq;
+use gpui_tetris::game::board::{AsciiError, BOARD_HEIGHT, Board, BoardSize, Cell};
+use gpui_tetris::game::input::GameAction;
+use gpui_tetris::game::pieces::{Rotation, Tetromino, TetrominoType};
+use gpui_tetris::game::state::{GameConfig, GameState};
+
+#[test]
+fn rows_fill_the_bottom_of_the_board() {
+    let board = Board::from_ascii(
+        "
+        ....T.....
+        GGGG.GGGGG
+        ",
+    )
+    .expect("parses");
+
+    assert_eq!(board.size(), BoardSize::default());
+    assert_eq!(
+        board.cell(4, BOARD_HEIGHT - 2),
+        Cell::piece(TetrominoType::T)
+    );
+    assert_eq!(board.cell(0, BOARD_HEIGHT - 1), Cell::GARBAGE);
+    assert!(!board.cell(4, BOARD_HEIGHT - 1).filled);
+    assert_eq!(board.column_heights()[0], 1);
+}
+
+#[test]
+fn to_ascii_round_trips_from_the_highest_row() {
+    let rows = "I.........\nI....OO...\nISZJLOOTGG";
+    let board = Board::from_ascii(&format!("..........\n{rows}")).expect("parses");
+    assert_eq!(board.to_ascii(), rows);
+    assert_eq!(Board::new().to_ascii(), "");
+
+    let wide = Board::from_ascii("............\nGGGGGGGGGGG.").expect("parses");
+    assert_eq!(wide.width(), 12);
+    assert_eq!(wide.to_ascii(), "GGGGGGGGGGG.");
+}
+
+#[test]
+fn bad_rows_are_reported() {
+    assert_eq!(
+        Board::from_ascii("..........\n....."),
+        Err(AsciiError::RaggedRow { row: 1, width: 10 })
+    );
+    assert_eq!(
+        Board::from_ascii(".....x...."),
+        Err(AsciiError::InvalidCell {
+            row: 0,
+            column: 5,
+            found: 'x'
+        })
+    );
+    assert_eq!(Board::from_ascii("..."), Err(AsciiError::InvalidWidth(3)));
+    let tall = "....\n".repeat(5);
+    assert_eq!(
+        Board::from_ascii_sized(
+            BoardSize {
+                width: 4,
+                height: 4,
+                visible_height: 4
+            },
+            &tall
+        ),
+        Err(AsciiError::TooManyRows { rows: 5, height: 4 })
+    );
+}
+
+#[test]
+fn builder_sets_up_the_position() {
+    let mut piece = Tetromino::new(TetrominoType::I, 6, 10);
+    piece.rotation = Rotation::East;
+    let mut state = GameState::builder()
+        .seed(7)
+        .board(
+            "
+            .........G
+            GGGGGGGG.G
+            ",
+        )
+        .active(piece)
+        .hold(TetrominoType::T)
+        .queue([TetrominoType::O, TetrominoType::S])
+        .build()
+        .expect("builds");
+
+    assert_eq!(state.hold, Some(TetrominoType::T));
+    assert_eq!(state.next_queue[..2], [TetrominoType::O, TetrominoType::S]);
+    assert_eq!(state.preview().len(), GameConfig::default().preview_count);
+
+    state.apply_action(GameAction::HardDrop);
+
+    assert_board_eq!(
+        state.board,
+        "
+        ........I.
+        ........I.
+        ........IG
+        "
+    );
+    assert_eq!(state.lines, 1);
+    while state.is_spawn_delay_active() {
+        state.tick(16, false);
+    }
+    assert_eq!(state.active.kind, TetrominoType::O);
+}
+
+#[test]
+fn builder_rejects_rows_that_do_not_fit_the_config() {
+    let result = GameState::builder().board("GGGG.GGGGGG").build();
+    assert!(matches!(result, Err(AsciiError::RaggedRow { .. })));
+}
+
+#[test]
+#[should_panic(expected = "boards differ")]
+fn assert_board_eq_panics_on_a_mismatch() {
+    let board = Board::from_ascii("GGGG.GGGGG").expect("parses");
+    assert_board_eq!(board, "GGGGG.GGGG");
+}
+
+#[test]
+fn diff_lines_up_the_bottom_rows() {
+    let board = Board::from_ascii("....T.....\nGGGG.GGGGG").expect("parses");
+    let diff = gpui_tetris::game::board::ascii_diff(&board, "GGGGG.GGGG").expect("differs");
+    assert_eq!(
+        diff,
+        "  expected     actual\n\
+         >              ....T.....\n\
+         > GGGGG.GGGG   GGGG.GGGGG\n"
+    );
+}
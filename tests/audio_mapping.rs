@@ -1,3 +1,5 @@
+#![cfg(feature = "audio")]
+
 use gpui_tetris::audio::sound_event_to_asset;
 use gpui_tetris::game::state::SoundEvent;
 
@@ -30,6 +32,14 @@ fn maps_sound_events_to_asset_keys() {
         sound_event_to_asset(&SoundEvent::LineClear(4)),
         Some("line_clear_4")
     );
+    assert_eq!(
+        sound_event_to_asset(&SoundEvent::PerfectClear),
+        Some("line_clear_4")
+    );
+    assert_eq!(
+        sound_event_to_asset(&SoundEvent::CountdownTick),
+        Some("move")
+    );
     assert_eq!(
         sound_event_to_asset(&SoundEvent::GameOver),
         Some("game_over")
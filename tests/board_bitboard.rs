@@ -0,0 +1,84 @@
+use gpui_tetris::game::board::{
+    BOARD_HEIGHT, BOARD_WIDTH, Board, BoardSize, Cell, MAX_BOARD_WIDTH,
+};
+use gpui_tetris::game::pieces::{Rotation, Tetromino, TetrominoType};
+
+#[test]
+fn set_cell_keeps_the_row_mask_in_step() {
+    let mut board = Board::new();
+    let y = BOARD_HEIGHT - 1;
+
+    board.set_cell(0, y, Cell::piece(TetrominoType::S));
+    board.set_cell(9, y, Cell::GARBAGE);
+    assert_eq!(board.row_mask(y), 0b10_0000_0001);
+    assert_eq!(board.cell(0, y).kind, Some(TetrominoType::S));
+    assert!(board.is_occupied(9, y as i32));
+
+    board.set_cell(0, y, Cell::default());
+    assert_eq!(board.row_mask(y), 0b10_0000_0000);
+    assert!(!board.is_occupied(0, y as i32));
+}
+
+#[test]
+fn column_heights_count_from_the_floor() {
+    let mut board = Board::new();
+    board.set_cell(0, BOARD_HEIGHT - 1, Cell::GARBAGE);
+    board.set_cell(3, BOARD_HEIGHT - 4, Cell::GARBAGE);
+    board.set_cell(3, BOARD_HEIGHT - 2, Cell::GARBAGE);
+    board.set_cell(9, 0, Cell::GARBAGE);
+
+    let heights = board.column_heights();
+    assert_eq!(heights, vec![1, 0, 0, 4, 0, 0, 0, 0, 0, BOARD_HEIGHT]);
+    for (x, &height) in heights.iter().enumerate() {
+        assert_eq!(board.column_height(x), height);
+    }
+}
+
+#[test]
+fn widest_boards_use_every_bit() {
+    let mut board = Board::with_size(BoardSize::with_buffer(MAX_BOARD_WIDTH, 20));
+    assert_eq!(board.full_row_mask(), u32::MAX);
+    let y = board.height() - 1;
+    for x in 0..MAX_BOARD_WIDTH {
+        board.set_cell(x, y, Cell::GARBAGE);
+    }
+
+    let mut piece = Tetromino::new(TetrominoType::I, MAX_BOARD_WIDTH as i32 - 4, 0);
+    piece.rotation = Rotation::North;
+    assert!(board.can_place(&piece, piece.x, piece.y, piece.rotation));
+    assert!(!board.can_place(&piece, piece.x + 1, piece.y, piece.rotation));
+
+    assert_eq!(board.clear_lines().len(), 1);
+    assert!(board.is_empty());
+}
+
+#[test]
+fn garbage_rows_leave_the_hole_open() {
+    let mut board = Board::new();
+    assert!(board.insert_garbage(1, 4));
+    assert_eq!(
+        board.row_mask(BOARD_HEIGHT - 1),
+        board.full_row_mask() & !(1 << 4)
+    );
+    assert_eq!(board.column_height(4), 0);
+    assert_eq!(board.column_height(BOARD_WIDTH - 1), 1);
+}
+
+#[test]
+fn direct_cell_writes_reach_the_row_masks() {
+    let mut board = Board::new();
+    let y = BOARD_HEIGHT - 1;
+    for x in 0..BOARD_WIDTH {
+        board.cells[y][x] = Cell::GARBAGE;
+    }
+    board.cells[y - 1][4].filled = true;
+
+    assert_eq!(board.row_mask(y), board.full_row_mask());
+    assert!(board.is_occupied(4, y as i32 - 1));
+    assert_eq!(board.column_height(4), 2);
+
+    let cleared = board.clear_lines();
+    assert_eq!(cleared.len(), 1);
+    assert_eq!(board.row_mask(y), 1 << 4);
+    assert!(board.cells[y][4].filled);
+}
@@ -0,0 +1,104 @@
+use gpui_tetris::game::board::{
+    BOARD_HEIGHT, BOARD_WIDTH, Board, BoardSize, MAX_BOARD_WIDTH, VISIBLE_HEIGHT,
+};
+use gpui_tetris::game::input::GameAction;
+use gpui_tetris::game::pieces::{Tetromino, TetrominoType};
+use gpui_tetris::game::state::{GameConfig, GameState};
+
+fn state_with(board: BoardSize) -> GameState {
+    GameState::new(
+        1,
+        GameConfig {
+            board,
+            ..GameConfig::default()
+        },
+    )
+}
+
+#[test]
+fn default_size_is_ten_by_twenty_with_a_vanish_zone() {
+    let size = BoardSize::default();
+    assert_eq!(size.width, BOARD_WIDTH);
+    assert_eq!(size.height, BOARD_HEIGHT);
+    assert_eq!(size.visible_height, VISIBLE_HEIGHT);
+    assert_eq!(Board::new().size(), size);
+}
+
+#[test]
+fn spawn_is_centred_above_the_visible_field() {
+    assert_eq!(BoardSize::with_buffer(4, 20).spawn_position(), (0, 18));
+    assert_eq!(BoardSize::with_buffer(12, 24).spawn_position(), (4, 18));
+    // Without a vanish zone pieces spawn at the very top.
+    let flat = BoardSize {
+        width: 10,
+        height: 20,
+        visible_height: 20,
+    };
+    assert_eq!(flat.spawn_position(), (3, 0));
+}
+
+#[test]
+fn state_follows_the_configured_size() {
+    let size = BoardSize::with_buffer(12, 24);
+    let state = state_with(size);
+
+    assert_eq!(state.board.size(), size);
+    assert_eq!(state.board.cells.len(), 44);
+    assert!(state.board.cells.iter().all(|row| row.len() == 12));
+    assert_eq!((state.active.x, state.active.y), (4, 18));
+    let ghost_bottom = state.ghost_blocks().iter().map(|&(_, y)| y).max();
+    assert_eq!(ghost_bottom, Some(43));
+}
+
+#[test]
+fn four_wide_board_clears_with_a_flat_i() {
+    let mut state = state_with(BoardSize::with_buffer(4, 20));
+    state.active = Tetromino::new(TetrominoType::I, 0, 30);
+    state.apply_action(GameAction::HardDrop);
+
+    assert_eq!(state.lines, 1);
+    assert!(state.board.is_empty());
+}
+
+#[test]
+fn pieces_stop_at_a_narrow_wall() {
+    let mut state = state_with(BoardSize::with_buffer(6, 20));
+    state.active = Tetromino::new(TetrominoType::O, 1, 10);
+    for _ in 0..10 {
+        state.apply_action(GameAction::MoveRight);
+    }
+    // The O occupies box columns 1-2, so it stops with its right edge on x = 5.
+    assert_eq!(state.active.x, 3);
+}
+
+#[test]
+fn out_of_range_sizes_are_clamped() {
+    let state = state_with(BoardSize {
+        width: 100,
+        height: 2,
+        visible_height: 9,
+    });
+    let size = state.board.size();
+    assert!(size.is_valid());
+    assert_eq!(size.width, MAX_BOARD_WIDTH);
+    assert_eq!(size.visible_height, size.height);
+}
+
+#[test]
+fn snapshot_keeps_board_size() {
+    let mut state = state_with(BoardSize::with_buffer(8, 16));
+    state.apply_action(GameAction::HardDrop);
+
+    let restored = GameState::from_snapshot(&state.to_snapshot()).expect("decodes");
+    assert_eq!(restored.board.size(), state.board.size());
+    assert_eq!(
+        restored
+            .board
+            .cells
+            .iter()
+            .flatten()
+            .filter(|c| c.filled)
+            .count(),
+        4
+    );
+}
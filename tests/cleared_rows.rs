@@ -0,0 +1,107 @@
+use gpui_tetris::game::board::{BOARD_HEIGHT, BOARD_WIDTH, Board};
+use gpui_tetris::game::input::GameAction;
+use gpui_tetris::game::pieces::{Rotation, Tetromino, TetrominoType};
+use gpui_tetris::game::state::{GameConfig, GameState, LevelTimings, TimingCurve};
+
+const HOLE: usize = 2;
+
+/// Fills the bottom four rows except column `HOLE`; the second row from
+/// the bottom also keeps column 7 open so it survives the clear.
+fn stack(board: &mut Board) {
+    for y in BOARD_HEIGHT - 4..BOARD_HEIGHT {
+        for x in (0..BOARD_WIDTH).filter(|&x| x != HOLE) {
+            board.cells[y][x].filled = true;
+            board.cells[y][x].kind = Some(TetrominoType::O);
+        }
+    }
+    board.cells[BOARD_HEIGHT - 2][7].filled = false;
+    board.cells[BOARD_HEIGHT - 2][7].kind = None;
+}
+
+fn state_with_pause(line_clear_ms: u64) -> GameState {
+    let mut state = GameState::new(
+        1,
+        GameConfig {
+            timings: TimingCurve::fixed(LevelTimings {
+                line_clear_ms,
+                ..LevelTimings::default()
+            }),
+            ..GameConfig::default()
+        },
+    );
+    state.board = Board::new();
+    stack(&mut state.board);
+    let mut piece = Tetromino::new(TetrominoType::I, HOLE as i32 - 2, 10);
+    piece.rotation = Rotation::East;
+    state.active = piece;
+    state
+}
+
+#[test]
+fn clear_lines_reports_rows_top_to_bottom() {
+    let mut board = Board::new();
+    stack(&mut board);
+    for y in BOARD_HEIGHT - 4..BOARD_HEIGHT {
+        board.cells[y][HOLE].filled = true;
+    }
+    let before = board.cells.clone();
+
+    let cleared = board.clear_lines();
+
+    let rows: Vec<usize> = cleared.iter().map(|row| row.y).collect();
+    assert_eq!(
+        rows,
+        vec![BOARD_HEIGHT - 4, BOARD_HEIGHT - 3, BOARD_HEIGHT - 1]
+    );
+    for row in &cleared {
+        assert_eq!(row.cells, before[row.y]);
+    }
+    assert_eq!(board.cells[BOARD_HEIGHT - 1], before[BOARD_HEIGHT - 2]);
+
+    board.restore_lines(&cleared);
+    assert_eq!(board.cells, before);
+}
+
+#[test]
+fn cleared_rows_last_for_the_line_clear_pause() {
+    let mut state = state_with_pause(200);
+
+    state.apply_action(GameAction::HardDrop);
+
+    let rows: Vec<usize> = state.cleared_rows().iter().map(|row| row.y).collect();
+    assert_eq!(
+        rows,
+        vec![BOARD_HEIGHT - 4, BOARD_HEIGHT - 3, BOARD_HEIGHT - 1]
+    );
+    assert!(state.cleared_rows()[0].cells.iter().all(|cell| cell.filled));
+    assert_eq!(state.line_clear_progress(), 0.0);
+
+    state.tick(50, false);
+    assert_eq!(state.line_clear_progress(), 0.25);
+
+    state.tick(150, false);
+    assert!(state.cleared_rows().is_empty());
+    assert_eq!(state.line_clear_progress(), 1.0);
+}
+
+#[test]
+fn no_pause_means_no_cleared_rows() {
+    let mut state = state_with_pause(0);
+
+    state.apply_action(GameAction::HardDrop);
+
+    assert_eq!(state.lines, 3);
+    assert!(state.cleared_rows().is_empty());
+}
+
+#[test]
+fn snapshots_keep_the_rows_mid_clear() {
+    let mut state = state_with_pause(200);
+    state.apply_action(GameAction::HardDrop);
+    state.tick(100, false);
+
+    let restored = GameState::from_snapshot(&state.to_snapshot()).expect("decodes");
+
+    assert_eq!(restored.cleared_rows(), state.cleared_rows());
+    assert_eq!(restored.line_clear_progress(), 0.5);
+}
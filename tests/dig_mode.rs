@@ -0,0 +1,90 @@
+use gpui_tetris::game::board::{BOARD_HEIGHT, BOARD_WIDTH};
+use gpui_tetris::game::input::GameAction;
+use gpui_tetris::game::pieces::{Rotation, Tetromino, TetrominoType};
+use gpui_tetris::game::state::{GameConfig, GameMode, GameState};
+
+fn dig_state(seed: u64, rows: u32, messiness: u8) -> GameState {
+    GameState::new(
+        seed,
+        GameConfig {
+            mode: GameMode::Dig { rows, messiness },
+            ..GameConfig::default()
+        },
+    )
+}
+
+fn holes(state: &GameState) -> Vec<usize> {
+    state
+        .board
+        .cells
+        .iter()
+        .filter(|row| row.iter().any(|cell| cell.garbage))
+        .map(|row| row.iter().position(|cell| !cell.filled).expect("hole"))
+        .collect()
+}
+
+#[test]
+fn dig_starts_with_garbage_rows() {
+    let state = dig_state(3, 8, 100);
+    assert_eq!(state.garbage_remaining(), Some(8));
+    assert!(
+        state.board.cells[BOARD_HEIGHT - 8..].iter().all(|row| row
+            .iter()
+            .filter(|cell| cell.garbage)
+            .count()
+            == BOARD_WIDTH - 1)
+    );
+}
+
+#[test]
+fn dig_board_is_reproducible_from_the_seed() {
+    assert_eq!(holes(&dig_state(9, 10, 50)), holes(&dig_state(9, 10, 50)));
+    assert_ne!(
+        holes(&dig_state(9, 10, 100)),
+        holes(&dig_state(10, 10, 100))
+    );
+}
+
+#[test]
+fn messiness_controls_hole_changes() {
+    let clean = holes(&dig_state(5, 10, 0));
+    assert!(clean.windows(2).all(|pair| pair[0] == pair[1]));
+
+    let messy = holes(&dig_state(5, 10, 100));
+    assert!(messy.windows(2).all(|pair| pair[0] != pair[1]));
+}
+
+#[test]
+fn digging_out_the_last_row_completes_the_mode() {
+    let mut state = dig_state(1, 1, 0);
+    let hole = holes(&state)[0] as i32;
+    state.active = Tetromino::new(TetrominoType::I, hole - 2, BOARD_HEIGHT as i32 - 4);
+    state.active.rotation = Rotation::East;
+    state.apply_action(GameAction::HardDrop);
+
+    assert_eq!(state.garbage_remaining(), Some(0));
+    assert!(state.game_over);
+    assert!(state.mode_complete);
+    assert_eq!(state.top_out, None);
+}
+
+#[test]
+fn other_modes_report_no_garbage() {
+    let state = GameState::new(1, GameConfig::default());
+    assert_eq!(state.garbage_remaining(), None);
+    assert_eq!(state.board.garbage_rows(), 0);
+}
+
+#[test]
+fn snapshot_keeps_dig_mode() {
+    let state = dig_state(4, 6, 30);
+    let restored = GameState::from_snapshot(&state.to_snapshot()).expect("decodes");
+    assert_eq!(
+        restored.mode,
+        GameMode::Dig {
+            rows: 6,
+            messiness: 30
+        }
+    );
+    assert_eq!(holes(&restored), holes(&state));
+}
@@ -0,0 +1,174 @@
+use gpui_tetris::game::board::{BOARD_HEIGHT, BOARD_WIDTH, Board};
+use gpui_tetris::game::env::{Env, EnvAction, EnvError};
+use gpui_tetris::game::input::GameAction;
+use gpui_tetris::game::pieces::{Tetromino, TetrominoType};
+use gpui_tetris::game::state::{GameConfig, GameState, LevelTimings, TimingCurve};
+
+fn timings(are_ms: u64, line_are_ms: u64, line_clear_ms: u64) -> LevelTimings {
+    LevelTimings {
+        are_ms,
+        line_are_ms,
+        line_clear_ms,
+        ..LevelTimings::default()
+    }
+}
+
+fn state_with(curve: TimingCurve) -> GameState {
+    let mut state = GameState::new(
+        1,
+        GameConfig {
+            timings: curve,
+            ..GameConfig::default()
+        },
+    );
+    state.board = Board::new();
+    state
+}
+
+fn clear_bottom_row(state: &mut GameState) {
+    for x in 4..BOARD_WIDTH {
+        state.board.cells[BOARD_HEIGHT - 1][x].filled = true;
+        state.board.cells[BOARD_HEIGHT - 1][x].kind = Some(TetrominoType::O);
+    }
+    state.active = Tetromino::new(TetrominoType::I, 0, BOARD_HEIGHT as i32 - 2);
+    state.apply_action(GameAction::HardDrop);
+}
+
+#[test]
+fn default_timings_spawn_right_after_a_lock() {
+    let mut state = state_with(TimingCurve::default());
+    state.apply_action(GameAction::HardDrop);
+
+    assert!(!state.is_spawn_delay_active());
+    assert_eq!(state.landing_flash_timer_ms, 120);
+}
+
+#[test]
+fn are_holds_back_the_next_piece_and_ignores_input() {
+    let mut state = state_with(TimingCurve::fixed(timings(100, 0, 0)));
+    state.apply_action(GameAction::HardDrop);
+    let locked = state.active;
+    assert!(state.is_spawn_delay_active());
+
+    state.apply_action(GameAction::MoveLeft);
+    state.apply_action(GameAction::Hold);
+    state.tick(99, false);
+    assert_eq!(state.active, locked);
+    assert_eq!(state.hold, None);
+
+    state.tick(1, false);
+    assert!(!state.is_spawn_delay_active());
+    assert_ne!(state.active, locked);
+}
+
+#[test]
+fn line_are_starts_after_the_clear() {
+    let mut state = state_with(TimingCurve::fixed(timings(10, 50, 200)));
+    clear_bottom_row(&mut state);
+    assert_eq!(state.line_clear_timer_ms, 200);
+    assert_eq!(state.spawn_delay_ms, 50);
+
+    state.tick(200, false);
+    assert!(state.is_spawn_delay_active());
+    state.tick(49, false);
+    assert!(state.is_spawn_delay_active());
+    state.tick(1, false);
+    assert!(!state.is_spawn_delay_active());
+}
+
+#[test]
+fn line_clears_spawn_once_the_animation_ends() {
+    let mut state = state_with(TimingCurve::default());
+    clear_bottom_row(&mut state);
+    assert!(state.is_spawn_delay_active());
+
+    state.tick(180, false);
+    assert!(!state.is_spawn_delay_active());
+    assert!(!state.is_line_clear_active());
+}
+
+#[test]
+fn timings_follow_the_level() {
+    let curve = TimingCurve::fixed(timings(100, 100, 300))
+        .from_level(5, timings(50, 40, 200))
+        .from_level(3, timings(0, 0, 0))
+        .from_level(10, timings(20, 10, 100));
+    assert_eq!(curve.steps().len(), 3);
+    assert_eq!(curve.at(4).are_ms, 100);
+    assert_eq!(curve.at(5).line_clear_ms, 200);
+    assert_eq!(curve.at(25).line_are_ms, 10);
+
+    let mut state = state_with(curve);
+    state.level = 7;
+    state.apply_action(GameAction::HardDrop);
+    assert_eq!(state.spawn_delay_ms, 50);
+}
+
+#[test]
+fn effect_durations_come_from_the_config() {
+    let mut state = state_with(TimingCurve::fixed(LevelTimings {
+        landing_flash_ms: 40,
+        perfect_clear_ms: 500,
+        ..LevelTimings::default()
+    }));
+    clear_bottom_row(&mut state);
+
+    assert_eq!(state.landing_flash_timer_ms, 40);
+    assert_eq!(state.perfect_clear_timer_ms, 500);
+}
+
+#[test]
+fn snapshot_keeps_timings_and_a_pending_spawn() {
+    let curve = TimingCurve::fixed(timings(100, 0, 0)).from_level(2, timings(60, 30, 90));
+    let mut state = state_with(curve);
+    state.apply_action(GameAction::HardDrop);
+    state.tick(30, false);
+
+    let mut restored = GameState::from_snapshot(&state.to_snapshot()).expect("decodes");
+    assert_eq!(restored.timings, curve);
+    assert!(restored.is_spawn_delay_active());
+    assert_eq!(restored.spawn_delay_ms, 70);
+
+    restored.tick(70, false);
+    assert!(!restored.is_spawn_delay_active());
+}
+
+#[test]
+fn env_placements_wait_out_the_spawn_phase() {
+    let mut env = Env::new(GameConfig {
+        timings: TimingCurve::fixed(timings(100, 100, 200)),
+        ..GameConfig::default()
+    });
+    env.reset(3);
+    for _ in 0..3 {
+        let placement = env.placements()[0];
+        env.step(EnvAction::Place(placement)).expect("placement");
+        assert!(!env.state().is_spawn_delay_active());
+    }
+}
+
+#[test]
+fn env_rejects_placements_while_paused_or_waiting_to_spawn() {
+    let mut env = Env::new(GameConfig {
+        timings: TimingCurve::fixed(timings(500, 500, 0)),
+        ..GameConfig::default()
+    });
+    env.reset(3);
+    let placement = env.placements()[0];
+
+    env.step(EnvAction::Input(GameAction::HardDrop))
+        .expect("hard drop");
+    assert!(env.state().is_spawn_delay_active());
+    let before = env.observe();
+    assert_eq!(
+        env.step(EnvAction::Place(placement)),
+        Err(EnvError::SpawnPending)
+    );
+    assert_eq!(env.observe(), before);
+
+    env.step(EnvAction::Input(GameAction::Pause))
+        .expect("pause");
+    assert!(env.state().paused);
+    assert_eq!(env.step(EnvAction::Place(placement)), Err(EnvError::Paused));
+    assert_eq!(env.observe(), before);
+}
@@ -0,0 +1,82 @@
+use gpui_tetris::game::clock::FixedTimestep;
+use gpui_tetris::game::input::GameAction;
+use gpui_tetris::game::state::{GameConfig, GameState};
+use std::time::Duration;
+
+fn run_at_refresh_rate(frame_us: u64) -> GameState {
+    let mut state = GameState::new(21, GameConfig::default());
+    let mut clock = FixedTimestep::new(state.step_ms());
+    let inputs = [
+        (40, GameAction::MoveLeft),
+        (90, GameAction::RotateCw),
+        (150, GameAction::HardDrop),
+        (200, GameAction::MoveRight),
+        (260, GameAction::Hold),
+    ];
+
+    let mut steps = 0;
+    while steps < 600 {
+        let due = clock.advance(Duration::from_micros(frame_us));
+        for _ in 0..due.min(600 - steps) {
+            for (frame, action) in inputs.iter() {
+                if *frame == steps {
+                    state.apply_action(*action);
+                }
+            }
+            state.step(false);
+            steps += 1;
+        }
+    }
+    state
+}
+
+#[test]
+fn clock_carries_partial_steps_between_frames() {
+    let mut clock = FixedTimestep::new(16);
+
+    assert_eq!(clock.advance(Duration::from_millis(10)), 0);
+    assert_eq!(clock.advance(Duration::from_millis(10)), 1);
+    assert_eq!(clock.advance(Duration::from_millis(12)), 1);
+    assert_eq!(clock.advance(Duration::from_micros(16_667 * 3)), 3);
+}
+
+#[test]
+fn clock_caps_catch_up_after_a_stall() {
+    let mut clock = FixedTimestep::new(16);
+
+    let steps = clock.advance(Duration::from_secs(5));
+
+    assert_eq!(
+        steps as u64,
+        FixedTimestep::MAX_CATCH_UP.as_millis() as u64 / 16
+    );
+}
+
+#[test]
+fn same_inputs_give_identical_state_at_any_refresh_rate() {
+    let at_60hz = run_at_refresh_rate(16_667);
+    let at_144hz = run_at_refresh_rate(6_944);
+    let at_30hz = run_at_refresh_rate(33_333);
+
+    for other in [&at_144hz, &at_30hz] {
+        assert_eq!(other.frame, at_60hz.frame);
+        assert_eq!(other.sim_time_ms, at_60hz.sim_time_ms);
+        assert_eq!(other.active, at_60hz.active);
+        assert_eq!(other.hold, at_60hz.hold);
+        assert_eq!(other.score, at_60hz.score);
+        assert_eq!(other.next_queue, at_60hz.next_queue);
+    }
+}
+
+#[test]
+fn paused_steps_do_not_advance_the_frame() {
+    let mut state = GameState::new(2, GameConfig::default());
+    state.step(false);
+    state.apply_action(GameAction::Pause);
+
+    state.step(false);
+    state.step(false);
+
+    assert_eq!(state.frame, 1);
+    assert_eq!(state.sim_time_ms, state.step_ms());
+}
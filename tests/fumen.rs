@@ -0,0 +1,225 @@
+use gpui_tetris::game::board::{BOARD_HEIGHT, Board, BoardSize};
+use gpui_tetris::game::codec::DecodeError;
+use gpui_tetris::game::fumen::{self, EncodeError, FumenPage};
+use gpui_tetris::game::pieces::{Rotation, Tetromino, TetrominoType};
+use gpui_tetris::game::rotation::RotationSystem;
+use gpui_tetris::game::state::GameState;
+
+fn board(rows: &str) -> Board {
+    Board::from_ascii(rows).expect("parses")
+}
+
+fn piece(kind: TetrominoType, rotation: Rotation, x: i32, y: i32) -> Tetromino {
+    let mut piece = Tetromino::new(kind, x, y);
+    piece.rotation = rotation;
+    piece
+}
+
+/// A piece whose leftmost block is in column `left` and lowest block on row `bottom`.
+fn placed(kind: TetrominoType, rotation: Rotation, left: i32, bottom: i32) -> Tetromino {
+    let mut piece = piece(kind, rotation, 0, 0);
+    let blocks = piece.blocks(rotation);
+    piece.x = left - blocks.iter().map(|&(dx, _)| dx).min().unwrap();
+    piece.y = bottom - blocks.iter().map(|&(_, dy)| dy).max().unwrap();
+    piece
+}
+
+fn cells(piece: &Tetromino) -> Vec<(i32, i32)> {
+    let mut cells: Vec<_> = piece
+        .blocks(piece.rotation)
+        .iter()
+        .map(|&(dx, dy)| (piece.x + dx, piece.y + dy))
+        .collect();
+    cells.sort_unstable();
+    cells
+}
+
+#[test]
+fn empty_page_matches_the_editor() {
+    assert_eq!(
+        fumen::encode(&[FumenPage::default()]).as_deref(),
+        Ok("v115@vhAAgH")
+    );
+    assert_eq!(fumen::decode("v115@vhAAgH"), Ok(vec![FumenPage::default()]));
+}
+
+#[test]
+fn pieces_decode_into_board_coordinates() {
+    let pages = fumen::decode("v115@vhAVQJ").expect("decodes");
+    let piece = pages[0].piece.expect("has a piece");
+    let floor = BOARD_HEIGHT as i32 - 1;
+
+    assert_eq!(piece.kind, TetrominoType::T);
+    assert_eq!(piece.rotation, Rotation::North);
+    assert_eq!(
+        cells(&piece),
+        [(3, floor), (4, floor - 1), (4, floor), (5, floor)]
+    );
+    assert_eq!(fumen::encode(&pages).as_deref(), Ok("v115@vhAVQJ"));
+}
+
+#[test]
+fn every_piece_and_rotation_round_trips() {
+    let field = board(
+        "
+        L.........
+        LZZ...OO..
+        LLZZ..OOTG
+        ",
+    );
+    let mut pages = Vec::new();
+    for kind in TetrominoType::ALL {
+        for rotation in (0..4).filter_map(Rotation::from_index) {
+            pages.push(FumenPage {
+                board: field.clone(),
+                piece: Some(piece(kind, rotation, 4, BOARD_HEIGHT as i32 - 8)),
+                comment: format!("{kind:?} {rotation:?}"),
+            });
+        }
+    }
+    // Each page locks its piece, so later fields are full diffs.
+    let encoded = fumen::encode(&pages).expect("encodes");
+    assert_eq!(fumen::decode(&encoded), Ok(pages));
+}
+
+#[test]
+fn locked_pieces_carry_into_the_next_page() {
+    let floor = BOARD_HEIGHT as i32 - 1;
+    let first = FumenPage {
+        board: board("IIIIII...."),
+        piece: Some(placed(TetrominoType::O, Rotation::North, 6, floor)),
+        comment: String::new(),
+    };
+    let second = FumenPage {
+        board: board(
+            "
+            ......OO..
+            IIIIIIOO..
+            ",
+        ),
+        piece: Some(placed(TetrominoType::O, Rotation::North, 8, floor)),
+        comment: String::new(),
+    };
+    let cleared = FumenPage {
+        board: board("..........\n..........\n......OOOO"),
+        piece: None,
+        comment: String::new(),
+    };
+    let first_only = fumen::encode(std::slice::from_ref(&first)).expect("encodes");
+    let pages = vec![first, second, cleared];
+
+    let encoded = fumen::encode(&pages).expect("encodes");
+    assert_eq!(fumen::decode(&encoded), Ok(pages));
+    // The later fields repeat what the page before left behind: one
+    // repeated-field marker for both, then their actions.
+    assert_eq!(encoded.len(), first_only.len() + 3 + 3 + 3);
+}
+
+#[test]
+fn comments_are_inherited_and_escaped() {
+    let pages = vec![
+        FumenPage {
+            comment: "PC 100% — 日本".to_string(),
+            ..FumenPage::default()
+        },
+        FumenPage::default(),
+    ];
+    let encoded = fumen::encode(&pages).expect("encodes");
+    let decoded = fumen::decode(&encoded).expect("decodes");
+    assert_eq!(decoded[0].comment, "PC 100% — 日本");
+    assert_eq!(decoded[1].comment, "");
+
+    let repeated = vec![pages[0].clone(), pages[0].clone()];
+    let encoded = fumen::encode(&repeated).expect("encodes");
+    assert_eq!(fumen::decode(&encoded), Ok(repeated));
+}
+
+#[test]
+fn long_fumens_wrap_and_decode_from_a_url() {
+    let page = FumenPage {
+        board: board("GGGGGGGGG.\nGGGGGGGG..\nGGGGGGG..."),
+        piece: None,
+        comment: "a comment long enough to push the data past a line".to_string(),
+    };
+    let encoded = fumen::encode(std::slice::from_ref(&page)).expect("encodes");
+    assert_eq!(encoded.find('?'), Some(5 + 42));
+    let url = format!("https://fumen.zui.jp/?{encoded}");
+    assert_eq!(fumen::decode(&url), Ok(vec![page]));
+}
+
+#[test]
+fn pieces_from_other_systems_keep_their_blocks() {
+    let piece = piece(TetrominoType::S, Rotation::East, 3, BOARD_HEIGHT as i32 - 4)
+        .with_system(RotationSystem::Ars);
+    let page = FumenPage {
+        piece: Some(piece),
+        ..FumenPage::default()
+    };
+    let encoded = fumen::encode(&[page]).expect("encodes");
+    let decoded = fumen::decode(&encoded).expect("decodes")[0]
+        .piece
+        .expect("has a piece");
+    assert_eq!(decoded.system, RotationSystem::Srs);
+    assert_eq!(cells(&decoded), cells(&piece));
+}
+
+#[test]
+fn bad_fumens_are_rejected() {
+    assert_eq!(fumen::decode("hello"), Err(DecodeError::BadMagic));
+    assert_eq!(
+        fumen::decode("v110@vhAAgH"),
+        Err(DecodeError::UnsupportedVersion(110))
+    );
+    assert_eq!(fumen::decode("v115@vhA"), Err(DecodeError::UnexpectedEof));
+    assert_eq!(fumen::decode("v115@"), Err(DecodeError::UnexpectedEof));
+    assert!(matches!(
+        fumen::decode("v115@vh!AAgH"),
+        Err(DecodeError::InvalidValue(_))
+    ));
+}
+
+#[test]
+fn boards_a_fumen_cannot_hold_are_rejected() {
+    let wide = Board::with_size(BoardSize::with_buffer(12, 20));
+    let page = FumenPage {
+        board: wide,
+        ..FumenPage::default()
+    };
+    assert_eq!(fumen::encode(&[page]), Err(EncodeError::Width(12)));
+
+    let mut tall = "G.........\n".repeat(24);
+    tall.push_str("GGGGGGGGG.");
+    let page = FumenPage {
+        board: board(&tall),
+        ..FumenPage::default()
+    };
+    assert_eq!(fumen::encode(&[page]), Err(EncodeError::OutOfField));
+}
+
+#[test]
+fn games_start_from_and_export_to_fumen() {
+    let field = board("GGGG..GGGG\nGGGG.GGGGG");
+    let encoded = fumen::encode(&[FumenPage {
+        board: field.clone(),
+        ..FumenPage::default()
+    }])
+    .expect("encodes");
+
+    let page = &fumen::decode(&encoded).expect("decodes")[0];
+    let state = GameState::builder()
+        .seed(3)
+        .board(&page.board.to_ascii())
+        .build()
+        .expect("builds");
+    assert_eq!(state.board, field);
+
+    let exported = FumenPage::from_state(&state);
+    assert_eq!(exported.piece, Some(state.active));
+    let round_trip =
+        &fumen::decode(&fumen::encode(&[exported]).expect("encodes")).expect("decodes")[0];
+    assert_eq!(round_trip.board, field);
+    assert_eq!(
+        round_trip.piece.map(|piece| cells(&piece)),
+        Some(cells(&state.active))
+    );
+}
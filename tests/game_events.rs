@@ -0,0 +1,175 @@
+use gpui_tetris::game::board::{BOARD_HEIGHT, BOARD_WIDTH, Board};
+use gpui_tetris::game::input::GameAction;
+use gpui_tetris::game::pieces::{Tetromino, TetrominoType};
+use gpui_tetris::game::replay::ReplayPlayer;
+use gpui_tetris::game::state::{
+    GameConfig, GameEvent, GameState, Ruleset, TSpinKind, TopOutReason,
+};
+
+fn state_with(ruleset: Ruleset) -> GameState {
+    let mut state = GameState::new(
+        4,
+        GameConfig {
+            ruleset,
+            ..GameConfig::default()
+        },
+    );
+    state.board = Board::new();
+    state
+}
+
+fn fill_bottom_row_from(state: &mut GameState, from: usize) {
+    for x in from..BOARD_WIDTH {
+        state.board.cells[BOARD_HEIGHT - 1][x].filled = true;
+        state.board.cells[BOARD_HEIGHT - 1][x].kind = Some(TetrominoType::O);
+    }
+}
+
+#[test]
+fn new_games_announce_the_first_piece() {
+    let mut state = GameState::new(1, GameConfig::default());
+    let kind = state.active.kind;
+    assert_eq!(state.take_events(), vec![GameEvent::PieceSpawned { kind }]);
+    assert!(state.take_events().is_empty());
+}
+
+#[test]
+fn a_hard_drop_reports_the_whole_lock_in_order() {
+    let mut state = state_with(Ruleset::Classic);
+    state.take_events();
+    let next = state.next_queue[0];
+
+    state.apply_action(GameAction::HardDrop);
+
+    let events = state.take_events();
+    assert!(matches!(events[0], GameEvent::HardDropped { rows } if rows > 0));
+    assert_eq!(
+        events[1],
+        GameEvent::PieceLocked {
+            cells: state.last_lock_cells
+        }
+    );
+    assert_eq!(events[2], GameEvent::PieceSpawned { kind: next });
+    assert_eq!(events.len(), 3);
+}
+
+#[test]
+fn line_clears_report_rows_combo_and_perfect_clears() {
+    let mut state = state_with(Ruleset::Modern);
+    fill_bottom_row_from(&mut state, 4);
+    state.active = Tetromino::new(TetrominoType::I, 0, BOARD_HEIGHT as i32 - 2);
+    state.take_events();
+
+    state.apply_action(GameAction::HardDrop);
+
+    let cleared = state
+        .take_events()
+        .into_iter()
+        .find(|event| matches!(event, GameEvent::LinesCleared { .. }));
+    assert_eq!(
+        cleared,
+        Some(GameEvent::LinesCleared {
+            rows: 1,
+            spin: TSpinKind::None,
+            b2b: false,
+            combo: 0,
+            perfect_clear: true,
+        })
+    );
+}
+
+#[test]
+fn back_to_back_tetrises_are_flagged() {
+    let mut state = state_with(Ruleset::Modern);
+    state.apply_line_clear(4, TSpinKind::None);
+    state.take_events();
+
+    state.apply_line_clear(4, TSpinKind::None);
+
+    assert!(matches!(
+        state.take_events()[..],
+        [GameEvent::LinesCleared {
+            rows: 4,
+            b2b: true,
+            combo: 1,
+            ..
+        }]
+    ));
+}
+
+#[test]
+fn level_ups_are_reported() {
+    let mut state = state_with(Ruleset::Classic);
+    state.lines = 9;
+    fill_bottom_row_from(&mut state, 4);
+    state.active = Tetromino::new(TetrominoType::I, 0, BOARD_HEIGHT as i32 - 2);
+
+    state.apply_action(GameAction::HardDrop);
+
+    assert!(
+        state
+            .take_events()
+            .contains(&GameEvent::LevelUp { level: 1 })
+    );
+}
+
+#[test]
+fn rotations_holds_and_pauses_are_reported() {
+    let mut state = state_with(Ruleset::Classic);
+    state.active = Tetromino::new(TetrominoType::T, 3, 5);
+    state.take_events();
+
+    state.apply_action(GameAction::RotateCw);
+    state.apply_action(GameAction::Hold);
+    state.apply_action(GameAction::Pause);
+
+    let events = state.take_events();
+    assert_eq!(events[0], GameEvent::Rotated { kick_index: 0 });
+    assert_eq!(
+        events[1],
+        GameEvent::Held {
+            kind: TetrominoType::T
+        }
+    );
+    assert!(matches!(events[2], GameEvent::PieceSpawned { .. }));
+    assert_eq!(events[3], GameEvent::Paused { paused: true });
+}
+
+#[test]
+fn block_outs_end_the_stream_with_a_top_out() {
+    let mut state = GameState::new(3, GameConfig::default());
+    let (x, y) = state.board.spawn_position();
+    for dx in 0..4 {
+        state.board.cells[y as usize + 1][(x + dx) as usize].filled = true;
+    }
+    state.take_events();
+
+    state.spawn_next();
+
+    assert_eq!(
+        state.take_events(),
+        vec![GameEvent::TopOut {
+            reason: TopOutReason::BlockOut
+        }]
+    );
+}
+
+#[test]
+fn replay_playback_emits_the_same_events() {
+    let mut state = GameState::new(9, GameConfig::default());
+    state.start_recording();
+    for action in [
+        GameAction::MoveLeft,
+        GameAction::HardDrop,
+        GameAction::RotateCw,
+        GameAction::HardDrop,
+    ] {
+        state.apply_action(action);
+        state.step(false);
+    }
+    let live = state.take_events();
+
+    let mut player = ReplayPlayer::new(state.replay().expect("recording").clone());
+    player.seek(player.duration_ms());
+    assert_eq!(player.take_events(), live);
+}
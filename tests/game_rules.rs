@@ -1,4 +1,4 @@
-use gpui_tetris::game::board::{BOARD_HEIGHT, BOARD_WIDTH, Board};
+use gpui_tetris::game::board::{BOARD_HEIGHT, BOARD_WIDTH, BUFFER_HEIGHT, Board};
 use gpui_tetris::game::pieces::{Rotation, Tetromino, TetrominoType, spawn_position};
 use gpui_tetris::game::state::{GameConfig, GameState, TSpinKind};
 
@@ -16,8 +16,8 @@ fn rotation_cycles() {
 }
 
 #[test]
-fn spawn_position_is_classic() {
-    assert_eq!(spawn_position(), (3, 0));
+fn spawn_position_is_just_above_the_visible_field() {
+    assert_eq!(spawn_position(), (3, BUFFER_HEIGHT as i32 - 2));
 }
 
 #[test]
@@ -59,7 +59,9 @@ fn clear_lines_removes_full_row() {
     }
 
     let cleared = board.clear_lines();
-    assert_eq!(cleared, 1);
+    assert_eq!(cleared.len(), 1);
+    assert_eq!(cleared[0].y, y);
+    assert!(cleared[0].cells.iter().all(|cell| cell.filled));
     assert!(board.cells[y].iter().all(|cell| !cell.filled));
 }
 
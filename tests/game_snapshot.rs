@@ -0,0 +1,94 @@
+use gpui_tetris::game::codec::DecodeError;
+use gpui_tetris::game::input::GameAction;
+use gpui_tetris::game::pieces::TetrominoType;
+use gpui_tetris::game::state::{GameConfig, GameSnapshot, GameState, Ruleset};
+
+#[test]
+fn snapshot_roundtrip_restores_state() {
+    let config = GameConfig {
+        ruleset: Ruleset::Modern,
+        lock_delay_ms: 600,
+        ..GameConfig::default()
+    };
+    let mut state = GameState::new(7, config);
+    state.apply_action(GameAction::HardDrop);
+    state.apply_action(GameAction::Hold);
+    state.apply_action(GameAction::MoveLeft);
+    state.tick(250, false);
+    state.score = 1234;
+    state.combo = 2;
+    state.back_to_back = true;
+
+    let bytes = state.to_snapshot().as_bytes().to_vec();
+    let snapshot = GameSnapshot::from_bytes(bytes).expect("valid snapshot");
+    let restored = GameState::from_snapshot(&snapshot).expect("decodes");
+
+    assert_eq!(restored.active, state.active);
+    assert_eq!(restored.hold, state.hold);
+    assert_eq!(restored.can_hold, state.can_hold);
+    assert_eq!(restored.next_queue, state.next_queue);
+    assert_eq!(restored.score, 1234);
+    assert_eq!(restored.combo, 2);
+    assert!(restored.back_to_back);
+    assert_eq!(restored.drop_timer_ms, state.drop_timer_ms);
+    assert_eq!(restored.lock_delay_ms, 600);
+    assert_eq!(restored.ruleset, Ruleset::Modern);
+    assert_eq!(restored.ghost_blocks(), state.ghost_blocks());
+    for (a, b) in restored
+        .board
+        .cells
+        .iter()
+        .flatten()
+        .zip(state.board.cells.iter().flatten())
+    {
+        assert_eq!(a, b);
+    }
+}
+
+#[test]
+fn restored_game_continues_identically() {
+    let mut state = GameState::new(11, GameConfig::default());
+    for _ in 0..6 {
+        state.apply_action(GameAction::HardDrop);
+    }
+    let mut restored = GameState::from_snapshot(&state.to_snapshot()).expect("decodes");
+
+    for _ in 0..10 {
+        state.apply_action(GameAction::HardDrop);
+        restored.apply_action(GameAction::HardDrop);
+    }
+
+    assert_eq!(restored.active, state.active);
+    assert_eq!(restored.next_queue, state.next_queue);
+    assert_eq!(restored.score, state.score);
+}
+
+#[test]
+fn snapshot_rejects_foreign_data() {
+    assert_eq!(
+        GameSnapshot::from_bytes(b"nope".to_vec()),
+        Err(DecodeError::BadMagic)
+    );
+
+    let mut bytes = GameState::new(1, GameConfig::default())
+        .to_snapshot()
+        .as_bytes()
+        .to_vec();
+    bytes[4] = 0xee;
+    assert!(matches!(
+        GameSnapshot::from_bytes(bytes),
+        Err(DecodeError::UnsupportedVersion(_))
+    ));
+}
+
+#[test]
+fn snapshot_preserves_piece_colours() {
+    let mut state = GameState::new(3, GameConfig::default());
+    state.board.cells[19][0].filled = true;
+    state.board.cells[19][0].kind = Some(TetrominoType::Z);
+
+    let restored = GameState::from_snapshot(&state.to_snapshot()).expect("decodes");
+
+    assert!(restored.board.cells[19][0].filled);
+    assert_eq!(restored.board.cells[19][0].kind, Some(TetrominoType::Z));
+}
@@ -1,4 +1,5 @@
 use gpui_tetris::game::input::GameAction;
+use gpui_tetris::game::pieces::spawn_position;
 use gpui_tetris::game::state::{GameConfig, GameState};
 
 #[test]
@@ -32,8 +33,10 @@ fn restart_resets_score_and_flags() {
 #[test]
 fn game_over_when_spawn_blocked() {
     let mut state = GameState::new(3, GameConfig::default());
-    state.board.cells[0][4].filled = true;
-    state.board.cells[0][4].kind = Some(gpui_tetris::game::pieces::TetrominoType::I);
+    let (_, spawn_y) = spawn_position();
+    state.board.cells[spawn_y as usize + 1][4].filled = true;
+    state.board.cells[spawn_y as usize + 1][4].kind =
+        Some(gpui_tetris::game::pieces::TetrominoType::I);
     state.next_queue = vec![gpui_tetris::game::pieces::TetrominoType::O];
 
     state.spawn_next();
@@ -0,0 +1,144 @@
+use gpui_tetris::assert_board_eq;
+use gpui_tetris::game::board::{BOARD_HEIGHT, BOARD_WIDTH, Board};
+use gpui_tetris::game::codec::DecodeError;
+use gpui_tetris::game::input::GameAction;
+use gpui_tetris::game::pieces::{Tetromino, TetrominoType};
+use gpui_tetris::game::state::{GameConfig, GameSnapshot, GameState, PendingGarbage, TopOutReason};
+
+fn empty_state() -> GameState {
+    let mut state = GameState::new(1, GameConfig::default());
+    state.board = Board::new();
+    state
+}
+
+fn garbage_rows(board: &Board) -> usize {
+    board
+        .cells
+        .iter()
+        .filter(|row| row.iter().any(|cell| cell.garbage))
+        .count()
+}
+
+#[test]
+fn insert_garbage_pushes_the_stack_up() {
+    let mut board = Board::from_ascii("T.........").expect("parses");
+
+    assert!(board.insert_garbage(2, 3));
+
+    assert_board_eq!(
+        board,
+        "
+        T.........
+        GGG.GGGGGG
+        GGG.GGGGGG
+        "
+    );
+}
+
+#[test]
+fn insert_garbage_reports_overflow() {
+    let mut board = Board::new();
+    board.cells[1][5].filled = true;
+    assert!(board.insert_garbage(1, 0));
+    assert!(!board.insert_garbage(1, 0));
+}
+
+#[test]
+fn garbage_rises_when_a_piece_locks_without_clearing() {
+    let mut state = empty_state();
+    state.queue_garbage(2, 0);
+    state.queue_garbage(1, 9);
+    assert_eq!(state.pending_garbage_rows(), 3);
+
+    state.apply_action(GameAction::HardDrop);
+
+    assert_eq!(state.pending_garbage_rows(), 0);
+    assert_eq!(garbage_rows(&state.board), 3);
+    assert!(!state.board.cells[BOARD_HEIGHT - 1][9].filled);
+    assert!(!state.board.cells[BOARD_HEIGHT - 3][0].filled);
+    assert!(!state.game_over);
+}
+
+#[test]
+fn line_clears_cancel_pending_garbage() {
+    let mut state = empty_state();
+    state.queue_garbage(1, 0);
+    state.queue_garbage(2, 4);
+    for x in 4..BOARD_WIDTH {
+        state.board.cells[BOARD_HEIGHT - 1][x].filled = true;
+    }
+    state.active = Tetromino::new(TetrominoType::I, 0, BOARD_HEIGHT as i32 - 2);
+
+    state.apply_action(GameAction::HardDrop);
+
+    assert_eq!(state.lines, 1);
+    assert_eq!(
+        state.pending_garbage(),
+        &[PendingGarbage {
+            rows: 2,
+            hole_column: 4
+        }]
+    );
+    assert_eq!(garbage_rows(&state.board), 0);
+}
+
+#[test]
+fn garbage_that_pushes_blocks_out_tops_out() {
+    let mut state = empty_state();
+    state.board.cells[0][9].filled = true;
+    state.queue_garbage(1, 0);
+
+    state.apply_action(GameAction::HardDrop);
+
+    assert!(state.game_over);
+    assert_eq!(state.top_out, Some(TopOutReason::GarbageOut));
+}
+
+#[test]
+fn snapshot_keeps_garbage() {
+    let mut state = empty_state();
+    state.queue_garbage(1, 2);
+    state.apply_action(GameAction::HardDrop);
+    state.queue_garbage(4, 7);
+
+    let restored = GameState::from_snapshot(&state.to_snapshot()).expect("decodes");
+    assert_eq!(restored.pending_garbage(), state.pending_garbage());
+    assert_eq!(
+        restored.board.cells[BOARD_HEIGHT - 1],
+        state.board.cells[BOARD_HEIGHT - 1]
+    );
+    assert!(restored.board.cells[BOARD_HEIGHT - 1][0].garbage);
+}
+
+#[test]
+#[should_panic(expected = "off the board")]
+fn insert_garbage_rejects_a_hole_off_the_board() {
+    Board::new().insert_garbage(1, BOARD_WIDTH);
+}
+
+#[test]
+fn out_of_range_holes_are_clamped_or_rejected() {
+    let mut state = empty_state();
+    state.queue_garbage(3, BOARD_WIDTH + 5);
+    assert_eq!(
+        state.pending_garbage(),
+        &[PendingGarbage {
+            rows: 3,
+            hole_column: BOARD_WIDTH - 1,
+        }]
+    );
+
+    // Point the stored hole past the right wall.
+    let mut bytes = state.to_snapshot().as_bytes().to_vec();
+    let entry = [3, 0, 0, 0, BOARD_WIDTH as u8 - 1, 0, 0, 0];
+    let at = bytes
+        .windows(entry.len())
+        .rposition(|window| window == entry)
+        .expect("pending garbage entry");
+    bytes[at + 4] = BOARD_WIDTH as u8;
+    let snapshot = GameSnapshot::from_bytes(bytes).expect("header");
+    assert!(matches!(
+        GameState::from_snapshot(&snapshot),
+        Err(DecodeError::InvalidValue("garbage hole column"))
+    ));
+}
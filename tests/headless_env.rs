@@ -0,0 +1,75 @@
+use gpui_tetris::game::env::{Env, EnvAction, EnvError, Placement};
+use gpui_tetris::game::input::GameAction;
+use gpui_tetris::game::pieces::Rotation;
+use gpui_tetris::game::state::GameConfig;
+
+#[test]
+fn reset_is_deterministic_per_seed() {
+    let mut env = Env::new(GameConfig::default());
+
+    let first = env.reset(12);
+    env.step(EnvAction::Input(GameAction::HardDrop)).unwrap();
+    let again = env.reset(12);
+
+    assert_eq!(first, again);
+    assert_eq!(first.score, 0);
+    assert!(first.board.iter().flatten().all(|filled| !filled));
+}
+
+#[test]
+fn placements_cover_every_column_for_the_first_piece() {
+    let mut env = Env::new(GameConfig::default());
+    env.reset(3);
+
+    let placements = env.placements();
+
+    assert!(!placements.is_empty());
+    assert!(placements.iter().any(|placement| placement.hold));
+    for placement in placements {
+        let mut probe = env.clone();
+        let result = probe.step(EnvAction::Place(placement)).unwrap();
+        assert!(
+            result
+                .observation
+                .board
+                .iter()
+                .flatten()
+                .any(|filled| *filled)
+        );
+        assert!(result.reward >= 0.0);
+    }
+}
+
+#[test]
+fn unreachable_placement_leaves_state_untouched() {
+    let mut env = Env::new(GameConfig::default());
+    let before = env.reset(5);
+
+    let result = env.step(EnvAction::Place(Placement {
+        hold: false,
+        rotation: Rotation::North,
+        x: 40,
+    }));
+
+    assert_eq!(result, Err(EnvError::UnreachablePlacement));
+    assert_eq!(env.observe(), before);
+}
+
+#[test]
+fn stacking_in_one_column_ends_the_episode() {
+    let mut env = Env::new(GameConfig::default());
+    env.reset(8);
+
+    let mut done = false;
+    for _ in 0..100 {
+        let result = env.step(EnvAction::Input(GameAction::HardDrop)).unwrap();
+        if result.done {
+            done = true;
+            break;
+        }
+    }
+
+    assert!(done);
+    assert!(env.is_done());
+    assert!(env.placements().is_empty());
+}
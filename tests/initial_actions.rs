@@ -0,0 +1,170 @@
+use gpui_tetris::game::board::Board;
+use gpui_tetris::game::input::{GameAction, HeldButtons};
+use gpui_tetris::game::pieces::{Rotation, TetrominoType};
+use gpui_tetris::game::replay::Replay;
+use gpui_tetris::game::state::{GameConfig, GameState, LevelTimings, TimingCurve};
+
+fn state_with(initial_actions: bool) -> GameState {
+    let mut state = GameState::new(
+        5,
+        GameConfig {
+            initial_actions,
+            timings: TimingCurve::fixed(LevelTimings {
+                are_ms: 100,
+                ..LevelTimings::default()
+            }),
+            ..GameConfig::default()
+        },
+    );
+    state.board = Board::new();
+    state
+}
+
+fn held(action: GameAction) -> HeldButtons {
+    let mut held = HeldButtons::default();
+    held.set(action, true);
+    held
+}
+
+fn lock_and_wait(state: &mut GameState) {
+    state.apply_action(GameAction::HardDrop);
+    while state.is_spawn_delay_active() {
+        state.tick(16, false);
+    }
+}
+
+#[test]
+fn rotation_held_through_are_turns_the_next_piece() {
+    let mut state = state_with(true);
+    state.apply_action(GameAction::HardDrop);
+    state.set_held_buttons(held(GameAction::RotateCw));
+    while state.is_spawn_delay_active() {
+        state.tick(16, false);
+    }
+
+    assert_eq!(state.active.rotation, Rotation::East);
+    assert_eq!(state.active.y, state.board.spawn_position().1);
+}
+
+#[test]
+fn counter_clockwise_and_180_are_buffered_too() {
+    let mut state = state_with(true);
+    state.set_held_buttons(held(GameAction::RotateCcw));
+    lock_and_wait(&mut state);
+    assert_eq!(state.active.rotation, Rotation::West);
+
+    // Plain SRS has no 180 rotation.
+    state.set_held_buttons(held(GameAction::Rotate180));
+    lock_and_wait(&mut state);
+    assert_eq!(state.active.rotation, Rotation::North);
+}
+
+#[test]
+fn hold_held_through_are_swaps_the_next_piece() {
+    let mut state = state_with(true);
+    state.apply_action(GameAction::HardDrop);
+    let incoming = state.next_queue[0];
+    let after = state.next_queue[1];
+    state.set_held_buttons(held(GameAction::Hold));
+    while state.is_spawn_delay_active() {
+        state.tick(16, false);
+    }
+
+    assert_eq!(state.hold, Some(incoming));
+    assert_eq!(state.active.kind, after);
+    assert!(!state.can_hold);
+}
+
+#[test]
+fn ihs_swaps_with_a_stored_piece_and_then_applies_irs() {
+    let mut state = state_with(true);
+    state.hold = Some(TetrominoType::T);
+    state.apply_action(GameAction::HardDrop);
+    let incoming = state.next_queue[0];
+    state.set_held_buttons(held(GameAction::Hold).union(held(GameAction::RotateCw)));
+    while state.is_spawn_delay_active() {
+        state.tick(16, false);
+    }
+
+    assert_eq!(state.active.kind, TetrominoType::T);
+    assert_eq!(state.active.rotation, Rotation::East);
+    assert_eq!(state.hold, Some(incoming));
+}
+
+#[test]
+fn manual_hold_into_an_empty_slot_skips_initial_actions() {
+    let mut state = state_with(true);
+    let current = state.active.kind;
+    let incoming = state.next_queue[0];
+    let mut buttons = held(GameAction::Hold);
+    buttons.set(GameAction::RotateCw, true);
+    state.set_held_buttons(buttons);
+
+    state.apply_action(GameAction::Hold);
+
+    assert_eq!(state.hold, Some(current));
+    assert_eq!(state.active.kind, incoming);
+    assert_eq!(state.active.rotation, Rotation::North);
+    assert!(!state.can_hold);
+}
+
+#[test]
+fn held_buttons_do_nothing_when_switched_off() {
+    let mut state = state_with(false);
+    state.apply_action(GameAction::HardDrop);
+    let incoming = state.next_queue[0];
+    state.set_held_buttons(held(GameAction::Hold).union(held(GameAction::RotateCw)));
+    while state.is_spawn_delay_active() {
+        state.tick(16, false);
+    }
+
+    assert_eq!(state.active.kind, incoming);
+    assert_eq!(state.active.rotation, Rotation::North);
+    assert_eq!(state.hold, None);
+}
+
+#[test]
+fn presses_during_are_are_dropped() {
+    let mut state = state_with(true);
+    state.apply_action(GameAction::HardDrop);
+    state.apply_action(GameAction::RotateCw);
+    state.apply_action(GameAction::Hold);
+    lock_and_wait(&mut state);
+
+    assert_eq!(state.hold, None);
+}
+
+#[test]
+fn replays_reproduce_buffered_inputs() {
+    let mut state = state_with(true);
+    state.start_recording();
+    state.step(false);
+    state.apply_action(GameAction::HardDrop);
+    state.set_held_buttons(held(GameAction::RotateCcw));
+    for _ in 0..10 {
+        state.step(false);
+    }
+    state.set_held_buttons(HeldButtons::default());
+    state.apply_action(GameAction::HardDrop);
+    state.set_held_buttons(held(GameAction::Hold));
+    for _ in 0..10 {
+        state.step(false);
+    }
+
+    let replay = state.replay().expect("recording").clone();
+    let decoded = Replay::from_bytes(&replay.to_bytes()).expect("decodes");
+    let played = decoded.play();
+    assert_eq!(played.active, state.active);
+    assert_eq!(played.hold, state.hold);
+    assert_eq!(played.board.cells, state.board.cells);
+}
+
+#[test]
+fn snapshot_keeps_the_setting_and_held_buttons() {
+    let mut state = state_with(true);
+    state.set_held_buttons(held(GameAction::Hold));
+
+    let restored = GameState::from_snapshot(&state.to_snapshot()).expect("decodes");
+    assert!(restored.initial_actions);
+    assert_eq!(restored.held_buttons(), held(GameAction::Hold));
+}
@@ -0,0 +1,126 @@
+use gpui_tetris::game::board::{BOARD_HEIGHT, Board};
+use gpui_tetris::game::input::GameAction;
+use gpui_tetris::game::pieces::{Rotation, Tetromino, TetrominoType};
+use gpui_tetris::game::state::{GameConfig, GameState, LockDownMode};
+
+const LEDGE_END: usize = 6;
+
+/// O piece resting on a one-row ledge that ends at `LEDGE_END`, with the
+/// floor two cells to its right.
+fn state_on_ledge(lock_down_mode: LockDownMode) -> GameState {
+    let mut state = GameState::new(
+        1,
+        GameConfig {
+            lock_delay_ms: 1000,
+            lock_reset_limit: 1,
+            lock_down_mode,
+            ..GameConfig::default()
+        },
+    );
+    state.board = Board::new();
+    for x in 0..=LEDGE_END {
+        state.board.cells[BOARD_HEIGHT - 1][x].filled = true;
+        state.board.cells[BOARD_HEIGHT - 1][x].kind = Some(TetrominoType::I);
+    }
+    let right = Tetromino::new(TetrominoType::O, 0, 0)
+        .blocks(Rotation::North)
+        .iter()
+        .map(|(dx, _)| *dx)
+        .max()
+        .unwrap_or(0);
+    state.active = Tetromino::new(
+        TetrominoType::O,
+        LEDGE_END as i32 - right,
+        BOARD_HEIGHT as i32 - 4,
+    );
+    state.apply_action(GameAction::SoftDrop);
+    assert!(state.is_grounded());
+    state
+}
+
+#[test]
+fn extended_placement_restores_resets_on_a_new_lowest_row() {
+    let mut state = state_on_ledge(LockDownMode::ExtendedPlacement);
+    assert_eq!(state.lock_reset_remaining(), 1);
+
+    state.lock_timer_ms = 900;
+    state.apply_action(GameAction::MoveLeft);
+    assert_eq!(state.lock_timer_ms, 0);
+    assert_eq!(state.lock_reset_remaining(), 0);
+
+    state.lock_timer_ms = 900;
+    state.apply_action(GameAction::MoveRight);
+    assert_eq!(state.lock_timer_ms, 900);
+
+    // Stepping off the ledge starts a fresh count once the piece lands lower.
+    state.apply_action(GameAction::MoveRight);
+    state.apply_action(GameAction::MoveRight);
+    assert!(!state.is_grounded());
+    state.apply_action(GameAction::SoftDrop);
+    assert!(state.is_grounded());
+    assert_eq!(state.lock_reset_remaining(), 1);
+}
+
+#[test]
+fn extended_placement_keeps_the_count_while_airborne() {
+    let mut state = state_on_ledge(LockDownMode::ExtendedPlacement);
+    state.apply_action(GameAction::MoveLeft);
+    assert_eq!(state.lock_reset_remaining(), 0);
+
+    state.tick(0, false);
+    state.apply_action(GameAction::MoveRight);
+    state.apply_action(GameAction::MoveRight);
+    state.apply_action(GameAction::MoveRight);
+    state.tick(0, false);
+    assert!(!state.is_grounded());
+    assert_eq!(state.lock_reset_remaining(), 0);
+}
+
+#[test]
+fn infinite_resets_on_every_move() {
+    let mut state = state_on_ledge(LockDownMode::Infinite);
+    for action in [GameAction::MoveLeft, GameAction::MoveRight].repeat(4) {
+        state.lock_timer_ms = 900;
+        state.apply_action(action);
+        assert_eq!(state.lock_timer_ms, 0);
+    }
+    assert!(state.is_grounded());
+}
+
+#[test]
+fn classic_only_resets_on_a_step_down() {
+    let mut state = state_on_ledge(LockDownMode::Classic);
+    state.lock_timer_ms = 900;
+    state.apply_action(GameAction::MoveLeft);
+    state.apply_action(GameAction::RotateCw);
+    assert_eq!(state.lock_timer_ms, 900);
+
+    state.apply_action(GameAction::MoveRight);
+    state.apply_action(GameAction::MoveRight);
+    state.apply_action(GameAction::MoveRight);
+    state.lock_timer_ms = 900;
+    state.apply_action(GameAction::SoftDrop);
+    assert_eq!(state.lock_timer_ms, 0);
+}
+
+#[test]
+fn snapshot_keeps_the_mode_and_lowest_row() {
+    let mut state = state_on_ledge(LockDownMode::ExtendedPlacement);
+    state.apply_action(GameAction::MoveLeft);
+
+    let mut restored = GameState::from_snapshot(&state.to_snapshot()).expect("decodes");
+    assert_eq!(restored.lock_down_mode, LockDownMode::ExtendedPlacement);
+    assert_eq!(restored.lock_reset_remaining(), 0);
+
+    // The restored lowest row still sits on the ledge, so only a real drop
+    // past it brings the resets back.
+    restored.apply_action(GameAction::MoveRight);
+    restored.apply_action(GameAction::MoveRight);
+    restored.apply_action(GameAction::MoveRight);
+    restored.apply_action(GameAction::SoftDrop);
+    assert_eq!(restored.lock_reset_remaining(), 1);
+
+    let classic = state_on_ledge(LockDownMode::Classic);
+    let restored = GameState::from_snapshot(&classic.to_snapshot()).expect("decodes");
+    assert_eq!(restored.lock_down_mode, LockDownMode::Classic);
+}
@@ -0,0 +1,195 @@
+use gpui_tetris::game::board::{BOARD_HEIGHT, BOARD_WIDTH, Board};
+use gpui_tetris::game::input::GameAction;
+use gpui_tetris::game::pieces::{Tetromino, TetrominoType};
+use gpui_tetris::game::state::{GameConfig, GameMode, GameState, Gravity};
+
+fn master_state() -> GameState {
+    let mut state = GameState::new(
+        1,
+        GameConfig {
+            mode: GameMode::Master,
+            ..GameConfig::default()
+        },
+    );
+    state.board = Board::new();
+    state
+}
+
+fn fill_bottom_row_except(state: &mut GameState, from: usize) {
+    for x in from..BOARD_WIDTH {
+        state.board.cells[BOARD_HEIGHT - 1][x].filled = true;
+        state.board.cells[BOARD_HEIGHT - 1][x].kind = Some(TetrominoType::O);
+    }
+}
+
+fn run_spawn_delay(state: &mut GameState) {
+    while state.is_line_clear_active() || state.is_spawn_delay_active() {
+        state.tick(1, false);
+    }
+}
+
+#[test]
+fn gravity_intervals_follow_g() {
+    assert_eq!(Gravity::ONE_G.interval_ms(), 16);
+    assert_eq!(Gravity(4).interval_ms(), 1066);
+    assert_eq!(Gravity::TWENTY_G.interval_ms(), 1);
+    assert!(Gravity::TWENTY_G.is_instant());
+    assert!(!Gravity(5119).is_instant());
+}
+
+#[test]
+fn fixed_gravity_overrides_the_level_table() {
+    let state = GameState::new(
+        1,
+        GameConfig {
+            gravity: Some(Gravity(512)),
+            ..GameConfig::default()
+        },
+    );
+    assert_eq!(state.drop_interval_ms(false), 8);
+    assert_eq!(state.drop_interval_ms(true), 1);
+}
+
+#[test]
+fn twenty_g_pieces_spawn_on_the_stack() {
+    let mut state = GameState::new(
+        1,
+        GameConfig {
+            gravity: Some(Gravity::TWENTY_G),
+            ..GameConfig::default()
+        },
+    );
+    state.board = Board::new();
+    state.spawn_next();
+
+    let floor = state.ghost_blocks();
+    let mut blocks = state.active.blocks(state.active.rotation);
+    for (x, y) in blocks.iter_mut() {
+        *x += state.active.x;
+        *y += state.active.y;
+    }
+    assert_eq!(blocks, floor);
+    assert!(state.is_grounded());
+}
+
+#[test]
+fn twenty_g_pieces_fall_off_ledges_as_they_move() {
+    let mut state = GameState::new(
+        1,
+        GameConfig {
+            gravity: Some(Gravity::TWENTY_G),
+            ..GameConfig::default()
+        },
+    );
+    state.board = Board::new();
+    fill_bottom_row_except(&mut state, 5);
+    state.active = Tetromino::new(TetrominoType::O, 5, BOARD_HEIGHT as i32 - 3);
+    assert!(state.is_grounded());
+
+    for _ in 0..5 {
+        state.apply_action(GameAction::MoveLeft);
+    }
+    assert_eq!(state.active.x, 0);
+    assert_eq!(state.active.y, BOARD_HEIGHT as i32 - 2);
+}
+
+#[test]
+fn master_starts_slow_and_reaches_twenty_g() {
+    let mut state = master_state();
+    assert_eq!(state.current_gravity(), Some(Gravity(4)));
+    state.level = 251;
+    assert_eq!(state.current_gravity(), Some(Gravity::ONE_G));
+    state.level = 500;
+    assert_eq!(state.current_gravity(), Some(Gravity::TWENTY_G));
+}
+
+#[test]
+fn master_levels_count_pieces_and_lines_but_stop_at_section_ends() {
+    let mut state = master_state();
+    state.apply_action(GameAction::HardDrop);
+    assert_eq!(state.level, 1);
+    assert!(state.is_spawn_delay_active());
+
+    run_spawn_delay(&mut state);
+    state.level = 99;
+    state.apply_action(GameAction::HardDrop);
+    assert_eq!(state.level, 99);
+
+    run_spawn_delay(&mut state);
+    fill_bottom_row_except(&mut state, 4);
+    state.active = Tetromino::new(TetrominoType::I, 0, BOARD_HEIGHT as i32 - 2);
+    state.apply_action(GameAction::HardDrop);
+    assert_eq!(state.level, 100);
+}
+
+#[test]
+fn master_waits_out_are_before_spawning() {
+    let mut state = master_state();
+    state.apply_action(GameAction::HardDrop);
+    let locked = state.active;
+    assert_eq!(state.spawn_delay_ms, 416);
+
+    state.apply_action(GameAction::MoveLeft);
+    state.tick(400, false);
+    assert_eq!(state.active, locked);
+
+    state.tick(16, false);
+    assert!(!state.is_spawn_delay_active());
+    assert_ne!(state.active, locked);
+    assert_eq!(state.pieces_placed, 1);
+}
+
+#[test]
+fn master_delays_shrink_with_level() {
+    let mut state = master_state();
+    assert_eq!(state.lock_delay_ms, 500);
+    state.level = 900;
+    state.apply_action(GameAction::HardDrop);
+    assert_eq!(state.level, 901);
+    assert_eq!(state.lock_delay_ms, 283);
+    assert_eq!(state.spawn_delay_ms, 200);
+}
+
+#[test]
+fn master_grade_follows_score() {
+    let mut state = master_state();
+    assert_eq!(state.master_grade(), Some("9"));
+    state.score = 16_000;
+    assert_eq!(state.master_grade(), Some("S1"));
+    state.score = 130_000;
+    assert_eq!(state.master_grade(), Some("S9"));
+
+    state.level = 999;
+    state.sim_time_ms = 13 * 60_000;
+    assert_eq!(state.master_grade(), Some("GM"));
+    assert_eq!(
+        GameState::new(1, GameConfig::default()).master_grade(),
+        None
+    );
+}
+
+#[test]
+fn master_completes_at_level_999() {
+    let mut state = master_state();
+    state.level = 997;
+    fill_bottom_row_except(&mut state, 4);
+    state.active = Tetromino::new(TetrominoType::I, 0, BOARD_HEIGHT as i32 - 2);
+    state.apply_action(GameAction::HardDrop);
+
+    assert_eq!(state.level, 999);
+    assert!(state.game_over);
+    assert!(state.mode_complete);
+}
+
+#[test]
+fn snapshot_keeps_master_progress() {
+    let mut state = master_state();
+    state.level = 650;
+    state.apply_action(GameAction::HardDrop);
+
+    let restored = GameState::from_snapshot(&state.to_snapshot()).expect("decodes");
+    assert_eq!(restored.mode, GameMode::Master);
+    assert_eq!(restored.level, 651);
+    assert_eq!(restored.spawn_delay_ms, state.spawn_delay_ms);
+    assert_eq!(restored.lock_delay_ms, state.lock_delay_ms);
+}
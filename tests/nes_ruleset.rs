@@ -0,0 +1,168 @@
+use gpui_tetris::game::board::Board;
+use gpui_tetris::game::input::{GameAction, RepeatConfig};
+use gpui_tetris::game::pieces::{Tetromino, TetrominoType};
+use gpui_tetris::game::rotation::RotationSystem;
+use gpui_tetris::game::state::{
+    GameConfig, GameMode, GameState, LockDownMode, RandomizerKind, Ruleset, RulesetPreset,
+    TSpinKind,
+};
+
+fn nes_state(start_level: u32) -> GameState {
+    let config = RulesetPreset::Nes.apply(GameConfig {
+        start_level,
+        ..GameConfig::default()
+    });
+    let mut state = GameState::new(1, config);
+    state.board = Board::new();
+    state
+}
+
+#[test]
+fn preset_sets_up_nes_rules() {
+    let config = RulesetPreset::Nes.apply(GameConfig {
+        mode: GameMode::sprint(),
+        start_level: 9,
+        ..GameConfig::default()
+    });
+    assert_eq!(config.ruleset, Ruleset::Nes);
+    assert_eq!(config.randomizer, RandomizerKind::Nes);
+    assert_eq!(config.rotation_system, RotationSystem::Nes);
+    assert_eq!(config.preview_count, 1);
+    assert_eq!(config.lock_down_mode, LockDownMode::Classic);
+    assert_eq!(config.mode, GameMode::sprint());
+    assert_eq!(config.start_level, 9);
+
+    let guideline = RulesetPreset::Guideline.apply(config);
+    assert_eq!(guideline.ruleset, Ruleset::Modern);
+    assert_eq!(guideline.preview_count, GameConfig::default().preview_count);
+    assert_eq!(RulesetPreset::Nes.next(), RulesetPreset::ALL[0]);
+}
+
+#[test]
+fn gravity_follows_the_frame_table() {
+    let mut state = nes_state(0);
+    assert_eq!(state.drop_interval_ms(false), 800);
+    for (level, frames) in [(8, 8), (9, 6), (18, 3), (19, 2), (28, 2), (29, 1), (40, 1)] {
+        state.level = level;
+        assert_eq!(
+            state.drop_interval_ms(false),
+            frames * 1000 / 60,
+            "level {level}"
+        );
+    }
+
+    // Soft drop never falls slower than every other frame.
+    state.level = 0;
+    assert_eq!(state.drop_interval_ms(true), 33);
+    state.level = 29;
+    assert_eq!(state.drop_interval_ms(true), 16);
+}
+
+#[test]
+fn first_level_up_follows_the_start_level() {
+    for (start, first) in [
+        (0, 10),
+        (5, 60),
+        (9, 100),
+        (12, 100),
+        (15, 100),
+        (18, 130),
+        (19, 140),
+    ] {
+        let mut state = nes_state(start);
+        assert_eq!(state.level, start);
+        state.lines = first - 1;
+        state.apply_line_clear(0, TSpinKind::None);
+        assert_eq!(state.level, start, "start {start}");
+        state.apply_line_clear(1, TSpinKind::None);
+        assert_eq!(state.level, start + 1, "start {start}");
+        state.lines += 9;
+        state.apply_line_clear(1, TSpinKind::None);
+        assert_eq!(state.level, start + 2, "start {start}");
+    }
+}
+
+#[test]
+fn other_rulesets_start_on_the_start_level() {
+    let mut state = GameState::new(
+        1,
+        GameConfig {
+            start_level: 5,
+            ..GameConfig::default()
+        },
+    );
+    assert_eq!(state.level, 5);
+    state.apply_line_clear(4, TSpinKind::None);
+    assert_eq!(state.level, 5);
+    state.lines = 59;
+    state.apply_line_clear(1, TSpinKind::None);
+    assert_eq!(state.level, 6);
+}
+
+#[test]
+fn line_scores_use_the_level_and_hard_drops_score_nothing() {
+    let mut state = nes_state(3);
+    state.apply_line_clear(4, TSpinKind::None);
+    assert_eq!(state.score, 1200 * 4);
+
+    let mut state = nes_state(0);
+    state.active = Tetromino::new(TetrominoType::O, 3, 0);
+    state.apply_action(GameAction::SoftDrop);
+    assert_eq!(state.score, 1);
+    state.apply_action(GameAction::HardDrop);
+    assert_eq!(state.score, 1);
+}
+
+#[test]
+fn hold_is_disabled() {
+    let mut state = nes_state(0);
+    let active = state.active.kind;
+    assert!(!state.hold_enabled());
+    assert!(!state.shows_ghost());
+    assert!(!state.can_hold);
+
+    state.apply_action(GameAction::Hold);
+    assert_eq!(state.hold, None);
+    assert_eq!(state.active.kind, active);
+
+    state.apply_action(GameAction::HardDrop);
+    while state.is_line_clear_active() || state.is_spawn_delay_active() {
+        state.tick(16, false);
+    }
+    assert!(!state.can_hold);
+    assert_eq!(state.preview().len(), 1);
+}
+
+#[test]
+fn lock_delay_is_one_drop_interval() {
+    let mut state = nes_state(19);
+    assert_eq!(state.lock_delay_ms, state.drop_interval_ms(false));
+
+    state.lines = 139;
+    state.apply_line_clear(1, TSpinKind::None);
+    assert_eq!(state.level, 20);
+    assert_eq!(state.lock_delay_ms, state.drop_interval_ms(false));
+}
+
+#[test]
+fn das_uses_nes_frames() {
+    let nes = RepeatConfig::for_ruleset(Ruleset::Nes);
+    assert_eq!((nes.das_ms, nes.arr_ms), (266, 100));
+    assert_eq!(
+        RepeatConfig::for_ruleset(Ruleset::Modern).das_ms,
+        RepeatConfig::default().das_ms
+    );
+}
+
+#[test]
+fn snapshot_keeps_the_ruleset_and_start_level() {
+    let mut state = nes_state(18);
+    state.lines = 120;
+
+    let restored = GameState::from_snapshot(&state.to_snapshot()).expect("decodes");
+    assert_eq!(restored.ruleset, Ruleset::Nes);
+    assert_eq!(restored.start_level, 18);
+    assert_eq!(restored.level, 18);
+    assert_eq!(restored.lock_delay_ms, state.lock_delay_ms);
+    assert!(!restored.can_hold);
+}
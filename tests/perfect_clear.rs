@@ -0,0 +1,99 @@
+use gpui_tetris::game::board::{BOARD_HEIGHT, BOARD_WIDTH, Board};
+use gpui_tetris::game::input::GameAction;
+use gpui_tetris::game::pieces::{Rotation, Tetromino, TetrominoType};
+use gpui_tetris::game::state::{GameConfig, GameState, Ruleset, SoundEvent};
+
+fn state_with(ruleset: Ruleset) -> GameState {
+    let mut state = GameState::new(
+        1,
+        GameConfig {
+            ruleset,
+            ..GameConfig::default()
+        },
+    );
+    state.board = Board::new();
+    state
+}
+
+fn fill_rows(state: &mut GameState, rows: std::ops::Range<usize>, from_x: usize) {
+    for y in rows {
+        for x in from_x..BOARD_WIDTH {
+            state.board.cells[y][x].filled = true;
+            state.board.cells[y][x].kind = Some(TetrominoType::O);
+        }
+    }
+}
+
+fn drop_flat_i_into_bottom_row(state: &mut GameState) {
+    fill_rows(state, BOARD_HEIGHT - 1..BOARD_HEIGHT, 4);
+    state.active = Tetromino::new(TetrominoType::I, 0, BOARD_HEIGHT as i32 - 2);
+    state.take_events();
+    state.apply_action(GameAction::HardDrop);
+}
+
+#[test]
+fn single_perfect_clear_adds_bonus() {
+    let mut state = state_with(Ruleset::Modern);
+    drop_flat_i_into_bottom_row(&mut state);
+
+    assert!(state.board.is_empty());
+    assert_eq!(state.score, 800 + 40);
+    assert!(state.perfect_clear_active());
+    assert!(
+        state
+            .take_events()
+            .iter()
+            .any(|event| event.sound() == Some(SoundEvent::PerfectClear))
+    );
+}
+
+#[test]
+fn leftover_blocks_are_not_a_perfect_clear() {
+    let mut state = state_with(Ruleset::Modern);
+    state.board.cells[BOARD_HEIGHT - 2][9].filled = true;
+    drop_flat_i_into_bottom_row(&mut state);
+
+    assert!(!state.board.is_empty());
+    assert_eq!(state.score, 40);
+    assert!(!state.perfect_clear_active());
+}
+
+#[test]
+fn back_to_back_tetris_perfect_clear_uses_b2b_bonus() {
+    let mut state = state_with(Ruleset::Modern);
+    state.back_to_back = true;
+    fill_rows(&mut state, BOARD_HEIGHT - 4..BOARD_HEIGHT, 1);
+    state.active = Tetromino::new(TetrominoType::I, -2, BOARD_HEIGHT as i32 - 4);
+    state.active.rotation = Rotation::East;
+
+    state.apply_action(GameAction::HardDrop);
+    assert!(state.board.is_empty());
+    // 3200 perfect clear plus the 1200 tetris with its 3/2 B2B bonus.
+    assert_eq!(state.score, 3200 + 1800);
+}
+
+#[test]
+fn classic_perfect_clear_is_announced_without_bonus() {
+    let mut state = state_with(Ruleset::Classic);
+    drop_flat_i_into_bottom_row(&mut state);
+
+    assert_eq!(state.score, 40);
+    assert!(state.perfect_clear_active());
+    assert!(
+        state
+            .take_events()
+            .iter()
+            .any(|event| event.sound() == Some(SoundEvent::PerfectClear))
+    );
+}
+
+#[test]
+fn perfect_clear_callout_expires() {
+    let mut state = state_with(Ruleset::Modern);
+    drop_flat_i_into_bottom_row(&mut state);
+
+    for _ in 0..200 {
+        state.step(false);
+    }
+    assert!(!state.perfect_clear_active());
+}
@@ -0,0 +1,60 @@
+use gpui_tetris::game::input::GameAction;
+use gpui_tetris::game::state::{GameConfig, GameState, MAX_PREVIEW_COUNT};
+
+fn state_with(preview_count: usize) -> GameState {
+    GameState::new(
+        7,
+        GameConfig {
+            preview_count,
+            ..GameConfig::default()
+        },
+    )
+}
+
+#[test]
+fn preview_shows_the_configured_number_of_pieces() {
+    for count in 0..=MAX_PREVIEW_COUNT {
+        let mut state = state_with(count);
+        for _ in 0..10 {
+            assert_eq!(state.preview().len(), count);
+            assert!(state.next_queue.len() >= count);
+            state.apply_action(GameAction::HardDrop);
+        }
+    }
+}
+
+#[test]
+fn preview_count_is_clamped() {
+    let state = state_with(12);
+    assert_eq!(state.preview_count, MAX_PREVIEW_COUNT);
+    assert_eq!(state.preview().len(), MAX_PREVIEW_COUNT);
+}
+
+#[test]
+fn preview_is_the_front_of_the_queue() {
+    let mut state = state_with(7);
+    let upcoming = state.preview().to_vec();
+    state.apply_action(GameAction::HardDrop);
+
+    assert_eq!(state.active.kind, upcoming[0]);
+    assert_eq!(state.preview()[..6], upcoming[1..]);
+}
+
+#[test]
+fn preview_count_does_not_change_the_sequence() {
+    let mut short = state_with(1);
+    let mut long = state_with(7);
+    for _ in 0..20 {
+        assert_eq!(short.active.kind, long.active.kind);
+        short.apply_action(GameAction::HardDrop);
+        long.apply_action(GameAction::HardDrop);
+    }
+}
+
+#[test]
+fn snapshot_keeps_preview_count() {
+    let state = state_with(3);
+    let restored = GameState::from_snapshot(&state.to_snapshot()).expect("decodes");
+    assert_eq!(restored.preview_count, 3);
+    assert_eq!(restored.preview(), state.preview());
+}
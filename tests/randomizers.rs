@@ -0,0 +1,131 @@
+use gpui_tetris::game::pieces::TetrominoType;
+use gpui_tetris::game::state::{
+    BagRandomizer, GameConfig, GameState, NesRandomizer, PureRandomizer, Randomizer,
+    RandomizerKind, TgmRandomizer,
+};
+
+const SAMPLES: usize = 70_000;
+
+fn draw(randomizer: &mut impl Randomizer, count: usize) -> Vec<TetrominoType> {
+    (0..count).map(|_| randomizer.next_piece()).collect()
+}
+
+fn counts(pieces: &[TetrominoType]) -> [usize; 7] {
+    let mut counts = [0; 7];
+    for kind in pieces {
+        counts[kind.index()] += 1;
+    }
+    counts
+}
+
+fn assert_roughly_uniform(pieces: &[TetrominoType]) {
+    let expected = pieces.len() / 7;
+    for count in counts(pieces) {
+        assert!(
+            count.abs_diff(expected) < expected / 10,
+            "{count} vs {expected}"
+        );
+    }
+}
+
+fn repeat_rate(pieces: &[TetrominoType]) -> f64 {
+    let repeats = pieces.windows(2).filter(|pair| pair[0] == pair[1]).count();
+    repeats as f64 / (pieces.len() - 1) as f64
+}
+
+#[test]
+fn seven_bag_deals_each_piece_once_per_bag() {
+    let pieces = draw(&mut BagRandomizer::new(5, 1), 7 * 500);
+
+    for bag in pieces.chunks(7) {
+        assert_eq!(counts(bag), [1; 7]);
+    }
+    for kind in TetrominoType::ALL {
+        let positions: Vec<usize> = (0..pieces.len()).filter(|&i| pieces[i] == kind).collect();
+        assert!(positions.windows(2).all(|pair| pair[1] - pair[0] <= 13));
+    }
+}
+
+#[test]
+fn fourteen_bag_deals_each_piece_twice_per_bag() {
+    let pieces = draw(&mut BagRandomizer::new(5, 2), 14 * 500);
+
+    for bag in pieces.chunks(14) {
+        assert_eq!(counts(bag), [2; 7]);
+    }
+    // Unlike the 7-bag, the same piece can come up twice in a row.
+    assert!(repeat_rate(&pieces) > 0.0);
+}
+
+#[test]
+fn pure_random_is_uniform_with_independent_repeats() {
+    let pieces = draw(&mut PureRandomizer::new(9), SAMPLES);
+
+    assert_roughly_uniform(&pieces);
+    let rate = repeat_rate(&pieces);
+    assert!((0.12..0.17).contains(&rate), "repeat rate {rate}");
+}
+
+#[test]
+fn nes_reroll_cuts_repeats_to_about_one_in_twenty_eight() {
+    let pieces = draw(&mut NesRandomizer::new(9), SAMPLES);
+
+    assert_roughly_uniform(&pieces);
+    let rate = repeat_rate(&pieces);
+    assert!((0.025..0.047).contains(&rate), "repeat rate {rate}");
+}
+
+#[test]
+fn tgm_history_rarely_repeats_recent_pieces() {
+    let pieces = draw(&mut TgmRandomizer::new(9), SAMPLES);
+
+    assert_roughly_uniform(&pieces);
+    let recent = pieces
+        .windows(5)
+        .filter(|window| window[..4].contains(&window[4]))
+        .count();
+    let rate = recent as f64 / (pieces.len() - 4) as f64;
+    assert!(rate < 0.06, "history hit rate {rate}");
+}
+
+#[test]
+fn tgm_never_opens_with_s_z_or_o() {
+    for seed in 0..200 {
+        let first = TgmRandomizer::new(seed).next_piece();
+        assert!(!matches!(
+            first,
+            TetrominoType::S | TetrominoType::Z | TetrominoType::O
+        ));
+    }
+}
+
+#[test]
+fn every_randomizer_is_deterministic_from_the_seed() {
+    for randomizer in [
+        RandomizerKind::SevenBag,
+        RandomizerKind::FourteenBag,
+        RandomizerKind::Random,
+        RandomizerKind::Nes,
+        RandomizerKind::Tgm,
+    ] {
+        let config = GameConfig {
+            randomizer,
+            ..GameConfig::default()
+        };
+        let mut a = GameState::new(77, config);
+        let mut b = GameState::new(77, config);
+        for _ in 0..50 {
+            a.spawn_next();
+            b.spawn_next();
+            assert_eq!(a.active.kind, b.active.kind);
+        }
+        assert_eq!(a.next_queue, b.next_queue);
+
+        let mut restored = GameState::from_snapshot(&a.to_snapshot()).expect("decodes");
+        for _ in 0..50 {
+            a.spawn_next();
+            restored.spawn_next();
+            assert_eq!(a.active.kind, restored.active.kind);
+        }
+    }
+}
@@ -0,0 +1,161 @@
+use gpui_tetris::game::codec::DecodeError;
+use gpui_tetris::game::input::GameAction;
+use gpui_tetris::game::replay::{Replay, ReplayEvent, ReplayPlayer};
+use gpui_tetris::game::state::{GameConfig, GameState, Ruleset};
+
+fn recorded_game(seed: u64) -> GameState {
+    let config = GameConfig {
+        ruleset: Ruleset::Modern,
+        ..GameConfig::default()
+    };
+    let mut state = GameState::new(seed, config);
+    state.start_recording();
+
+    let script = [
+        GameAction::MoveLeft,
+        GameAction::RotateCw,
+        GameAction::HardDrop,
+        GameAction::Hold,
+        GameAction::MoveRight,
+        GameAction::MoveRight,
+        GameAction::SoftDrop,
+        GameAction::RotateCcw,
+    ];
+    for step in 0..400usize {
+        state.step(step % 7 == 0);
+        if step % 5 == 0 {
+            state.apply_action(script[(step / 5) % script.len()]);
+        }
+    }
+    state
+}
+
+#[test]
+fn recording_tags_inputs_with_sim_frames() {
+    let state = recorded_game(1);
+    let replay = state.replay().expect("recording");
+
+    assert_eq!(replay.seed, 1);
+    assert!(
+        replay
+            .events
+            .iter()
+            .any(|event| matches!(event, ReplayEvent::Action { .. }))
+    );
+    assert!(
+        replay
+            .events
+            .iter()
+            .any(|event| matches!(event, ReplayEvent::SoftDrop { held: true, .. }))
+    );
+    assert_eq!(replay.frames, state.frame);
+    assert_eq!(replay.duration_ms(), state.sim_time_ms);
+    assert!(
+        replay
+            .events
+            .windows(2)
+            .all(|pair| pair[0].frame() <= pair[1].frame())
+    );
+}
+
+#[test]
+fn playback_rebuilds_final_board_and_score() {
+    let state = recorded_game(42);
+    let replay = state.replay().expect("recording").clone();
+
+    let bytes = replay.to_bytes();
+    let decoded = Replay::from_bytes(&bytes).expect("decodes");
+    let played = decoded.play();
+
+    assert_eq!(played.score, state.score);
+    assert_eq!(played.lines, state.lines);
+    assert_eq!(played.active, state.active);
+    assert_eq!(played.hold, state.hold);
+    for (a, b) in played
+        .board
+        .cells
+        .iter()
+        .flatten()
+        .zip(state.board.cells.iter().flatten())
+    {
+        assert_eq!(a, b);
+    }
+}
+
+#[test]
+fn games_set_up_on_a_field_replay_from_it() {
+    let mut state = GameState::builder()
+        .seed(11)
+        .board("GGGG.GGGGG\nGGG.GGGGGG")
+        .build()
+        .expect("builds");
+    state.start_recording();
+    for step in 0..200usize {
+        state.step(false);
+        if step % 20 == 0 {
+            state.apply_action(GameAction::HardDrop);
+        }
+    }
+
+    let replay = state.replay().expect("recording");
+    let decoded = Replay::from_bytes(&replay.to_bytes()).expect("decodes");
+    assert_eq!(decoded.field, replay.field);
+    let played = decoded.play();
+    assert_eq!(played.board, state.board);
+    assert_eq!(played.lines, state.lines);
+    assert!(ReplayPlayer::new(decoded).state().board.garbage_rows() > 0);
+}
+
+#[test]
+fn replay_file_is_compact() {
+    let state = recorded_game(3);
+    let replay = state.replay().expect("recording");
+    let bytes = replay.to_bytes();
+
+    assert!(bytes.len() < replay.events.len() * 3 + 128);
+}
+
+#[test]
+fn player_seeks_backwards_and_forwards() {
+    let state = recorded_game(9);
+    let replay = state.replay().expect("recording").clone();
+    let mut player = ReplayPlayer::new(replay);
+
+    player.seek(player.duration_ms());
+    assert!(player.is_finished());
+    assert_eq!(player.state().score, state.score);
+
+    player.seek(1000);
+    assert_eq!(player.position_ms(), 1000);
+    assert_eq!(player.state().frame, 1000 / 16);
+    assert!(!player.is_finished());
+
+    player.set_speed(2.0);
+    player.advance(500);
+    assert_eq!(player.position_ms(), 2000);
+
+    player.toggle_pause();
+    player.advance(500);
+    assert_eq!(player.position_ms(), 2000);
+
+    player.seek(u64::MAX);
+    assert_eq!(player.state().active, state.active);
+}
+
+#[test]
+fn restart_starts_a_new_recording() {
+    let mut state = recorded_game(5);
+    state.apply_action(GameAction::Restart);
+
+    let replay = state.replay().expect("still recording");
+    assert!(replay.events.is_empty());
+    assert_eq!(replay.seed, state.seed());
+}
+
+#[test]
+fn replay_rejects_unknown_header() {
+    assert_eq!(
+        Replay::from_bytes(b"GTSV\x01\x00\x00\x00").unwrap_err(),
+        DecodeError::BadMagic
+    );
+}
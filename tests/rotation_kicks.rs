@@ -1,4 +1,4 @@
-use gpui_tetris::game::board::{BOARD_WIDTH, Board};
+use gpui_tetris::game::board::{BOARD_HEIGHT, BOARD_WIDTH, Board};
 use gpui_tetris::game::input::GameAction;
 use gpui_tetris::game::pieces::{Rotation, Tetromino, TetrominoType};
 use gpui_tetris::game::state::{GameConfig, GameState};
@@ -22,7 +22,7 @@ fn rotate_kicks_inside_right_wall() {
     state.active.rotation = Rotation::North;
 
     state.apply_action(GameAction::RotateCw);
-    assert!(state.active.x <= BOARD_WIDTH as i32 - 1);
+    assert!(state.active.x < BOARD_WIDTH as i32);
 }
 
 #[test]
@@ -60,10 +60,11 @@ fn rotate_fails_when_all_kicks_blocked() {
 fn rotate_kicks_up_from_floor() {
     let mut state = GameState::new(5, GameConfig::default());
     state.board = Board::new();
-    state.active = Tetromino::new(TetrominoType::I, 3, 17);
+    let floor_y = BOARD_HEIGHT as i32 - 3;
+    state.active = Tetromino::new(TetrominoType::I, 3, floor_y);
     state.active.rotation = Rotation::North;
 
     state.apply_action(GameAction::RotateCw);
     assert_eq!(state.active.rotation, Rotation::East);
-    assert!(state.active.y < 17);
+    assert!(state.active.y < floor_y);
 }
@@ -0,0 +1,174 @@
+use gpui_tetris::game::board::Board;
+use gpui_tetris::game::input::GameAction;
+use gpui_tetris::game::pieces::{Rotation, Tetromino, TetrominoType};
+use gpui_tetris::game::rotation::RotationSystem;
+use gpui_tetris::game::state::{GameConfig, GameState};
+
+fn state_with(
+    system: RotationSystem,
+    kind: TetrominoType,
+    x: i32,
+    y: i32,
+    rotation: Rotation,
+) -> GameState {
+    let config = GameConfig {
+        rotation_system: system,
+        ..GameConfig::default()
+    };
+    let mut state = GameState::new(1, config);
+    state.board = Board::new();
+    state.active = Tetromino::new(kind, x, y).with_system(system);
+    state.active.rotation = rotation;
+    state
+}
+
+fn fill(state: &mut GameState, x: usize, y: usize) {
+    state.board.cells[y][x].filled = true;
+    state.board.cells[y][x].kind = Some(TetrominoType::O);
+}
+
+#[test]
+fn srs_is_the_default_system() {
+    let state = GameState::new(1, GameConfig::default());
+    assert_eq!(state.rotation_system, RotationSystem::Srs);
+    assert_eq!(state.active.system, RotationSystem::Srs);
+}
+
+#[test]
+fn spawned_pieces_use_the_configured_system() {
+    for system in RotationSystem::ALL {
+        let config = GameConfig {
+            rotation_system: system,
+            ..GameConfig::default()
+        };
+        let mut state = GameState::new(2, config);
+        assert_eq!(state.active.system, system);
+        state.apply_action(GameAction::HardDrop);
+        assert_eq!(state.active.system, system);
+    }
+}
+
+#[test]
+fn ars_t_spawns_flat_side_up() {
+    assert_eq!(
+        RotationSystem::Ars.shape(TetrominoType::T, Rotation::North),
+        [(0, 1), (1, 1), (2, 1), (1, 2)]
+    );
+}
+
+#[test]
+fn ars_kicks_off_left_wall() {
+    let mut state = state_with(RotationSystem::Ars, TetrominoType::T, -1, 5, Rotation::West);
+
+    state.apply_action(GameAction::RotateCw);
+    assert_eq!(state.active.rotation, Rotation::North);
+    assert_eq!(state.active.x, 0);
+}
+
+#[test]
+fn ars_centre_column_rule_refuses_kick() {
+    let mut state = state_with(RotationSystem::Ars, TetrominoType::T, 3, 5, Rotation::North);
+    fill(&mut state, 4, 5);
+
+    state.apply_action(GameAction::RotateCw);
+    assert_eq!(state.active.rotation, Rotation::North);
+    assert_eq!(state.active.x, 3);
+}
+
+#[test]
+fn ars_kicks_when_side_column_is_blocked() {
+    let mut state = state_with(RotationSystem::Ars, TetrominoType::J, 3, 5, Rotation::North);
+    fill(&mut state, 3, 7);
+
+    state.apply_action(GameAction::RotateCw);
+    assert_eq!(state.active.rotation, Rotation::East);
+    assert_eq!(state.active.x, 4);
+}
+
+#[test]
+fn ars_i_piece_never_kicks() {
+    let mut state = state_with(RotationSystem::Ars, TetrominoType::I, -2, 5, Rotation::East);
+
+    state.apply_action(GameAction::RotateCw);
+    assert_eq!(state.active.rotation, Rotation::East);
+    assert_eq!(state.active.x, -2);
+}
+
+#[test]
+fn nes_rotation_never_kicks() {
+    let mut state = state_with(RotationSystem::Nes, TetrominoType::L, -1, 5, Rotation::West);
+
+    state.apply_action(GameAction::RotateCw);
+    assert_eq!(state.active.rotation, Rotation::West);
+    assert_eq!(state.active.x, -1);
+}
+
+#[test]
+fn nes_two_state_pieces_are_right_handed() {
+    let nes = RotationSystem::Nes;
+    for kind in [TetrominoType::I, TetrominoType::S, TetrominoType::Z] {
+        assert_eq!(
+            nes.shape(kind, Rotation::North),
+            nes.shape(kind, Rotation::South)
+        );
+        assert_eq!(
+            nes.shape(kind, Rotation::East),
+            nes.shape(kind, Rotation::West)
+        );
+        let right = nes
+            .shape(kind, Rotation::East)
+            .iter()
+            .map(|&(x, _)| x)
+            .max();
+        assert_eq!(right, Some(2), "{kind:?} should lean right");
+    }
+}
+
+#[test]
+fn srs_plus_rotates_180() {
+    let mut state = state_with(
+        RotationSystem::SrsPlus,
+        TetrominoType::T,
+        3,
+        5,
+        Rotation::North,
+    );
+
+    state.apply_action(GameAction::Rotate180);
+    assert_eq!(state.active.rotation, Rotation::South);
+    assert_eq!((state.active.x, state.active.y), (3, 5));
+}
+
+#[test]
+fn srs_plus_180_kicks_around_blocker() {
+    let mut state = state_with(
+        RotationSystem::SrsPlus,
+        TetrominoType::T,
+        3,
+        5,
+        Rotation::North,
+    );
+    fill(&mut state, 4, 7);
+
+    state.apply_action(GameAction::Rotate180);
+    assert_eq!(state.active.rotation, Rotation::South);
+    assert_eq!((state.active.x, state.active.y), (4, 5));
+}
+
+#[test]
+fn rotate_180_is_ignored_without_srs_plus() {
+    let mut state = state_with(RotationSystem::Srs, TetrominoType::T, 3, 5, Rotation::North);
+
+    state.apply_action(GameAction::Rotate180);
+    assert_eq!(state.active.rotation, Rotation::North);
+}
+
+#[test]
+fn snapshot_keeps_rotation_system() {
+    let mut state = state_with(RotationSystem::Ars, TetrominoType::T, 3, 5, Rotation::East);
+    state.apply_action(GameAction::MoveLeft);
+
+    let restored = GameState::from_snapshot(&state.to_snapshot()).expect("decodes");
+    assert_eq!(restored.rotation_system, RotationSystem::Ars);
+    assert_eq!(restored.active, state.active);
+}
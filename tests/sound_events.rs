@@ -1,6 +1,14 @@
 use gpui_tetris::game::input::GameAction;
-use gpui_tetris::game::pieces::{Rotation, Tetromino, TetrominoType};
-use gpui_tetris::game::state::{GameConfig, GameState, SoundEvent, TSpinKind};
+use gpui_tetris::game::pieces::{Rotation, Tetromino, TetrominoType, spawn_position};
+use gpui_tetris::game::state::{GameConfig, GameEvent, GameState, SoundEvent, TSpinKind};
+
+fn sounds(state: &mut GameState) -> Vec<SoundEvent> {
+    state
+        .take_events()
+        .iter()
+        .filter_map(|event| event.sound())
+        .collect()
+}
 
 #[test]
 fn emits_sound_events_for_actions() {
@@ -13,30 +21,46 @@ fn emits_sound_events_for_actions() {
     state.apply_action(GameAction::SoftDrop);
     state.apply_action(GameAction::HardDrop);
 
-    let events = state.take_sound_events();
+    let events = sounds(&mut state);
     assert!(events.contains(&SoundEvent::Move));
     assert!(events.contains(&SoundEvent::Rotate));
     assert!(events.contains(&SoundEvent::SoftDrop));
     assert!(events.contains(&SoundEvent::HardDrop));
 }
 
+#[test]
+fn blocked_moves_are_silent() {
+    let mut state = GameState::new(1, GameConfig::default());
+    for _ in 0..state.board.width() {
+        state.apply_action(GameAction::MoveLeft);
+    }
+    state.take_events();
+
+    state.apply_action(GameAction::MoveLeft);
+
+    let events = state.take_events();
+    assert_eq!(events, vec![GameEvent::MoveBlocked { dx: -1 }]);
+    assert_eq!(events[0].sound(), None);
+}
+
 #[test]
 fn emits_line_clear_sound() {
     let mut state = GameState::new(2, GameConfig::default());
     state.apply_line_clear(2, TSpinKind::None);
-    let events = state.take_sound_events();
+    let events = sounds(&mut state);
     assert!(events.contains(&SoundEvent::LineClear(2)));
 }
 
 #[test]
 fn emits_game_over_sound_on_spawn_blocked() {
     let mut state = GameState::new(3, GameConfig::default());
-    state.board.cells[0][4].filled = true;
-    state.board.cells[0][4].kind = Some(TetrominoType::I);
+    let (_, spawn_y) = spawn_position();
+    state.board.cells[spawn_y as usize + 1][4].filled = true;
+    state.board.cells[spawn_y as usize + 1][4].kind = Some(TetrominoType::I);
     state.next_queue = vec![TetrominoType::O];
 
     state.spawn_next();
 
-    let events = state.take_sound_events();
+    let events = sounds(&mut state);
     assert!(events.contains(&SoundEvent::GameOver));
 }
@@ -0,0 +1,132 @@
+use gpui_tetris::game::board::{BUFFER_HEIGHT, Board};
+use gpui_tetris::game::input::GameAction;
+use gpui_tetris::game::pieces::{Rotation, Tetromino, TetrominoType};
+use gpui_tetris::game::rotation::RotationSystem;
+use gpui_tetris::game::state::{GameConfig, GameState, Ruleset, TSpinKind};
+
+fn modern_state(all_spin: bool) -> GameState {
+    let mut state = GameState::new(
+        1,
+        GameConfig {
+            ruleset: Ruleset::Modern,
+            all_spin,
+            ..GameConfig::default()
+        },
+    );
+    state.board = Board::new();
+    state
+}
+
+// Rows below are counted from the top of the visible field.
+const TOP: i32 = BUFFER_HEIGHT as i32;
+
+fn fill(state: &mut GameState, cells: &[(usize, usize)]) {
+    for &(x, y) in cells {
+        let y = y + BUFFER_HEIGHT;
+        state.board.cells[y][x].filled = true;
+        state.board.cells[y][x].kind = Some(TetrominoType::O);
+    }
+}
+
+#[test]
+fn tst_kick_upgrades_mini_to_full() {
+    let mut state = modern_state(false);
+    state.active = Tetromino::new(TetrominoType::T, -1, TOP);
+    state.active.rotation = Rotation::East;
+    // Only the last kick fits, and the landing spot has a single front corner.
+    fill(&mut state, &[(1, 0), (2, 2), (0, 4), (2, 4)]);
+
+    state.apply_action(GameAction::RotateCcw);
+    assert_eq!(state.active.rotation, Rotation::North);
+    assert_eq!((state.active.x, state.active.y), (0, TOP + 2));
+
+    state.apply_action(GameAction::HardDrop);
+    assert_eq!(state.score, 400);
+}
+
+#[test]
+fn half_turn_kick_does_not_upgrade_mini() {
+    let mut state = modern_state(false);
+    state.active = Tetromino::new(TetrominoType::T, 0, TOP).with_system(RotationSystem::SrsPlus);
+    state.active.rotation = Rotation::East;
+    // The 180° turn lands on its fifth kick with one front corner filled.
+    fill(&mut state, &[(0, 1), (2, 2), (0, 4), (2, 4)]);
+
+    state.apply_action(GameAction::Rotate180);
+    assert_eq!(state.active.rotation, Rotation::West);
+    assert_eq!((state.active.x, state.active.y), (0, TOP + 2));
+
+    state.apply_action(GameAction::HardDrop);
+    assert_eq!(state.score, 100);
+}
+
+#[test]
+fn ars_corners_follow_the_bottom_aligned_t() {
+    let mut state = modern_state(false);
+    state.active = Tetromino::new(TetrominoType::T, 0, TOP).with_system(RotationSystem::Ars);
+    state.active.rotation = Rotation::West;
+    // The up-pointing ARS T sits a row low in its box, centred on (1, 2).
+    fill(&mut state, &[(0, 1), (0, 3), (2, 3)]);
+
+    state.apply_action(GameAction::RotateCcw);
+    assert_eq!(state.active.rotation, Rotation::South);
+    assert_eq!((state.active.x, state.active.y), (0, TOP));
+
+    state.apply_action(GameAction::HardDrop);
+    assert_eq!(state.score, 100);
+}
+
+#[test]
+fn shifting_after_rotation_cancels_t_spin() {
+    let mut state = modern_state(false);
+    state.active = Tetromino::new(TetrominoType::T, -1, TOP);
+    state.active.rotation = Rotation::East;
+    fill(&mut state, &[(1, 0), (2, 2), (0, 4), (2, 4)]);
+
+    state.apply_action(GameAction::RotateCcw);
+    state.apply_action(GameAction::MoveLeft);
+    state.apply_action(GameAction::HardDrop);
+    assert_eq!(state.score, 0);
+}
+
+fn immobile_s_setup(state: &mut GameState) {
+    state.active = Tetromino::new(TetrominoType::S, 3, TOP + 17);
+    fill(state, &[(4, 16), (4, 19), (6, 19)]);
+}
+
+#[test]
+fn all_spin_scores_immobile_s_rotation() {
+    let mut state = modern_state(true);
+    immobile_s_setup(&mut state);
+
+    state.apply_action(GameAction::RotateCw);
+    assert_eq!(state.active.rotation, Rotation::East);
+    state.apply_action(GameAction::HardDrop);
+    assert_eq!(state.score, 100);
+}
+
+#[test]
+fn immobile_s_rotation_scores_nothing_without_all_spin() {
+    let mut state = modern_state(false);
+    immobile_s_setup(&mut state);
+
+    state.apply_action(GameAction::RotateCw);
+    state.apply_action(GameAction::HardDrop);
+    assert_eq!(state.score, 0);
+}
+
+#[test]
+fn all_spin_clears_break_back_to_back_like_minis() {
+    let mut all_spin = modern_state(true);
+    let mut mini = modern_state(true);
+    for (state, kind) in [
+        (&mut all_spin, TSpinKind::AllSpin),
+        (&mut mini, TSpinKind::Mini),
+    ] {
+        state.apply_line_clear(4, TSpinKind::None);
+        assert!(state.back_to_back);
+        state.apply_line_clear(1, kind);
+        assert!(!state.back_to_back);
+    }
+    assert_eq!(all_spin.score, mini.score);
+}
@@ -0,0 +1,161 @@
+use gpui_tetris::game::board::{BOARD_HEIGHT, BOARD_WIDTH, Board};
+use gpui_tetris::game::input::GameAction;
+use gpui_tetris::game::pieces::{Tetromino, TetrominoType};
+use gpui_tetris::game::records::PersonalBests;
+use gpui_tetris::game::state::{GameConfig, GameMode, GameState};
+
+fn state_with(mode: GameMode) -> GameState {
+    let mut state = GameState::new(
+        1,
+        GameConfig {
+            mode,
+            ..GameConfig::default()
+        },
+    );
+    state.board = Board::new();
+    state
+}
+
+fn clear_bottom_row(state: &mut GameState) {
+    for x in 4..BOARD_WIDTH {
+        state.board.cells[BOARD_HEIGHT - 1][x].filled = true;
+        state.board.cells[BOARD_HEIGHT - 1][x].kind = Some(TetrominoType::O);
+    }
+    state.active = Tetromino::new(TetrominoType::I, 0, BOARD_HEIGHT as i32 - 2);
+    state.apply_action(GameAction::HardDrop);
+}
+
+fn fresh_t(state: &mut GameState) {
+    state.active = Tetromino::new(TetrominoType::T, 3, 0);
+}
+
+#[test]
+fn sprint_ends_when_line_target_is_reached() {
+    let mut state = state_with(GameMode::Sprint { lines: 1 });
+    for _ in 0..10 {
+        state.step(false);
+    }
+    clear_bottom_row(&mut state);
+
+    assert!(state.game_over);
+    assert!(state.mode_complete);
+    assert_eq!(state.sim_time_ms, 160);
+
+    state.step(false);
+    assert_eq!(state.sim_time_ms, 160);
+}
+
+#[test]
+fn marathon_keeps_going_after_clears() {
+    let mut state = state_with(GameMode::Marathon);
+    clear_bottom_row(&mut state);
+
+    assert_eq!(state.lines, 1);
+    assert!(!state.game_over);
+    assert!(!state.mode_complete);
+}
+
+#[test]
+fn sprint_defaults_to_forty_lines() {
+    assert_eq!(GameMode::sprint(), GameMode::Sprint { lines: 40 });
+}
+
+#[test]
+fn pieces_per_second_uses_simulated_time() {
+    let mut state = state_with(GameMode::sprint());
+    for _ in 0..125 {
+        state.step(false);
+    }
+    state.apply_action(GameAction::HardDrop);
+    state.apply_action(GameAction::HardDrop);
+
+    assert_eq!(state.pieces_placed, 2);
+    assert!((state.pieces_per_second() - 1.0).abs() < 1e-9);
+}
+
+#[test]
+fn tapping_past_a_das_route_is_a_fault() {
+    let mut state = state_with(GameMode::sprint());
+    fresh_t(&mut state);
+    // x = 6 takes DAS right plus one tap left; three taps waste one press.
+    for _ in 0..3 {
+        state.apply_action(GameAction::MoveRight);
+    }
+    state.apply_action(GameAction::HardDrop);
+
+    assert_eq!(state.finesse_faults, 1);
+}
+
+#[test]
+fn das_to_the_wall_counts_as_one_press() {
+    let mut state = state_with(GameMode::sprint());
+    fresh_t(&mut state);
+    for _ in 0..BOARD_WIDTH {
+        state.apply_action(GameAction::MoveRight);
+    }
+    state.apply_action(GameAction::HardDrop);
+
+    assert_eq!(state.finesse_faults, 0);
+}
+
+#[test]
+fn spare_rotations_are_faults() {
+    let mut state = state_with(GameMode::sprint());
+    fresh_t(&mut state);
+    for _ in 0..4 {
+        state.apply_action(GameAction::RotateCw);
+    }
+    state.apply_action(GameAction::HardDrop);
+
+    assert_eq!(state.finesse_faults, 4);
+}
+
+#[test]
+fn soft_dropped_pieces_are_not_judged() {
+    let mut state = state_with(GameMode::sprint());
+    fresh_t(&mut state);
+    state.apply_action(GameAction::RotateCw);
+    state.apply_action(GameAction::RotateCcw);
+    state.apply_action(GameAction::SoftDrop);
+    state.apply_action(GameAction::HardDrop);
+
+    assert_eq!(state.finesse_faults, 0);
+}
+
+#[test]
+fn snapshot_keeps_mode_and_sprint_stats() {
+    let mut state = state_with(GameMode::Sprint { lines: 20 });
+    fresh_t(&mut state);
+    state.apply_action(GameAction::RotateCw);
+    state.apply_action(GameAction::RotateCcw);
+    state.apply_action(GameAction::HardDrop);
+    state.apply_action(GameAction::MoveLeft);
+
+    let restored = GameState::from_snapshot(&state.to_snapshot()).expect("decodes");
+    assert_eq!(restored.mode, GameMode::Sprint { lines: 20 });
+    assert_eq!(restored.pieces_placed, 1);
+    assert_eq!(restored.finesse_faults, 2);
+}
+
+#[test]
+fn personal_bests_keep_fastest_time_per_target() {
+    let mut bests = PersonalBests::new();
+    assert!(bests.record_sprint(40, 61_000));
+    assert!(!bests.record_sprint(40, 65_000));
+    assert!(bests.record_sprint(40, 58_250));
+    assert!(bests.record_sprint(20, 30_000));
+
+    assert_eq!(bests.sprint_best(40), Some(58_250));
+    assert_eq!(bests.sprint_best(20), Some(30_000));
+    assert_eq!(bests.sprint_best(100), None);
+}
+
+#[test]
+fn personal_bests_round_trip_through_text() {
+    let mut bests = PersonalBests::new();
+    bests.record_sprint(40, 58_250);
+    bests.record_sprint(20, 30_000);
+
+    let text = format!("{}garbage\nsprint.x=1\n", bests.to_text());
+    assert_eq!(PersonalBests::from_text(&text), bests);
+}
@@ -0,0 +1,99 @@
+use gpui_tetris::game::board::{BOARD_HEIGHT, BOARD_WIDTH, BUFFER_HEIGHT, Board};
+use gpui_tetris::game::input::GameAction;
+use gpui_tetris::game::pieces::{Tetromino, TetrominoType, spawn_position};
+use gpui_tetris::game::state::{GameConfig, GameEvent, GameState, TopOutReason};
+
+fn state_with(partial_lock_out: bool) -> GameState {
+    let mut state = GameState::new(
+        1,
+        GameConfig {
+            partial_lock_out,
+            ..GameConfig::default()
+        },
+    );
+    state.board = Board::new();
+    state
+}
+
+/// Fills rows `from..` up to the floor, leaving the left column open so
+/// nothing clears.
+fn stack_from(state: &mut GameState, from: usize) {
+    for y in from..BOARD_HEIGHT {
+        for x in 1..BOARD_WIDTH {
+            state.board.cells[y][x].filled = true;
+            state.board.cells[y][x].kind = Some(TetrominoType::O);
+        }
+    }
+}
+
+fn drop_o(state: &mut GameState, x: i32) {
+    let (_, spawn_y) = spawn_position();
+    state.active = Tetromino::new(TetrominoType::O, x, spawn_y);
+    state.apply_action(GameAction::HardDrop);
+}
+
+#[test]
+fn pieces_spawn_in_the_vanish_zone() {
+    let state = GameState::new(1, GameConfig::default());
+    let blocks = state.active.blocks(state.active.rotation);
+    assert!(
+        blocks
+            .iter()
+            .all(|&(_, dy)| state.board.is_hidden_row(state.active.y + dy))
+    );
+    assert!(!state.board.is_hidden_row(BUFFER_HEIGHT as i32));
+}
+
+#[test]
+fn spawning_into_the_stack_is_a_block_out() {
+    let mut state = state_with(false);
+    let (_, spawn_y) = spawn_position();
+    state.board.cells[spawn_y as usize + 1][4].filled = true;
+    state.next_queue = vec![TetrominoType::O];
+
+    state.spawn_next();
+
+    assert!(state.game_over);
+    assert_eq!(state.top_out, Some(TopOutReason::BlockOut));
+}
+
+#[test]
+fn locking_fully_above_the_field_is_a_lock_out() {
+    let mut state = state_with(false);
+    stack_from(&mut state, BUFFER_HEIGHT);
+    drop_o(&mut state, 3);
+
+    assert!(state.game_over);
+    assert!(!state.mode_complete);
+    assert_eq!(state.top_out, Some(TopOutReason::LockOut));
+    assert!(state.take_events().contains(&GameEvent::TopOut {
+        reason: TopOutReason::LockOut
+    }));
+}
+
+#[test]
+fn partial_lock_out_is_opt_in() {
+    let mut state = state_with(false);
+    // Clear of the spawn columns so the next piece still fits.
+    stack_from(&mut state, BUFFER_HEIGHT + 1);
+    drop_o(&mut state, 6);
+    assert!(!state.game_over);
+    assert_eq!(state.top_out, None);
+
+    let mut state = state_with(true);
+    stack_from(&mut state, BUFFER_HEIGHT + 1);
+    drop_o(&mut state, 6);
+    assert!(state.game_over);
+    assert_eq!(state.top_out, Some(TopOutReason::PartialLockOut));
+}
+
+#[test]
+fn snapshot_keeps_top_out_reason() {
+    let mut state = state_with(true);
+    stack_from(&mut state, BUFFER_HEIGHT);
+    drop_o(&mut state, 3);
+
+    let restored = GameState::from_snapshot(&state.to_snapshot()).expect("decodes");
+    assert!(restored.partial_lock_out);
+    assert_eq!(restored.top_out, Some(TopOutReason::LockOut));
+}
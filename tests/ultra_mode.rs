@@ -0,0 +1,105 @@
+use gpui_tetris::game::input::GameAction;
+use gpui_tetris::game::records::PersonalBests;
+use gpui_tetris::game::state::{GameConfig, GameEvent, GameMode, GameState};
+
+fn ultra_state(duration_ms: u64) -> GameState {
+    GameState::new(
+        1,
+        GameConfig {
+            mode: GameMode::Ultra { duration_ms },
+            ..GameConfig::default()
+        },
+    )
+}
+
+fn countdown_ticks(state: &mut GameState) -> usize {
+    state
+        .take_events()
+        .iter()
+        .filter(|event| **event == GameEvent::CountdownTick)
+        .count()
+}
+
+#[test]
+fn ultra_defaults_to_two_minutes() {
+    assert_eq!(
+        GameMode::ultra(),
+        GameMode::Ultra {
+            duration_ms: 120_000
+        }
+    );
+}
+
+#[test]
+fn ultra_ends_when_the_clock_runs_out() {
+    let mut state = ultra_state(1_000);
+    state.apply_action(GameAction::HardDrop);
+    let score = state.score;
+
+    state.tick(999, false);
+    assert!(!state.game_over);
+    assert_eq!(state.time_remaining_ms(), Some(1));
+
+    state.tick(500, false);
+    assert!(state.game_over);
+    assert!(state.mode_complete);
+    assert_eq!(state.sim_time_ms, 1_000);
+    assert_eq!(state.time_remaining_ms(), Some(0));
+    assert_eq!(state.score, score);
+
+    state.apply_action(GameAction::HardDrop);
+    assert_eq!(state.score, score);
+}
+
+#[test]
+fn ultra_ticks_through_the_last_ten_seconds() {
+    let mut state = ultra_state(30_000);
+    state.tick(19_990, false);
+    assert!(!state.time_warning());
+    assert_eq!(countdown_ticks(&mut state), 0);
+
+    let mut ticks = 0;
+    while !state.game_over {
+        state.tick(16, false);
+        ticks += countdown_ticks(&mut state);
+        if !state.game_over {
+            assert!(state.time_warning());
+        }
+    }
+    assert!(state.mode_complete);
+    assert_eq!(ticks, 10);
+}
+
+#[test]
+fn marathon_and_sprint_have_no_clock() {
+    let state = GameState::new(1, GameConfig::default());
+    assert_eq!(state.time_remaining_ms(), None);
+    assert!(!state.time_warning());
+}
+
+#[test]
+fn snapshot_keeps_ultra_duration() {
+    let mut state = ultra_state(60_000);
+    state.tick(5_000, false);
+
+    let restored = GameState::from_snapshot(&state.to_snapshot()).expect("decodes");
+    assert_eq!(
+        restored.mode,
+        GameMode::Ultra {
+            duration_ms: 60_000
+        }
+    );
+    assert_eq!(restored.time_remaining_ms(), Some(55_000));
+}
+
+#[test]
+fn ultra_bests_keep_highest_score_per_duration() {
+    let mut bests = PersonalBests::new();
+    assert!(bests.record_ultra(120_000, 9_000));
+    assert!(!bests.record_ultra(120_000, 8_000));
+    assert!(bests.record_ultra(180_000, 4_000));
+    assert_eq!(bests.ultra_best(120_000), Some(9_000));
+
+    bests.record_sprint(40, 60_000);
+    assert_eq!(PersonalBests::from_text(&bests.to_text()), bests);
+}